@@ -0,0 +1,39 @@
+//! Draws the walls a player can walk through, `+` cells, with their colors
+//! inverted through `App::with_column_effect`, so the secret passages in this
+//! map give themselves away.
+
+use fpasciisimulator::{App, KeyBindings, Life, Map, Player, Texture, World};
+use pixel_canvas::Color;
+
+const MAP: &str = "
+    ##########
+    #........#
+    #..####..#
+    #..#..+..#
+    #..#..#..#
+    #..++##..#
+    #........#
+    ##########";
+
+fn main() {
+    let mut map = Map::parse(MAP).expect("the map is well formed");
+    map.textures.insert('#', Texture::bricks(64));
+    map.textures.insert('+', Texture::bricks(64));
+    let world = World::new(map, Player::new(1.5, 1.5, 0.5), Life::new(std::f64::consts::FRAC_PI_4, 16.0));
+
+    App::new(world, KeyBindings::classic())
+        .with_column_effect(|map, hit, column| {
+            // most columns aren't looking at a secret wall, leave them be
+            let Some((x, y)) = hit.cell else {
+                return;
+            };
+            if map.glyph(x, y) != '+' {
+                return;
+            }
+            for y in column.wall_rows() {
+                let Color { r, g, b } = column.get(y);
+                column.set(y, Color { r: 255 - r, g: 255 - g, b: 255 - b });
+            }
+        })
+        .run(512, 512);
+}
@@ -0,0 +1,24 @@
+//! Draws a translucent colored panel over the bottom right corner of the
+//! window through `App::with_overlay`.
+
+use fpasciisimulator::maps::demo_map;
+use fpasciisimulator::{App, KeyBindings, Life, Player, Texture, World};
+use pixel_canvas::{Blend, Color};
+
+fn main() {
+    let mut map = demo_map(0);
+    map.textures.insert('#', Texture::bricks(64));
+    let world = World::new(map, Player::new(8.5, 8.5, 0.0), Life::new(std::f64::consts::FRAC_PI_4, 16.0));
+
+    App::new(world, KeyBindings::classic())
+        .with_overlay(|buffer, width, _height| {
+            // rows count up from the bottom of the window
+            for y in 16..96 {
+                for x in width - 176..width - 16 {
+                    let pixel = &mut buffer[y * width + x];
+                    *pixel = pixel.blend(Color { r: 40, g: 90, b: 220 }, 0.5);
+                }
+            }
+        })
+        .run(512, 512);
+}
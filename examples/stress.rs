@@ -0,0 +1,35 @@
+//! Walks a player around every built-in map on random input and reports any
+//! panic, position that isn't a number or step off the map. Slow, for
+//! running by hand after touching movement or rendering:
+//!
+//!     cargo run --release --features stress --example stress -- 20000 7
+//!
+//! The first argument is frames per map, the second the seed. A failure
+//! prints the seed and map, running again with the same ones replays it.
+
+use fpasciisimulator::maps::{demo_map, DEMO_MAPS};
+use fpasciisimulator::stress::run_stress;
+use fpasciisimulator::{Texture, WorldBuilder};
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let frames = args.next().and_then(|arg| arg.parse().ok()).unwrap_or(10_000);
+    let seed = args.next().and_then(|arg| arg.parse().ok()).unwrap_or(1);
+
+    let mut failed = false;
+    for index in 0..DEMO_MAPS.len() {
+        let mut map = demo_map(index);
+        map.textures.insert('#', Texture::bricks(16));
+        let mut world = WorldBuilder::new().map(map).build().expect("demo maps have room to spawn");
+        match run_stress(&mut world, seed, frames, 1.0 / 60.0) {
+            Ok(()) => println!("map {}: {} frames ok", index, frames),
+            Err(failure) => {
+                println!("map {}, seed {}: {}", index, seed, failure);
+                failed = true;
+            }
+        }
+    }
+    if failed {
+        std::process::exit(1);
+    }
+}
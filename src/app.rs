@@ -0,0 +1,599 @@
+use crate::ascii::{frame_to_ascii, ASCII_COLUMNS, ASCII_ROWS};
+use crate::bindings::{Action, KeyBindings};
+use crate::column::{ColumnHook, ColumnPixels};
+use crate::font::{draw_text, line_height};
+use crate::framelog::FrameLog;
+#[cfg(feature = "gamepad")]
+use crate::gamepad::{no_gamepad, GamepadMapping, Sticks};
+use crate::input::{InputState, MouseLook};
+use crate::keyboard::KeyboardState;
+use crate::map::Map;
+use crate::maps::{demo_map, DEMO_MAPS};
+use crate::overlay::{render_compass, render_distances, render_help, render_minimap, Layer, Layers, DISTANCE_COLUMNS};
+use crate::player::Player;
+use crate::ppm::save_ppm;
+use crate::ray::RayHit;
+use crate::render::{first_person, render_scaled, Extras, Renderer, Scratch, Viewport};
+use crate::settings::Settings;
+use crate::stats::{FrameStats, SessionStats};
+use crate::taa::TemporalAa;
+use crate::timestep::FixedTimestep;
+use crate::vignette::Vignette;
+use crate::world::{Seat, World};
+use pixel_canvas::input::glutin::event::VirtualKeyCode;
+use pixel_canvas::{Canvas, Color};
+use std::path::PathBuf;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// A window key outside the player bindings.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Toggle {
+    Help,
+    TopDown,
+    Depth,
+    Wireframe,
+    Minimap,
+    Compass,
+    Stats,
+    NextMap,
+    PrintAscii,
+    SavePpm,
+    HorizonLine,
+    Distances,
+    FreezeRays,
+    TemporalAa,
+    Vignette,
+}
+
+/// The window's own keys, which the help overlay lists too.
+pub const TOGGLES: &[(VirtualKeyCode, Toggle, &str)] = &[
+    (VirtualKeyCode::F1, Toggle::Help, "show or hide this help"),
+    (VirtualKeyCode::Tab, Toggle::TopDown, "top-down map"),
+    (VirtualKeyCode::F3, Toggle::Depth, "depth buffer view"),
+    (VirtualKeyCode::F4, Toggle::Wireframe, "wireframe walls"),
+    (VirtualKeyCode::F5, Toggle::Distances, "wall distances of a few columns"),
+    (VirtualKeyCode::F6, Toggle::FreezeRays, "freeze the ray directions"),
+    (VirtualKeyCode::F7, Toggle::TemporalAa, "temporal anti-aliasing"),
+    (VirtualKeyCode::F8, Toggle::Vignette, "darken the edges of the screen"),
+    (VirtualKeyCode::M, Toggle::Minimap, "minimap"),
+    (VirtualKeyCode::C, Toggle::Compass, "compass"),
+    (VirtualKeyCode::F2, Toggle::Stats, "ray counts"),
+    (VirtualKeyCode::H, Toggle::HorizonLine, "horizon line"),
+    (VirtualKeyCode::N, Toggle::NextMap, "next map"),
+    (VirtualKeyCode::P, Toggle::PrintAscii, "print the frame as text"),
+    (VirtualKeyCode::F12, Toggle::SavePpm, "save the frame as a PPM image"),
+];
+
+/// Custom drawing over each finished frame, see `App::with_overlay`.
+pub type Overlay = Box<dyn FnMut(&mut [Color], usize, usize)>;
+
+/// Where a gamepad's sticks are this frame, see `App::with_gamepad`.
+#[cfg(feature = "gamepad")]
+pub type GamepadPoll = Box<dyn FnMut() -> Option<Sticks>>;
+
+/// The interactive window: steps the world from keyboard input and draws it with the overlays.
+pub struct App {
+    pub world: World,
+    pub bindings: KeyBindings,
+    pub renderer: Renderer,
+    /// Overlays drawn over the scene, bottom first; the minimap, compass and distances only show in a single 3D view.
+    pub layers: Layers,
+    /// Columns `Layer::Distances` prints, see `render_distances`.
+    pub distance_columns: Vec<f64>,
+    /// Toggled with F7, for the single player 3D view.
+    pub temporal_aa: Option<TemporalAa>,
+    /// Toggled with F8, for a single player's view, under the overlays.
+    pub vignette: Option<Vignette>,
+    /// Reuse the last scene under fresh overlays while nothing in it changes; column effects, temporal AA and split screen always draw.
+    pub skip_unchanged_frames: bool,
+    /// Turns the first player with the mouse; a sensitivity of 0.0 leaves the mouse out.
+    pub mouse_look: MouseLook,
+    pub stats: FrameStats,
+    /// Which of `DEMO_MAPS` is loaded, cycled with N.
+    pub map_index: usize,
+    timestep: FixedTimestep,
+    last_frame: Instant,
+    overlay: Option<Overlay>,
+    column_effect: Option<ColumnHook>,
+    /// Per column distances of the last 3D view drawn, for `Layer::Distances`.
+    column_depths: Vec<f64>,
+    /// What each frame draws through on its way into the window.
+    scratch: Scratch,
+    second: Option<(Seat, KeyBindings)>,
+    frame_log: Option<FrameLog>,
+    #[cfg(feature = "gamepad")]
+    gamepad: (GamepadMapping, GamepadPoll),
+    stats_file: Option<PathBuf>,
+    session: SessionStats,
+    settings_file: Option<PathBuf>,
+    /// What the settings file gets back for bindings in place of `with_session_bindings` ones.
+    saved_bindings: Option<KeyBindings>,
+    /// The last scene before the overlays and what it was drawn from, for `skip_unchanged_frames`.
+    scene: Vec<Color>,
+    scene_drawn_from: Option<(Player, Renderer, usize, usize)>,
+    reused_scene: bool,
+}
+
+impl App {
+    pub fn new(world: World, bindings: KeyBindings) -> Self {
+        Self {
+            world,
+            bindings,
+            renderer: Renderer::FirstPerson,
+            layers: Layers::default(),
+            distance_columns: DISTANCE_COLUMNS.to_vec(),
+            temporal_aa: None,
+            vignette: None,
+            skip_unchanged_frames: false,
+            mouse_look: MouseLook::default(),
+            stats: FrameStats::default(),
+            map_index: 0,
+            timestep: FixedTimestep::default(),
+            last_frame: Instant::now(),
+            overlay: None,
+            column_effect: None,
+            column_depths: Vec::new(),
+            scratch: Scratch::default(),
+            second: None,
+            frame_log: None,
+            #[cfg(feature = "gamepad")]
+            gamepad: (GamepadMapping::default(), Box::new(no_gamepad)),
+            stats_file: None,
+            session: SessionStats::default(),
+            settings_file: None,
+            saved_bindings: None,
+            scene: Vec::new(),
+            scene_drawn_from: None,
+            reused_scene: false,
+        }
+    }
+
+    /// Draw over every frame where `Layer::Custom` is, pixel `(x, y)` at `y * width + x` with `y` up from the bottom.
+    pub fn with_overlay(mut self, overlay: impl FnMut(&mut [Color], usize, usize) + 'static) -> Self {
+        self.overlay = Some(Box::new(overlay));
+        self
+    }
+
+    /// Change the single player 3D view column by column as it's drawn, see `ColumnHook`.
+    pub fn with_column_effect(mut self, effect: impl FnMut(&Map, &RayHit, &mut ColumnPixels) + 'static) -> Self {
+        self.column_effect = Some(Box::new(effect));
+        self
+    }
+
+    /// Split the window with `player` on the right half; the built-in overlays are left out.
+    pub fn with_second_player(mut self, player: Player, bindings: KeyBindings) -> Self {
+        self.second = Some((Seat::new(player), bindings));
+        self
+    }
+
+    /// Steer the first player with sticks `poll` reads each frame as well as the keys; `None` leaves the keys alone.
+    #[cfg(feature = "gamepad")]
+    pub fn with_gamepad(mut self, mapping: GamepadMapping, poll: impl FnMut() -> Option<Sticks> + 'static) -> Self {
+        self.gamepad = (mapping, Box::new(poll));
+        self
+    }
+
+    /// Log the first player every frame; a failed write drops the log with a message.
+    pub fn with_frame_log(mut self, log: FrameLog) -> Self {
+        self.frame_log = Some(log);
+        self
+    }
+
+    /// Write `SessionStats::report` to `path` when the window closes.
+    pub fn with_session_stats(mut self, path: impl Into<PathBuf>) -> Self {
+        self.stats_file = Some(path.into());
+        self
+    }
+
+    /// Load settings from `path` now, anything it doesn't set keeping the app's own, and write them back when the window closes.
+    pub fn with_settings_file(mut self, path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        match std::fs::read_to_string(&path) {
+            Ok(text) => {
+                let mut settings = Settings::from_app(&self);
+                let skipped = settings.read(&text);
+                if !skipped.is_empty() {
+                    eprintln!("{}: skipped unreadable lines {:?}", path.display(), skipped);
+                }
+                settings.apply(&mut self);
+            }
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {}
+            Err(error) => eprintln!("can't read settings from {}: {}", path.display(), error),
+        }
+        self.settings_file = Some(path);
+        self
+    }
+
+    /// Play with `bindings` for this run only, the settings file keeping the ones it had; call after `with_settings_file`.
+    pub fn with_session_bindings(mut self, bindings: KeyBindings) -> Self {
+        let saved = std::mem::replace(&mut self.bindings, bindings);
+        self.saved_bindings.get_or_insert(saved);
+        self
+    }
+
+    /// Whether the last frame reused the scene before it, see `skip_unchanged_frames`.
+    pub fn reused_scene(&self) -> bool {
+        self.reused_scene
+    }
+
+    pub fn session(&self) -> &SessionStats {
+        &self.session
+    }
+
+    /// Open a `width` x `height` window and run until it's closed.
+    pub fn run(mut self, width: usize, height: usize) {
+        let canvas = Canvas::new(width, height)
+            .title("Ray Casting Simulation")
+            .state(KeyboardState::new())
+            .input(KeyboardState::handle_input);
+
+        self.last_frame = Instant::now();
+        canvas.render(move |keyboard: &mut KeyboardState, image| {
+            let width = image.width();
+            let height = image.height();
+            self.frame(keyboard, image, width, height);
+        });
+    }
+
+    /// Each player's current bindings, then the window's own keys.
+    pub fn help_lines(&self) -> Vec<String> {
+        let mut lines = vec!["keys, F1 or escape to close".to_string(), String::new()];
+
+        let mut players = vec![("player", &self.bindings)];
+        if let Some((_, bindings)) = &self.second {
+            players = vec![("player 1", &self.bindings), ("player 2", bindings)];
+        }
+        for (name, bindings) in players {
+            lines.push(format!("{}:", name));
+            for action in Action::ALL {
+                let keys: Vec<String> = bindings
+                    .keys(action)
+                    .map(|key| format!("{:?}", key))
+                    .chain(bindings.scancodes(action).map(|scancode| format!("#{:x}", scancode)))
+                    .collect();
+                if !keys.is_empty() {
+                    lines.push(format!("  {:<12} {}", keys.join("/"), action.describe()));
+                }
+            }
+            lines.push(String::new());
+        }
+
+        lines.push("window:".to_string());
+        for &(key, _, description) in TOGGLES {
+            lines.push(format!("  {:<12} {}", format!("{:?}", key), description));
+        }
+        lines
+    }
+
+    /// Advance the world by the time since the last frame and draw it into
+    /// `buffer`.
+    pub fn frame(&mut self, keyboard: &mut KeyboardState, buffer: &mut [Color], width: usize, height: usize) {
+        let just_pressed = keyboard.take_just_pressed();
+        let just_pressed_scancode = keyboard.take_just_pressed_scancode();
+        // presses wait in the world's buffer until a step can use them, the
+        // frame might not run a step at all
+        let paused = self.layers.is_enabled(Layer::Help);
+        if !paused {
+            let pressed = |bindings: &KeyBindings| -> Vec<Action> {
+                let by_key = just_pressed.into_iter().flat_map(|key| bindings.actions(key));
+                let by_scancode = just_pressed_scancode.into_iter().flat_map(|code| bindings.scancode_actions(code));
+                by_key.chain(by_scancode).collect()
+            };
+            for action in pressed(&self.bindings) {
+                self.world.seat.actions.press(action);
+            }
+            if let Some((seat, bindings)) = &mut self.second {
+                for action in pressed(bindings) {
+                    seat.actions.press(action);
+                }
+            }
+        }
+
+        let now = Instant::now();
+        let dt = now.duration_since(self.last_frame).as_secs_f64();
+        let steps = self.timestep.advance(dt);
+        // the game stands still while the help is up
+        let steps = if paused { 0 } else { steps };
+
+        let mut input = InputState::from_keyboard(keyboard, &self.bindings);
+        // mouse motion waits for a frame that steps, and turns in its first step only
+        if paused {
+            keyboard.take_mouse_motion();
+            self.mouse_look.reset();
+        } else if steps > 0 {
+            input.mouse_turn = self.mouse_look.rotation(keyboard.take_mouse_motion());
+        }
+        #[cfg(feature = "gamepad")]
+        let input = {
+            let (mapping, poll) = &mut self.gamepad;
+            poll().map_or(input, |sticks| input.combined(mapping.input(sticks)))
+        };
+        let second_input = self.second.as_ref().map(|(_, bindings)| InputState::from_keyboard(keyboard, bindings));
+        for step in 0..steps {
+            let input = if step == 0 { input } else { InputState { mouse_turn: 0.0, ..input } };
+            self.world.step(&input, self.timestep.dt);
+            if let (Some((seat, _)), Some(second_input)) = (&mut self.second, &second_input) {
+                self.world.step_seat(seat, second_input, self.timestep.dt);
+            }
+        }
+        self.last_frame = now;
+        if let Some(log) = &mut self.frame_log {
+            if let Err(error) = log.record(dt, steps, &self.world.seat.player, &input) {
+                eprintln!("stopped the frame log: {}", error);
+                self.frame_log = None;
+            }
+        }
+
+        let toggle = just_pressed.and_then(|key| TOGGLES.iter().find(|&&(bound, _, _)| bound == key)).map(|&(_, toggle, _)| toggle);
+        if paused && just_pressed == Some(VirtualKeyCode::Escape) {
+            self.layers.set_enabled(Layer::Help, false);
+        }
+
+        // the window's own keys, views and overlays
+        match toggle {
+            Some(Toggle::Help) => self.layers.toggle(Layer::Help),
+            Some(Toggle::TopDown) => self.renderer = self.renderer.toggle(Renderer::TopDown),
+            Some(Toggle::Depth) => self.renderer = self.renderer.toggle(Renderer::Depth),
+            Some(Toggle::Wireframe) => self.renderer = self.renderer.toggle(Renderer::Wireframe),
+            Some(Toggle::Minimap) => self.layers.toggle(Layer::Minimap),
+            Some(Toggle::Compass) => self.layers.toggle(Layer::Compass),
+            Some(Toggle::Stats) => self.layers.toggle(Layer::Stats),
+            Some(Toggle::Distances) => self.layers.toggle(Layer::Distances),
+            Some(Toggle::TemporalAa) => {
+                self.temporal_aa = match self.temporal_aa {
+                    Some(_) => None,
+                    None => Some(TemporalAa::default()),
+                };
+            }
+            Some(Toggle::Vignette) => {
+                self.vignette = match self.vignette {
+                    Some(_) => None,
+                    None => Some(Vignette::default()),
+                };
+            }
+            Some(Toggle::FreezeRays) => {
+                let life = &mut self.world.life;
+                life.frozen_rays = match life.frozen_rays {
+                    Some(_) => None,
+                    None => Some((self.world.seat.player.vision_angle, life.fov_angle)),
+                };
+            }
+            Some(Toggle::HorizonLine) => self.world.life.horizon_line = !self.world.life.horizon_line,
+            Some(Toggle::NextMap) => {
+                self.map_index = (self.map_index + 1) % DEMO_MAPS.len();
+                self.world.load_map(demo_map(self.map_index));
+                if let Some((seat, _)) = &mut self.second {
+                    let (x, y) = (self.world.seat.player.player_x, self.world.seat.player.player_y);
+                    self.world.swap_seat(seat);
+                    self.world.teleport(x, y, 0.0);
+                    self.world.swap_seat(seat);
+                }
+            }
+            Some(Toggle::PrintAscii) | Some(Toggle::SavePpm) | None => (),
+        }
+
+        let camera = self.world.camera(self.timestep.alpha());
+        // the built-in overlays are laid out for a single view
+        let single_view = self.second.is_none() && self.renderer == Renderer::FirstPerson;
+        let mut temporal_aa = self.temporal_aa.as_mut().filter(|_| single_view);
+        self.world.life.ray_jitter = temporal_aa.as_mut().map_or(0.0, |taa| taa.next_jitter());
+
+        // anything a toggle changes might show, so only an untouched frame counts
+        let dirty = self.world.take_dirty() || toggle.is_some();
+        let drawn_from = (camera.clone(), self.renderer, width, height);
+        self.reused_scene = self.skip_unchanged_frames
+            && !dirty
+            && self.second.is_none()
+            && self.column_effect.is_none()
+            && temporal_aa.is_none()
+            && self.scene.len() == buffer.len()
+            && self.scene_drawn_from.as_ref() == Some(&drawn_from);
+        if self.reused_scene {
+            buffer.copy_from_slice(&self.scene);
+            self.stats = FrameStats::default();
+        } else if let Some((seat, _)) = &self.second {
+            // side by side, with a black line down the middle
+            let half = width / 2;
+            let left = Viewport { x: 0, y: 0, width: half, height };
+            let right = Viewport { x: half, y: 0, width: width - half, height };
+            let second_camera = seat.camera(self.timestep.alpha());
+            let (map, life, scratch) = (&self.world.map, &self.world.life, &mut self.scratch);
+            self.stats = self.renderer.render_frame_into(buffer, width, left, scratch, map, &camera, life)
+                + self.renderer.render_frame_into(buffer, width, right, scratch, map, &second_camera, life);
+            for y in 0..height {
+                buffer[y * width + half.saturating_sub(1)] = Color { r: 0, g: 0, b: 0 };
+                buffer[y * width + half] = Color { r: 0, g: 0, b: 0 };
+            }
+        } else if self.renderer == Renderer::FirstPerson {
+            let (map, life) = (&self.world.map, &self.world.life);
+            let extras = Extras {
+                columns: self.column_effect.as_mut(),
+                depths: Some(&mut self.column_depths),
+                shaft_depths: Some(&mut self.scratch.shaft_depths),
+                ..Extras::default()
+            };
+            self.stats = render_scaled(buffer, width, height, life.internal_scale, &mut self.scratch.internal, |buffer, width, height| {
+                first_person(buffer, width, height, map, &camera, life, extras)
+            });
+        } else {
+            self.stats = self.renderer.render_with(buffer, width, height, &self.world.map, &camera, &self.world.life, &mut self.scratch);
+        }
+        if self.skip_unchanged_frames && !self.reused_scene {
+            self.scene.clear();
+            self.scene.extend_from_slice(buffer);
+            self.scene_drawn_from = Some(drawn_from);
+        }
+        if let Some(taa) = temporal_aa {
+            taa.resolve(buffer, &camera, dt);
+        } else if let Some(taa) = &mut self.temporal_aa {
+            // blending picks up afresh when back to a single 3D view
+            taa.reset();
+        }
+        if let Some(vignette) = self.vignette.as_mut().filter(|_| self.second.is_none()) {
+            vignette.apply(buffer, width, height);
+        }
+        self.session.record(dt, self.stats);
+        let layers: Vec<Layer> = self.layers.enabled().collect();
+        for layer in layers {
+            match layer {
+                Layer::Minimap if single_view => render_minimap(buffer, width, height, &self.world, &camera),
+                Layer::Compass if single_view => render_compass(buffer, width, height, camera.vision_angle),
+                Layer::Distances if single_view => {
+                    render_distances(buffer, width, height, &self.column_depths, &self.distance_columns)
+                }
+                Layer::Minimap | Layer::Compass | Layer::Distances => (),
+                Layer::Stats => {
+                    let text = format!(
+                        "rays {}  steps {}  cells {}  {:.1} cells/ray",
+                        self.stats.rays,
+                        self.stats.steps,
+                        self.stats.cells,
+                        self.stats.cells_per_ray()
+                    );
+                    let scale = (width.min(height) / 256).max(1);
+                    let top = height.saturating_sub(line_height(scale) + 4);
+                    draw_text(buffer, width, height, (4, top), &text, scale, Color { r: 255, g: 255, b: 255 });
+                }
+                Layer::Custom => {
+                    if let Some(overlay) = &mut self.overlay {
+                        overlay(buffer, width, height);
+                    }
+                }
+                Layer::Help => render_help(buffer, width, height, &self.help_lines()),
+            }
+        }
+
+        // print the frame as text
+        if toggle == Some(Toggle::PrintAscii) {
+            print!("{}", frame_to_ascii(buffer, width, height, ASCII_COLUMNS, ASCII_ROWS));
+        }
+        // and as an image, named by the time so captures don't overwrite each other
+        if toggle == Some(Toggle::SavePpm) {
+            let millis = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_millis());
+            let path = format!("frame-{}.ppm", millis);
+            match save_ppm(&path, buffer, width, height) {
+                Ok(()) => println!("saved {}", path),
+                Err(error) => eprintln!("can't save {}: {}", path, error),
+            }
+        }
+    }
+}
+
+/// Closing the window drops the app, the last chance to write the session and settings.
+impl Drop for App {
+    fn drop(&mut self) {
+        if let Some(path) = &self.settings_file {
+            let mut settings = Settings::from_app(self);
+            if let Some(bindings) = &self.saved_bindings {
+                settings.bindings = bindings.clone();
+            }
+            if let Err(error) = std::fs::write(path, settings.to_text()) {
+                eprintln!("can't write settings to {}: {}", path.display(), error);
+            }
+        }
+        let Some(path) = &self.stats_file else {
+            return;
+        };
+        if let Err(error) = std::fs::write(path, self.session.report()) {
+            eprintln!("can't write stats to {}: {}", path.display(), error);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::{MOUSE_DEAD_ZONE, MOUSE_SENSITIVITY};
+    use crate::life::Life;
+    use crate::timestep::MAX_STEPS;
+    use std::f64::consts::FRAC_PI_4;
+    use std::time::Duration;
+
+    #[test]
+    fn layer_drawn_last_wins_where_they_overlap() {
+        let map = Map::parse("#####\n#...#\n#...#\n#...#\n#####").unwrap();
+        let world = World::new(map, Player::new(2.5, 2.5, 0.0), Life::new(FRAC_PI_4, 16.0));
+        let red = Color { r: 255, g: 0, b: 0 };
+        let mut app = App::new(world, KeyBindings::classic()).with_overlay(move |buffer, _, _| buffer.fill(red));
+        app.layers.set_enabled(Layer::Compass, false);
+        let (width, height) = (100, 100);
+        let mut buffer = vec![Color { r: 0, g: 0, b: 0 }; width * height];
+        // inside the minimap in the top left corner
+        let overlap = 80 * width + 20;
+        let mut keyboard = KeyboardState::new();
+
+        app.frame(&mut keyboard, &mut buffer, width, height);
+        assert_eq!((buffer[overlap].r, buffer[overlap].g, buffer[overlap].b), (255, 0, 0));
+
+        app.layers.bring_to_front(Layer::Minimap);
+        app.frame(&mut keyboard, &mut buffer, width, height);
+        assert_ne!((buffer[overlap].r, buffer[overlap].g, buffer[overlap].b), (255, 0, 0));
+    }
+
+    #[test]
+    fn still_frame_with_nothing_changing_skips_the_scene() {
+        let map = Map::parse("#####\n#...#\n#...#\n#...#\n#####").unwrap();
+        let world = World::new(map, Player::new(2.5, 2.5, 0.0), Life::new(FRAC_PI_4, 16.0));
+        let mut app = App::new(world, KeyBindings::classic());
+        app.skip_unchanged_frames = true;
+        let (width, height) = (64, 48);
+        let mut buffer = vec![Color { r: 0, g: 0, b: 0 }; width * height];
+        let mut keyboard = KeyboardState::new();
+
+        app.frame(&mut keyboard, &mut buffer, width, height);
+        assert!(!app.reused_scene());
+        app.frame(&mut keyboard, &mut buffer, width, height);
+        assert!(app.reused_scene());
+        assert_eq!(app.stats.rays, 0);
+
+        app.world.mark_dirty();
+        app.frame(&mut keyboard, &mut buffer, width, height);
+        assert!(!app.reused_scene());
+    }
+
+    #[test]
+    fn mouse_motion_turns_the_player_once_on_the_next_frame_that_steps() {
+        let map = Map::parse("#####\n#...#\n#...#\n#...#\n#####").unwrap();
+        let world = World::new(map, Player::new(2.5, 2.5, 0.0), Life::new(FRAC_PI_4, 16.0));
+        let mut app = App::new(world, KeyBindings::classic());
+        // steps a second long, so the first frame can't run one
+        app.timestep = FixedTimestep::new(1.0, MAX_STEPS);
+        let (width, height) = (32, 24);
+        let mut buffer = vec![Color { r: 0, g: 0, b: 0 }; width * height];
+        let mut keyboard = KeyboardState::new();
+
+        keyboard.mouse_motion = 200.0;
+        app.frame(&mut keyboard, &mut buffer, width, height);
+        assert_eq!((app.world.seat.player.vision_angle, keyboard.mouse_motion), (0.0, 200.0));
+
+        app.last_frame = Instant::now() - Duration::from_secs_f64(3.5);
+        app.frame(&mut keyboard, &mut buffer, width, height);
+        let turned = 200.0 * MOUSE_SENSITIVITY;
+        assert!((app.world.seat.player.vision_angle - turned).abs() < 1e-9, "{}", app.world.seat.player.vision_angle);
+        assert_eq!(keyboard.mouse_motion, 0.0);
+
+        // within the dead zone it's ignored
+        keyboard.mouse_motion = MOUSE_DEAD_ZONE;
+        app.last_frame = Instant::now() - Duration::from_secs_f64(1.5);
+        app.frame(&mut keyboard, &mut buffer, width, height);
+        assert!((app.world.seat.player.vision_angle - turned).abs() < 1e-9);
+    }
+
+    #[test]
+    fn session_bindings_leave_the_saved_ones_in_the_settings_file() {
+        let path = std::env::temp_dir().join(format!("settings-test-{}.txt", std::process::id()));
+        std::fs::write(&path, "fov = 1.2\nbind Forward = I").unwrap();
+        let map = Map::parse("#####\n#...#\n#...#\n#...#\n#####").unwrap();
+        let world = World::new(map, Player::new(2.5, 2.5, 0.0), Life::new(FRAC_PI_4, 16.0));
+        let app = App::new(world, KeyBindings::classic()).with_settings_file(&path).with_session_bindings(KeyBindings::arrows());
+        assert!(app.bindings.keys(Action::Forward).any(|key| key == VirtualKeyCode::Up));
+        // zooming still narrows the view to a third of the loaded one
+        assert!((app.world.life.zoom_fov_angle - 0.4).abs() < 1e-9, "{}", app.world.life.zoom_fov_angle);
+        drop(app);
+
+        let mut saved = Settings::default();
+        assert!(saved.read(&std::fs::read_to_string(&path).unwrap()).is_empty());
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(saved.bindings.keys(Action::Forward).collect::<Vec<_>>(), vec![VirtualKeyCode::I]);
+    }
+}
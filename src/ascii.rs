@@ -0,0 +1,52 @@
+/*
+    The engine started as a port of a console first person shooter (hence the
+    crate name), drawing with characters instead of pixels. This turns a
+    rendered frame back into that form: the frame is split into one block per
+    character and each block's average brightness picks a character from a
+    ramp going from dark to light.
+ */
+
+use pixel_canvas::Color;
+
+/// Characters from darkest to brightest.
+const RAMP: &[u8] = b" .:-=+*#%@";
+
+/// Default size of the printed frame, half as many rows as columns as terminal cells are tall.
+pub const ASCII_COLUMNS: usize = 96;
+pub const ASCII_ROWS: usize = 48;
+
+fn luminance(color: Color) -> f64 {
+    0.299 * color.r as f64 + 0.587 * color.g as f64 + 0.114 * color.b as f64
+}
+
+/// Downsample `buffer` to `columns` x `rows` characters, top of the frame first.
+pub fn frame_to_ascii(buffer: &[Color], width: usize, height: usize, columns: usize, rows: usize) -> String {
+    let mut frame = String::with_capacity((columns + 1) * rows);
+
+    for row in 0..rows {
+        // buffer rows go bottom to top, text goes top to bottom
+        let y_end = height - row * height / rows;
+        let y_start = height - (row + 1) * height / rows;
+
+        for column in 0..columns {
+            let x_start = column * width / columns;
+            let x_end = ((column + 1) * width / columns).max(x_start + 1);
+
+            let mut total = 0.0;
+            let mut samples = 0;
+            for y in y_start..y_end.max(y_start + 1) {
+                for x in x_start..x_end {
+                    total += luminance(buffer[y * width + x]);
+                    samples += 1;
+                }
+            }
+
+            let brightness = total / samples as f64 / 255.0;
+            let index = ((brightness * RAMP.len() as f64) as usize).min(RAMP.len() - 1);
+            frame.push(RAMP[index] as char);
+        }
+        frame.push('\n');
+    }
+
+    frame
+}
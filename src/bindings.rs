@@ -0,0 +1,278 @@
+use crate::keyboard::KeyboardState;
+use pixel_canvas::input::glutin::event::VirtualKeyCode;
+
+/*
+    Scancodes name a key by where it sits on the keyboard rather than by the
+    letter printed on it, so W is still the key above S on an AZERTY or Dvorak
+    layout. These are the values winit reports on Windows and Linux (X11 and
+    Wayland both pass the kernel's codes on), for the keys around WASD:
+
+        Q 0x10   W 0x11   E 0x12   R 0x13
+        A 0x1e   S 0x1f   D 0x20
+        Z 0x2c            space 0x39
+
+    macOS numbers its keys differently, so scancode bindings made from these
+    won't line up there.
+ */
+
+pub const SCANCODE_Q: u32 = 0x10;
+pub const SCANCODE_W: u32 = 0x11;
+pub const SCANCODE_E: u32 = 0x12;
+pub const SCANCODE_R: u32 = 0x13;
+pub const SCANCODE_A: u32 = 0x1e;
+pub const SCANCODE_S: u32 = 0x1f;
+pub const SCANCODE_D: u32 = 0x20;
+pub const SCANCODE_Z: u32 = 0x2c;
+pub const SCANCODE_SPACE: u32 = 0x39;
+
+/// Keys `key_from_name` knows, everything near the letters and the keypad.
+pub const NAMED_KEYS: &[VirtualKeyCode] = &[
+    VirtualKeyCode::A, VirtualKeyCode::B, VirtualKeyCode::C, VirtualKeyCode::D, VirtualKeyCode::E,
+    VirtualKeyCode::F, VirtualKeyCode::G, VirtualKeyCode::H, VirtualKeyCode::I, VirtualKeyCode::J,
+    VirtualKeyCode::K, VirtualKeyCode::L, VirtualKeyCode::M, VirtualKeyCode::N, VirtualKeyCode::O,
+    VirtualKeyCode::P, VirtualKeyCode::Q, VirtualKeyCode::R, VirtualKeyCode::S, VirtualKeyCode::T,
+    VirtualKeyCode::U, VirtualKeyCode::V, VirtualKeyCode::W, VirtualKeyCode::X, VirtualKeyCode::Y,
+    VirtualKeyCode::Z, VirtualKeyCode::Key0, VirtualKeyCode::Key1, VirtualKeyCode::Key2,
+    VirtualKeyCode::Key3, VirtualKeyCode::Key4, VirtualKeyCode::Key5, VirtualKeyCode::Key6,
+    VirtualKeyCode::Key7, VirtualKeyCode::Key8, VirtualKeyCode::Key9, VirtualKeyCode::F1,
+    VirtualKeyCode::F2, VirtualKeyCode::F3, VirtualKeyCode::F4, VirtualKeyCode::F5,
+    VirtualKeyCode::F6, VirtualKeyCode::F7, VirtualKeyCode::F8, VirtualKeyCode::F9,
+    VirtualKeyCode::F10, VirtualKeyCode::F11, VirtualKeyCode::F12, VirtualKeyCode::Up,
+    VirtualKeyCode::Down, VirtualKeyCode::Left, VirtualKeyCode::Right, VirtualKeyCode::Space,
+    VirtualKeyCode::Return, VirtualKeyCode::Escape, VirtualKeyCode::Tab, VirtualKeyCode::Back,
+    VirtualKeyCode::Insert, VirtualKeyCode::Delete, VirtualKeyCode::Home, VirtualKeyCode::End,
+    VirtualKeyCode::PageUp, VirtualKeyCode::PageDown, VirtualKeyCode::LShift,
+    VirtualKeyCode::RShift, VirtualKeyCode::LControl, VirtualKeyCode::RControl,
+    VirtualKeyCode::LAlt, VirtualKeyCode::RAlt, VirtualKeyCode::Comma, VirtualKeyCode::Period,
+    VirtualKeyCode::Slash, VirtualKeyCode::Semicolon, VirtualKeyCode::Apostrophe,
+    VirtualKeyCode::LBracket, VirtualKeyCode::RBracket, VirtualKeyCode::Backslash,
+    VirtualKeyCode::Minus, VirtualKeyCode::Equals, VirtualKeyCode::Grave, VirtualKeyCode::Numpad0,
+    VirtualKeyCode::Numpad1, VirtualKeyCode::Numpad2, VirtualKeyCode::Numpad3,
+    VirtualKeyCode::Numpad4, VirtualKeyCode::Numpad5, VirtualKeyCode::Numpad6,
+    VirtualKeyCode::Numpad7, VirtualKeyCode::Numpad8, VirtualKeyCode::Numpad9,
+];
+
+/// The key `{:?}` prints as `name`, if it's one of `NAMED_KEYS`.
+pub fn key_from_name(name: &str) -> Option<VirtualKeyCode> {
+    NAMED_KEYS.iter().copied().find(|key| format!("{:?}", key) == name)
+}
+
+/// Something the player can do by holding or pressing a key.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Action {
+    Forward,
+    Backward,
+    TurnLeft,
+    TurnRight,
+    StrafeLeft,
+    StrafeRight,
+    /// Narrow the field of view while held.
+    Zoom,
+    /// Jump, on pressing the key rather than holding it.
+    Jump,
+    /// Crouch while held, to get under low ceilings.
+    Crouch,
+    LookUp,
+    LookDown,
+    /// Start or stop walking forward without holding a key, see `Seat::autorun`.
+    Autorun,
+}
+
+impl Action {
+    /// Every action, in the order the help lists them.
+    pub const ALL: [Action; 12] = [
+        Action::Forward,
+        Action::Backward,
+        Action::TurnLeft,
+        Action::TurnRight,
+        Action::StrafeLeft,
+        Action::StrafeRight,
+        Action::Zoom,
+        Action::Jump,
+        Action::Crouch,
+        Action::LookUp,
+        Action::LookDown,
+        Action::Autorun,
+    ];
+
+    /// What the action does, for the help overlay.
+    pub fn describe(self) -> &'static str {
+        match self {
+            Action::Forward => "walk forward",
+            Action::Backward => "walk backward",
+            Action::TurnLeft => "turn left",
+            Action::TurnRight => "turn right",
+            Action::StrafeLeft => "step left",
+            Action::StrafeRight => "step right",
+            Action::Zoom => "zoom while held",
+            Action::Jump => "jump",
+            Action::Crouch => "crouch while held",
+            Action::LookUp => "look up",
+            Action::LookDown => "look down",
+            Action::Autorun => "keep walking forward",
+        }
+    }
+}
+
+/// Which keys trigger which actions, by virtual key code, by scancode or both.
+#[derive(Clone, Default)]
+pub struct KeyBindings {
+    bindings: Vec<(Action, VirtualKeyCode)>,
+    scancodes: Vec<(Action, u32)>,
+}
+
+impl KeyBindings {
+    /// No keys bound at all.
+    pub fn new() -> Self {
+        Self { bindings: Vec::new(), scancodes: Vec::new() }
+    }
+
+    /// W/S walk, A/D turn, Q/E strafe, R autoruns, Z zooms, space jumps, control crouches, page up/down look.
+    pub fn classic() -> Self {
+        let mut bindings = Self::new();
+        bindings
+            .bind(Action::Forward, VirtualKeyCode::W)
+            .bind(Action::Backward, VirtualKeyCode::S)
+            .bind(Action::TurnLeft, VirtualKeyCode::A)
+            .bind(Action::TurnRight, VirtualKeyCode::D)
+            .bind(Action::StrafeLeft, VirtualKeyCode::Q)
+            .bind(Action::StrafeRight, VirtualKeyCode::E)
+            .bind(Action::Autorun, VirtualKeyCode::R)
+            .bind(Action::Zoom, VirtualKeyCode::Z)
+            .bind(Action::Jump, VirtualKeyCode::Space)
+            .bind(Action::Crouch, VirtualKeyCode::LControl)
+            .bind(Action::LookUp, VirtualKeyCode::PageUp)
+            .bind(Action::LookDown, VirtualKeyCode::PageDown);
+        bindings
+    }
+
+    /// Arrow keys walk and turn and A/D strafe, otherwise as `classic`.
+    pub fn arrows() -> Self {
+        let mut bindings = Self::new();
+        bindings
+            .bind(Action::Forward, VirtualKeyCode::Up)
+            .bind(Action::Backward, VirtualKeyCode::Down)
+            .bind(Action::TurnLeft, VirtualKeyCode::Left)
+            .bind(Action::TurnRight, VirtualKeyCode::Right)
+            .bind(Action::Forward, VirtualKeyCode::W)
+            .bind(Action::Backward, VirtualKeyCode::S)
+            .bind(Action::StrafeLeft, VirtualKeyCode::A)
+            .bind(Action::StrafeRight, VirtualKeyCode::D)
+            .bind(Action::Autorun, VirtualKeyCode::R)
+            .bind(Action::Zoom, VirtualKeyCode::Z)
+            .bind(Action::Jump, VirtualKeyCode::Space)
+            .bind(Action::Crouch, VirtualKeyCode::LControl)
+            .bind(Action::LookUp, VirtualKeyCode::PageUp)
+            .bind(Action::LookDown, VirtualKeyCode::PageDown);
+        bindings
+    }
+
+    /// The `classic` keys bound by scancode, so they stay put on any layout; page up and down stay virtual.
+    pub fn physical() -> Self {
+        let mut bindings = Self::new();
+        bindings
+            .bind_scancode(Action::Forward, SCANCODE_W)
+            .bind_scancode(Action::Backward, SCANCODE_S)
+            .bind_scancode(Action::TurnLeft, SCANCODE_A)
+            .bind_scancode(Action::TurnRight, SCANCODE_D)
+            .bind_scancode(Action::StrafeLeft, SCANCODE_Q)
+            .bind_scancode(Action::StrafeRight, SCANCODE_E)
+            .bind_scancode(Action::Autorun, SCANCODE_R)
+            .bind_scancode(Action::Zoom, SCANCODE_Z)
+            .bind_scancode(Action::Jump, SCANCODE_SPACE)
+            .bind(Action::Crouch, VirtualKeyCode::LControl)
+            .bind(Action::LookUp, VirtualKeyCode::PageUp)
+            .bind(Action::LookDown, VirtualKeyCode::PageDown);
+        bindings
+    }
+
+    /// Arrows, comma/period strafe, right shift zoom, right control jump, slash crouch; shares the keyboard with `classic`.
+    pub fn second_player() -> Self {
+        let mut bindings = Self::new();
+        bindings
+            .bind(Action::Forward, VirtualKeyCode::Up)
+            .bind(Action::Backward, VirtualKeyCode::Down)
+            .bind(Action::TurnLeft, VirtualKeyCode::Left)
+            .bind(Action::TurnRight, VirtualKeyCode::Right)
+            .bind(Action::StrafeLeft, VirtualKeyCode::Comma)
+            .bind(Action::StrafeRight, VirtualKeyCode::Period)
+            .bind(Action::Zoom, VirtualKeyCode::RShift)
+            .bind(Action::Jump, VirtualKeyCode::RControl)
+            .bind(Action::Crouch, VirtualKeyCode::Slash);
+        bindings
+    }
+
+    /// Bind `key` to `action`, replacing whatever `key` did before.
+    pub fn bind(&mut self, action: Action, key: VirtualKeyCode) -> &mut Self {
+        self.unbind(key);
+        self.bindings.push((action, key));
+        self
+    }
+
+    pub fn unbind(&mut self, key: VirtualKeyCode) -> &mut Self {
+        self.bindings.retain(|&(_, bound)| bound != key);
+        self
+    }
+
+    /// Keys bound to `action`, in the order they were bound.
+    pub fn keys(&self, action: Action) -> impl Iterator<Item = VirtualKeyCode> + '_ {
+        self.bindings.iter().filter(move |&&(bound, _)| bound == action).map(|&(_, key)| key)
+    }
+
+    /// Actions bound to `key`.
+    pub fn actions(&self, key: VirtualKeyCode) -> impl Iterator<Item = Action> + '_ {
+        self.bindings.iter().filter(move |&&(_, bound)| bound == key).map(|&(action, _)| action)
+    }
+
+    /// Bind `scancode` to `action`, replacing whatever it did before.
+    pub fn bind_scancode(&mut self, action: Action, scancode: u32) -> &mut Self {
+        self.unbind_scancode(scancode);
+        self.scancodes.push((action, scancode));
+        self
+    }
+
+    pub fn unbind_scancode(&mut self, scancode: u32) -> &mut Self {
+        self.scancodes.retain(|&(_, bound)| bound != scancode);
+        self
+    }
+
+    /// Scancodes bound to `action`, in the order they were bound.
+    pub fn scancodes(&self, action: Action) -> impl Iterator<Item = u32> + '_ {
+        self.scancodes.iter().filter(move |&&(bound, _)| bound == action).map(|&(_, scancode)| scancode)
+    }
+
+    /// Actions bound to `scancode`.
+    pub fn scancode_actions(&self, scancode: u32) -> impl Iterator<Item = Action> + '_ {
+        self.scancodes.iter().filter(move |&&(_, bound)| bound == scancode).map(|&(action, _)| action)
+    }
+
+    /// Whether any key bound to `action` is held down.
+    pub fn is_active(&self, action: Action, keyboard: &KeyboardState) -> bool {
+        self.keys(action).any(|key| keyboard.is_held(key))
+            || self.scancodes(action).any(|scancode| keyboard.is_scancode_held(scancode))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keyboard::KeyboardState;
+
+    #[test]
+    fn scancodes_map_to_actions() {
+        let mut bindings = KeyBindings::physical();
+        assert_eq!(bindings.scancode_actions(SCANCODE_W).collect::<Vec<_>>(), [Action::Forward]);
+        assert_eq!(bindings.scancode_actions(SCANCODE_SPACE).collect::<Vec<_>>(), [Action::Jump]);
+        assert_eq!(bindings.scancodes(Action::StrafeLeft).collect::<Vec<_>>(), [SCANCODE_Q]);
+
+        // whatever letter the layout prints on it, the key above S walks
+        let mut keyboard = KeyboardState::new();
+        keyboard.held_scancodes.insert(SCANCODE_W);
+        assert!(bindings.is_active(Action::Forward, &keyboard));
+        assert!(!bindings.is_active(Action::Backward, &keyboard));
+
+        bindings.bind_scancode(Action::Backward, SCANCODE_W);
+        assert_eq!(bindings.scancode_actions(SCANCODE_W).collect::<Vec<_>>(), [Action::Backward]);
+        assert!(!bindings.is_active(Action::Forward, &keyboard));
+    }
+}
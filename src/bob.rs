@@ -0,0 +1,91 @@
+use crate::player::Player;
+use crate::sound::FOOTSTEP_STRIDE;
+use std::f64::consts::PI;
+
+/// Default for `HeadBob::height`, as a fraction of the wall height.
+pub const BOB_HEIGHT: f64 = 0.015;
+
+/// Default for `HeadBob::sway`, in map cells.
+pub const BOB_SWAY: f64 = 0.01;
+
+/// How quickly the bob fades in and out, per second.
+const BOB_EASE: f64 = 10.0;
+
+/// Below this much the bob settles and starts over from the beginning of a stride.
+const BOB_CUTOFF: f64 = 0.001;
+
+/// The camera rising and swaying with each step, driven by distance walked so it stops with the player.
+#[derive(Clone, Debug)]
+pub struct HeadBob {
+    /// Map cells per step, by default in time with the footsteps.
+    pub stride: f64,
+    /// How far the eyes rise in the middle of a step, 0.0 to not bob.
+    pub height: f64,
+    pub sway: f64,
+    /// Steps walked so far times pi, so a whole step is half a turn.
+    phase: f64,
+    /// From 0.0 when standing still up to 1.0 while walking.
+    amount: f64,
+}
+
+impl Default for HeadBob {
+    fn default() -> Self {
+        Self { stride: FOOTSTEP_STRIDE, height: BOB_HEIGHT, sway: BOB_SWAY, phase: 0.0, amount: 0.0 }
+    }
+}
+
+impl HeadBob {
+    /// Advance by `walked` map cells over `dt` seconds.
+    pub fn update(&mut self, walked: f64, dt: f64) {
+        if self.stride > 0.0 {
+            self.phase += walked / self.stride * PI;
+        }
+        let target = if walked > 0.0 { 1.0 } else { 0.0 };
+        self.amount += (target - self.amount) * (1.0 - (-BOB_EASE * dt).exp());
+        if target == 0.0 && self.amount < BOB_CUTOFF {
+            self.amount = 0.0;
+            self.phase = 0.0;
+        }
+    }
+
+    /// Pi for every step walked since the bob last settled.
+    pub fn phase(&self) -> f64 {
+        self.phase
+    }
+
+    pub fn apply(&self, camera: &mut Player) {
+        if self.amount == 0.0 {
+            return;
+        }
+        let rise = self.height * self.amount * self.phase.sin().abs();
+        let sway = self.sway * self.amount * self.phase.sin();
+        let (sin, cos) = camera.vision_angle.sin_cos();
+        camera.eye_height += rise;
+        camera.player_x += cos * sway;
+        camera.player_y -= sin * sway;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn phase_holds_while_standing_still() {
+        let mut bob = HeadBob::default();
+        for _ in 0..60 {
+            bob.update(0.0, 1.0 / 60.0);
+            assert_eq!(bob.phase(), 0.0);
+        }
+
+        bob.update(bob.stride / 2.0, 1.0 / 60.0);
+        let walked = bob.phase();
+        assert!((walked - PI / 2.0).abs() < 1e-9);
+        for _ in 0..60 {
+            bob.update(0.0, 1.0 / 60.0);
+            assert!(bob.phase() == walked || bob.phase() == 0.0);
+        }
+        // settled, so the next walk starts a fresh stride
+        assert_eq!(bob.phase(), 0.0);
+    }
+}
@@ -0,0 +1,250 @@
+use crate::life::{Life, LifeError, Quality};
+use crate::map::Map;
+use crate::player::{Player, MAX_EYE_HEIGHT, MIN_EYE_HEIGHT};
+use crate::world::World;
+use std::f64::consts::FRAC_PI_4;
+use std::fmt;
+
+/// Why `WorldBuilder::build` refused to build a world.
+#[derive(Clone, Debug, PartialEq)]
+pub enum BuildError {
+    MissingMap,
+    SpawnOffMap { x: f64, y: f64 },
+    SpawnInWall { x: f64, y: f64 },
+    FieldOfView(f64),
+    MaxDepth(f64),
+    InternalScale(f64),
+    EyeHeight(f64),
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BuildError::MissingMap => write!(f, "no map given"),
+            BuildError::SpawnOffMap { x, y } => write!(f, "spawn ({}, {}) is outside the map", x, y),
+            BuildError::SpawnInWall { x, y } => write!(f, "spawn ({}, {}) is inside a wall", x, y),
+            BuildError::FieldOfView(fov) => write!(f, "{}", LifeError::FieldOfView(*fov)),
+            BuildError::MaxDepth(depth) => write!(f, "{}", LifeError::MaxDepth(*depth)),
+            BuildError::InternalScale(scale) => write!(f, "internal scale {} is not in (0, 1]", scale),
+            BuildError::EyeHeight(eye) => {
+                write!(f, "eye height {} is not between {} and {}", eye, MIN_EYE_HEIGHT, MAX_EYE_HEIGHT)
+            }
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
+
+/// Sets up a `World` by name; unset values get the constructors' defaults and the map's spawn.
+pub struct WorldBuilder {
+    map: Option<Map>,
+    spawn: Option<(f64, f64)>,
+    angle: f64,
+    fov: f64,
+    max_depth: f64,
+    forward_speed: Option<f64>,
+    strafe_speed: Option<f64>,
+    turn_speed: Option<f64>,
+    eye_height: Option<f64>,
+    quality: Quality,
+    internal_scale: f64,
+    corner_cutting: bool,
+    transition_time: f64,
+}
+
+impl Default for WorldBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WorldBuilder {
+    pub fn new() -> Self {
+        Self {
+            map: None,
+            spawn: None,
+            angle: 0.0,
+            fov: FRAC_PI_4,
+            max_depth: 16.0,
+            forward_speed: None,
+            strafe_speed: None,
+            turn_speed: None,
+            eye_height: None,
+            quality: Quality::High,
+            internal_scale: 1.0,
+            corner_cutting: true,
+            transition_time: 0.0,
+        }
+    }
+
+    pub fn map(mut self, map: Map) -> Self {
+        self.map = Some(map);
+        self
+    }
+
+    /// Start the player at `(x, y)` in map coordinates instead of the spawn.
+    pub fn spawn(mut self, x: f64, y: f64) -> Self {
+        self.spawn = Some((x, y));
+        self
+    }
+
+    pub fn angle(mut self, angle: f64) -> Self {
+        self.angle = angle;
+        self
+    }
+
+    pub fn fov(mut self, fov: f64) -> Self {
+        self.fov = fov;
+        self
+    }
+
+    pub fn max_depth(mut self, max_depth: f64) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Walk and strafe at the same speed.
+    pub fn walk_speed(mut self, walk_speed: f64) -> Self {
+        self.forward_speed = Some(walk_speed);
+        self.strafe_speed = Some(walk_speed);
+        self
+    }
+
+    pub fn forward_speed(mut self, forward_speed: f64) -> Self {
+        self.forward_speed = Some(forward_speed);
+        self
+    }
+
+    pub fn strafe_speed(mut self, strafe_speed: f64) -> Self {
+        self.strafe_speed = Some(strafe_speed);
+        self
+    }
+
+    pub fn turn_speed(mut self, turn_speed: f64) -> Self {
+        self.turn_speed = Some(turn_speed);
+        self
+    }
+
+    pub fn eye_height(mut self, eye_height: f64) -> Self {
+        self.eye_height = Some(eye_height);
+        self
+    }
+
+    pub fn quality(mut self, quality: Quality) -> Self {
+        self.quality = quality;
+        self
+    }
+
+    pub fn internal_scale(mut self, internal_scale: f64) -> Self {
+        self.internal_scale = internal_scale;
+        self
+    }
+
+    /// See `World::corner_cutting`.
+    pub fn corner_cutting(mut self, corner_cutting: bool) -> Self {
+        self.corner_cutting = corner_cutting;
+        self
+    }
+
+    /// See `World::transition_time`.
+    pub fn transition_time(mut self, transition_time: f64) -> Self {
+        self.transition_time = transition_time;
+        self
+    }
+
+    pub fn build(self) -> Result<World, BuildError> {
+        let map = self.map.ok_or(BuildError::MissingMap)?;
+
+        let mut life = Life::try_new(self.fov, self.max_depth).map_err(|error| match error {
+            LifeError::FieldOfView(fov) => BuildError::FieldOfView(fov),
+            LifeError::MaxDepth(depth) => BuildError::MaxDepth(depth),
+        })?;
+        if !(self.internal_scale > 0.0 && self.internal_scale <= 1.0) {
+            return Err(BuildError::InternalScale(self.internal_scale));
+        }
+        if let Some(eye) = self.eye_height {
+            if !(MIN_EYE_HEIGHT..=MAX_EYE_HEIGHT).contains(&eye) {
+                return Err(BuildError::EyeHeight(eye));
+            }
+        }
+
+        let (x, y) = self.spawn.unwrap_or_else(|| {
+            let (cell_x, cell_y) = map.spawn().unwrap_or((map.width / 2, map.height / 2));
+            (cell_x as f64 + 0.5, cell_y as f64 + 0.5)
+        });
+        if !(x >= 0.0 && y >= 0.0 && x < map.width as f64 && y < map.height as f64) {
+            return Err(BuildError::SpawnOffMap { x, y });
+        }
+        if map.blocks_movement(x, y) {
+            return Err(BuildError::SpawnInWall { x, y });
+        }
+
+        let mut player = Player::new(x, y, self.angle);
+        if let Some(forward_speed) = self.forward_speed {
+            player.forward_speed = forward_speed;
+        }
+        if let Some(strafe_speed) = self.strafe_speed {
+            player.strafe_speed = strafe_speed;
+        }
+        if let Some(turn_speed) = self.turn_speed {
+            player.turn_speed = turn_speed;
+        }
+        if let Some(eye_height) = self.eye_height {
+            player.set_eye_height(eye_height);
+        }
+
+        life.quality = self.quality;
+        life.internal_scale = self.internal_scale;
+
+        let mut world = World::new(map, player, life);
+        world.corner_cutting = self.corner_cutting;
+        world.transition_time = self.transition_time;
+        Ok(world)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn room() -> Map {
+        Map::parse("#####\n#...#\n#.P.#\n#...#\n#####").unwrap()
+    }
+
+    #[test]
+    fn built_world_has_the_configured_values() {
+        let world = WorldBuilder::new()
+            .map(room())
+            .angle(1.0)
+            .fov(1.2)
+            .max_depth(10.0)
+            .forward_speed(3.0)
+            .strafe_speed(2.0)
+            .turn_speed(4.0)
+            .eye_height(0.4)
+            .quality(Quality::Low)
+            .internal_scale(0.5)
+            .corner_cutting(false)
+            .transition_time(0.3)
+            .build()
+            .unwrap();
+        assert_eq!((world.seat.player.player_x, world.seat.player.player_y, world.seat.player.vision_angle), (2.5, 2.5, 1.0));
+        assert_eq!(world.life.fov_angle, 1.2);
+        assert_eq!(world.life.max_wall_check_depth, 10.0);
+        assert_eq!((world.seat.player.forward_speed, world.seat.player.strafe_speed, world.seat.player.turn_speed), (3.0, 2.0, 4.0));
+        assert_eq!(world.seat.player.eye_height, 0.4);
+        assert_eq!(world.life.quality, Quality::Low);
+        assert_eq!(world.life.internal_scale, 0.5);
+        assert!(!world.corner_cutting);
+        assert_eq!(world.transition_time, 0.3);
+    }
+
+    #[test]
+    fn spawn_in_wall_is_rejected() {
+        let error = WorldBuilder::new().map(room()).spawn(0.5, 0.5).build().err();
+        assert_eq!(error, Some(BuildError::SpawnInWall { x: 0.5, y: 0.5 }));
+        let error = WorldBuilder::new().map(room()).spawn(9.0, 1.0).build().err();
+        assert_eq!(error, Some(BuildError::SpawnOffMap { x: 9.0, y: 1.0 }));
+        assert_eq!(WorldBuilder::new().build().err(), Some(BuildError::MissingMap));
+    }
+}
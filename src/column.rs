@@ -0,0 +1,48 @@
+use crate::map::Map;
+use crate::ray::RayHit;
+use pixel_canvas::Color;
+use std::ops::Range;
+
+/// One drawn column of a 3D view for a `ColumnHook` to change, rows counting from the bottom.
+pub struct ColumnPixels<'a> {
+    buffer: &'a mut [Color],
+    width: usize,
+    height: usize,
+    x: usize,
+    wall: Range<usize>,
+}
+
+impl<'a> ColumnPixels<'a> {
+    pub fn new(buffer: &'a mut [Color], width: usize, height: usize, x: usize, wall: Range<usize>) -> Self {
+        Self { buffer, width, height, x, wall }
+    }
+
+    pub fn x(&self) -> usize {
+        self.x
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Rows showing the column's wall, empty when it's too far away to cover a row.
+    pub fn wall_rows(&self) -> Range<usize> {
+        self.wall.clone()
+    }
+
+    pub fn get(&self, y: usize) -> Color {
+        self.buffer[y * self.width + self.x]
+    }
+
+    pub fn set(&mut self, y: usize, color: Color) {
+        self.buffer[y * self.width + self.x] = color;
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Color> + '_ {
+        self.buffer.iter_mut().skip(self.x).step_by(self.width).take(self.height)
+    }
+}
+
+/// Called per 3D view column after walls, floor and sky and before sprites, see `App::with_column_effect`.
+/// Runs hundreds of times a frame on pixels a buffer width apart: keep it small and bail out early.
+pub type ColumnHook = Box<dyn FnMut(&Map, &RayHit, &mut ColumnPixels)>;
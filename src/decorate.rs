@@ -0,0 +1,90 @@
+use crate::map::{Map, FLOOR_GLYPH};
+use crate::sprite::Sprite;
+use pixel_canvas::Color;
+
+/// Colors picked from for scattered decorations.
+const DECORATION_COLORS: &[Color] = &[
+    Color { r: 70, g: 140, b: 60 },
+    Color { r: 110, g: 100, b: 90 },
+    Color { r: 160, g: 150, b: 60 },
+    Color { r: 90, g: 70, b: 50 },
+];
+
+/// Small, fast and the same on every platform, which is all decorations need.
+pub(crate) struct SplitMix64(pub(crate) u64);
+
+impl SplitMix64 {
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `[0, 1)`.
+    pub(crate) fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    pub(crate) fn range(&mut self, low: f64, high: f64) -> f64 {
+        low + (high - low) * self.next_f64()
+    }
+}
+
+/// Whether `(x, y)` is a one cell wide passage with walls on both sides.
+fn is_doorway(map: &Map, x: u16, y: u16) -> bool {
+    let blocks = |dx: i32, dy: i32| map.blocks_movement(x as f64 + 0.5 + dx as f64, y as f64 + 0.5 + dy as f64);
+    (blocks(-1, 0) && blocks(1, 0)) || (blocks(0, -1) && blocks(0, 1))
+}
+
+/// Non-colliding sprites on plain floor away from the spawn and doorways, one a cell with chance `density`, the same for the same `seed`.
+pub fn scatter_decorations(map: &Map, density: f64, seed: u64) -> Vec<Sprite> {
+    let mut rng = SplitMix64(seed);
+    let mut decorations = Vec::new();
+
+    for y in 0..map.height {
+        for x in 0..map.width {
+            // the spawn has a glyph of its own, so it's not plain floor
+            if map.glyph(x, y) != FLOOR_GLYPH || is_doorway(map, x, y) {
+                continue;
+            }
+            let taken = map.sprites.iter().any(|sprite| sprite.x as u16 == x && sprite.y as u16 == y);
+            if taken || rng.next_f64() >= density {
+                continue;
+            }
+
+            let width = rng.range(0.15, 0.35);
+            let height = rng.range(0.15, 0.45);
+            let color = DECORATION_COLORS[(rng.next_u64() % DECORATION_COLORS.len() as u64) as usize];
+            // far enough from the cell's edges to not stick into walls
+            let margin = width / 2.0 + 0.05;
+            let sprite_x = x as f64 + rng.range(margin, 1.0 - margin);
+            let sprite_y = y as f64 + rng.range(margin, 1.0 - margin);
+            decorations.push(Sprite::new(sprite_x, sprite_y, width, height, color));
+        }
+    }
+    decorations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn placements(map: &Map, seed: u64) -> Vec<(f64, f64, f64, f64, u8)> {
+        scatter_decorations(map, 0.5, seed).iter().map(|s| (s.x, s.y, s.width, s.height, s.color.r)).collect()
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_placements() {
+        let map = Map::parse("########\n#P.....#\n#......#\n####.###\n#......#\n#......#\n########").unwrap();
+        assert!(!placements(&map, 7).is_empty());
+        assert_eq!(placements(&map, 7), placements(&map, 7));
+        assert_ne!(placements(&map, 7), placements(&map, 8));
+
+        // everywhere that may have one does, except the spawn and the doorway
+        let all = scatter_decorations(&map, 1.0, 7);
+        assert_eq!(all.len(), 23);
+        assert!(!all.iter().any(|s| (s.x as u16, s.y as u16) == (1, 1) || (s.x as u16, s.y as u16) == (4, 3)));
+    }
+}
@@ -0,0 +1,72 @@
+/// How far along a value should be, 0.0 to 1.0, for how far along the change is, `t` clamped to 0.0..1.0.
+pub type Easing = fn(f64) -> f64;
+
+/// Steady all the way.
+pub fn linear(t: f64) -> f64 {
+    t.clamp(0.0, 1.0)
+}
+
+/// Sets off slowly and speeds up.
+pub fn ease_in_quad(t: f64) -> f64 {
+    let t = t.clamp(0.0, 1.0);
+    t * t
+}
+
+/// Sets off quickly and slows down into the end.
+pub fn ease_out_quad(t: f64) -> f64 {
+    let t = t.clamp(0.0, 1.0);
+    t * (2.0 - t)
+}
+
+/// Slow at both ends and quickest halfway.
+pub fn ease_in_out_quad(t: f64) -> f64 {
+    let t = t.clamp(0.0, 1.0);
+    if t < 0.5 { 2.0 * t * t } else { 1.0 - 2.0 * (1.0 - t) * (1.0 - t) }
+}
+
+/// Like `ease_in_quad`, slower still to start.
+pub fn ease_in_cubic(t: f64) -> f64 {
+    let t = t.clamp(0.0, 1.0);
+    t * t * t
+}
+
+/// Like `ease_out_quad`, settling more gently.
+pub fn ease_out_cubic(t: f64) -> f64 {
+    let t = 1.0 - t.clamp(0.0, 1.0);
+    1.0 - t * t * t
+}
+
+/// Like `ease_in_out_quad`, with gentler ends.
+pub fn ease_in_out_cubic(t: f64) -> f64 {
+    let t = t.clamp(0.0, 1.0);
+    if t < 0.5 { 4.0 * t * t * t } else { 1.0 - 4.0 * (1.0 - t).powi(3) }
+}
+
+/// Slow at both ends and smooth halfway, between the quad and cubic ease in and out.
+pub fn smoothstep(t: f64) -> f64 {
+    let t = t.clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn curves_at_the_start_middle_and_end() {
+        let curves: [(Easing, f64); 8] = [
+            (linear, 0.5),
+            (ease_in_quad, 0.25),
+            (ease_out_quad, 0.75),
+            (ease_in_out_quad, 0.5),
+            (ease_in_cubic, 0.125),
+            (ease_out_cubic, 0.875),
+            (ease_in_out_cubic, 0.5),
+            (smoothstep, 0.5),
+        ];
+        for (curve, middle) in curves {
+            assert_eq!((curve(0.0), curve(0.5), curve(1.0)), (0.0, middle, 1.0));
+            assert_eq!((curve(-1.0), curve(2.0)), (0.0, 1.0));
+        }
+    }
+}
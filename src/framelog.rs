@@ -0,0 +1,55 @@
+use crate::input::InputState;
+use crate::player::Player;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::Instant;
+
+/// The first line of every frame log.
+pub const FRAME_LOG_HEADER: &str = "frame,time,dt,steps,x,y,angle,walk,turn,strafe,zoom";
+
+/// One CSV line per frame, written out as it goes so a long session does not pile up in memory.
+pub struct FrameLog {
+    out: Box<dyn Write>,
+    frame: u64,
+    start: Instant,
+}
+
+impl FrameLog {
+    /// Start a log in a new file at `path`, replacing any that's there.
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::new(BufWriter::new(File::create(path)?))
+    }
+
+    pub fn new(out: impl Write + 'static) -> io::Result<Self> {
+        let mut out: Box<dyn Write> = Box::new(out);
+        writeln!(out, "{}", FRAME_LOG_HEADER)?;
+        Ok(Self { out, frame: 0, start: Instant::now() })
+    }
+
+    /// Write the next line: `dt` since the last frame and the `steps` taken in it.
+    pub fn record(&mut self, dt: f64, steps: u32, player: &Player, input: &InputState) -> io::Result<()> {
+        writeln!(
+            self.out,
+            "{},{:.6},{:.6},{},{:.6},{:.6},{:.6},{},{},{},{}",
+            self.frame,
+            self.start.elapsed().as_secs_f64(),
+            dt,
+            steps,
+            player.player_x,
+            player.player_y,
+            player.vision_angle,
+            input.walk,
+            input.turn,
+            input.strafe,
+            input.zoom as u8
+        )?;
+        self.out.flush()?;
+        self.frame += 1;
+        Ok(())
+    }
+
+    pub fn frames(&self) -> u64 {
+        self.frame
+    }
+}
@@ -0,0 +1,94 @@
+/*
+    Gamepad sticks drive the player the same way keys do, by way of the same
+    `InputState`: the left stick walks and strafes, the right stick turns and
+    looks up and down, and pushing a stick only part of the way goes only
+    part as fast.
+
+    There's no gamepad backend in the engine: the feature only maps sticks
+    onto input. Reading the gamepad is left to the program, through whatever
+    library it likes, which hands over where the sticks are each frame, see
+    `App::with_gamepad`. Until it does, `no_gamepad` is polled and the keys
+    carry on alone.
+
+    Only built with the `gamepad` feature.
+ */
+
+use crate::input::InputState;
+
+/// Default for `GamepadMapping::dead_zone`.
+pub const STICK_DEAD_ZONE: f64 = 0.15;
+
+/// Both sticks as `(x, y)` from -1.0 to 1.0, x to the right and y pushed away.
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+pub struct Sticks {
+    pub left: (f64, f64),
+    pub right: (f64, f64),
+}
+
+/// Zero a stick within `dead_zone` of the middle and stretch the rest back to 0.0..1.0, keeping its direction.
+pub fn apply_dead_zone((x, y): (f64, f64), dead_zone: f64) -> (f64, f64) {
+    let length = x.hypot(y);
+    let dead_zone = dead_zone.clamp(0.0, 0.99);
+    if length <= dead_zone {
+        return (0.0, 0.0);
+    }
+    let scale = ((length - dead_zone) / (1.0 - dead_zone)).min(1.0) / length;
+    (x * scale, y * scale)
+}
+
+/// The poll an `App` starts with, which never sees a gamepad.
+pub fn no_gamepad() -> Option<Sticks> {
+    None
+}
+
+/// How stick positions turn into input.
+#[derive(Clone, Copy, Debug)]
+pub struct GamepadMapping {
+    pub dead_zone: f64,
+    /// Turning and looking with the right stick all the way out, as a fraction of full speed.
+    pub turn_scale: f64,
+    pub look_scale: f64,
+    /// Pushing the right stick away looks down.
+    pub invert_look: bool,
+}
+
+impl Default for GamepadMapping {
+    fn default() -> Self {
+        Self { dead_zone: STICK_DEAD_ZONE, turn_scale: 1.0, look_scale: 1.0, invert_look: false }
+    }
+}
+
+impl GamepadMapping {
+    /// The input `sticks` ask for. Buttons are left to the program.
+    pub fn input(&self, sticks: Sticks) -> InputState {
+        let (strafe, walk) = apply_dead_zone(sticks.left, self.dead_zone);
+        let (turn, look) = apply_dead_zone(sticks.right, self.dead_zone);
+        let look = if self.invert_look { -look } else { look };
+        InputState { walk, strafe, turn: turn * self.turn_scale, look: look * self.look_scale, ..Default::default() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sticks_map_to_input_magnitudes() {
+        let mapping = GamepadMapping::default();
+        let input = |left, right| mapping.input(Sticks { left, right });
+
+        let resting = input((0.1, -0.1), (0.05, 0.0));
+        assert_eq!((resting.walk, resting.strafe, resting.turn, resting.look), (0.0, 0.0, 0.0, 0.0));
+
+        let full = input((0.0, 1.0), (-1.0, 0.0));
+        assert!((full.walk - 1.0).abs() < 1e-9 && full.strafe.abs() < 1e-9);
+        assert!((full.turn + 1.0).abs() < 1e-9);
+
+        // halfway between the dead zone and the rim is half speed
+        let half = input((0.0, STICK_DEAD_ZONE + (1.0 - STICK_DEAD_ZONE) / 2.0), (0.0, 0.0));
+        assert!((half.walk - 0.5).abs() < 1e-9, "{}", half.walk);
+
+        let diagonal = input((0.5, 0.5), (0.0, 0.0));
+        assert!((diagonal.walk - diagonal.strafe).abs() < 1e-9);
+    }
+}
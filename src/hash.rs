@@ -0,0 +1,36 @@
+use pixel_canvas::Color;
+
+/// FNV-1a, 64 bit.
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Hash of each pixel's red, green and blue in buffer order, the same on every run and platform.
+pub fn frame_hash(buffer: &[Color]) -> u64 {
+    buffer
+        .iter()
+        .flat_map(|pixel| [pixel.r, pixel.g, pixel.b])
+        .fold(FNV_OFFSET, |hash, byte| (hash ^ byte as u64).wrapping_mul(FNV_PRIME))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::life::Life;
+    use crate::map::Map;
+    use crate::player::Player;
+    use crate::render::render_first_person;
+    use std::f64::consts::FRAC_PI_4;
+
+    #[test]
+    fn same_render_hashes_equal_and_a_changed_pixel_does_not() {
+        let render = || {
+            let mut buffer = vec![Color { r: 0, g: 0, b: 0 }; 64 * 48];
+            render_first_person(&mut buffer, 64, 48, &Map::new(15, 16), &Player::new(8.5, 8.5, 0.3), &Life::new(FRAC_PI_4, 16.0));
+            buffer
+        };
+        let (first, mut second) = (render(), render());
+        assert_eq!(frame_hash(&first), frame_hash(&second));
+        second[100].g ^= 1;
+        assert_ne!(frame_hash(&first), frame_hash(&second));
+    }
+}
@@ -0,0 +1,180 @@
+use crate::bindings::{Action, KeyBindings};
+use crate::keyboard::KeyboardState;
+
+/// What the player is asking for this frame, independent of where it came from.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct InputState {
+    /// -1.0 walks backwards, 1.0 walks forwards.
+    pub walk: f64,
+    /// -1.0 turns left, 1.0 turns right.
+    pub turn: f64,
+    /// -1.0 steps left, 1.0 steps right.
+    pub strafe: f64,
+    /// -1.0 looks down, 1.0 looks up.
+    pub look: f64,
+    /// Radians to turn right this frame on top of `turn`, e.g. from `MouseLook::rotation`.
+    pub mouse_turn: f64,
+    pub zoom: bool,
+    pub crouch: bool,
+}
+
+impl InputState {
+    pub fn from_keyboard(keyboard: &KeyboardState, bindings: &KeyBindings) -> Self {
+        let axis = |negative: Action, positive: Action| {
+            let mut value = 0.0;
+            if bindings.is_active(negative, keyboard) {
+                value -= 1.0;
+            }
+            if bindings.is_active(positive, keyboard) {
+                value += 1.0;
+            }
+            value
+        };
+
+        Self {
+            walk: axis(Action::Backward, Action::Forward),
+            turn: axis(Action::TurnLeft, Action::TurnRight),
+            strafe: axis(Action::StrafeLeft, Action::StrafeRight),
+            look: axis(Action::LookDown, Action::LookUp),
+            mouse_turn: 0.0,
+            zoom: bindings.is_active(Action::Zoom, keyboard),
+            crouch: bindings.is_active(Action::Crouch, keyboard),
+        }
+    }
+
+    /// Input from two sources at once: each axis takes whichever is pushed further, buttons either, mouse turns add.
+    pub fn combined(self, other: InputState) -> Self {
+        let axis = |a: f64, b: f64| if b.abs() > a.abs() { b } else { a };
+        Self {
+            walk: axis(self.walk, other.walk),
+            turn: axis(self.turn, other.turn),
+            strafe: axis(self.strafe, other.strafe),
+            look: axis(self.look, other.look),
+            mouse_turn: self.mouse_turn + other.mouse_turn,
+            zoom: self.zoom || other.zoom,
+            crouch: self.crouch || other.crouch,
+        }
+    }
+}
+
+/// Default for `ActionBuffer::window`, in seconds.
+pub const BUFFER_WINDOW: f64 = 0.15;
+
+/// Presses held for a moment, so one made a little early still happens once it can.
+#[derive(Clone, Debug)]
+pub struct ActionBuffer {
+    /// Seconds a press waits to be used.
+    pub window: f64,
+    pending: Vec<(Action, f64)>,
+}
+
+impl Default for ActionBuffer {
+    fn default() -> Self {
+        Self::new(BUFFER_WINDOW)
+    }
+}
+
+impl ActionBuffer {
+    pub fn new(window: f64) -> Self {
+        Self { window, pending: Vec::new() }
+    }
+
+    /// Remember a press of `action`, restarting its window if it was already waiting.
+    pub fn press(&mut self, action: Action) {
+        self.pending.retain(|&(pending, _)| pending != action);
+        self.pending.push((action, 0.0));
+    }
+
+    /// Use up a waiting press of `action`, returning whether there was one.
+    pub fn take(&mut self, action: Action) -> bool {
+        let waiting = self.pending.iter().any(|&(pending, _)| pending == action);
+        self.pending.retain(|&(pending, _)| pending != action);
+        waiting
+    }
+
+    /// Let `dt` seconds pass, dropping presses older than the window.
+    pub fn tick(&mut self, dt: f64) {
+        for (_, age) in &mut self.pending {
+            *age += dt;
+        }
+        let window = self.window;
+        self.pending.retain(|&(_, age)| age <= window);
+    }
+
+    pub fn clear(&mut self) {
+        self.pending.clear();
+    }
+}
+
+/// Defaults for `MouseLook`'s fields.
+pub const MOUSE_SENSITIVITY: f64 = 0.003;
+pub const MOUSE_DEAD_ZONE: f64 = 0.5;
+pub const MOUSE_SMOOTHING: f64 = 0.0;
+
+/// Turns each frame's raw mouse motion into how far to turn, past a dead zone and optionally smoothed.
+#[derive(Clone, Debug)]
+pub struct MouseLook {
+    /// Radians per unit of mouse motion.
+    pub sensitivity: f64,
+    pub dead_zone: f64,
+    /// How much of the last smoothed reading carries into the next, 0.0 for none; higher lags more.
+    pub smoothing: f64,
+    smoothed: f64,
+}
+
+impl Default for MouseLook {
+    fn default() -> Self {
+        Self::new(MOUSE_SENSITIVITY, MOUSE_DEAD_ZONE, MOUSE_SMOOTHING)
+    }
+}
+
+impl MouseLook {
+    pub fn new(sensitivity: f64, dead_zone: f64, smoothing: f64) -> Self {
+        Self { sensitivity, dead_zone, smoothing, smoothed: 0.0 }
+    }
+
+    /// Radians to turn for `motion`, the mouse motion since the last reading.
+    pub fn rotation(&mut self, motion: f64) -> f64 {
+        let motion = if motion.abs() <= self.dead_zone { 0.0 } else { motion };
+        let smoothing = self.smoothing.clamp(0.0, 0.99);
+        self.smoothed = self.smoothed * smoothing + motion * (1.0 - smoothing);
+        self.smoothed * self.sensitivity
+    }
+
+    /// Forget earlier readings, e.g. after the mouse was let go of.
+    pub fn reset(&mut self) {
+        self.smoothed = 0.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn press_within_the_window_is_kept() {
+        let mut buffer = ActionBuffer::new(0.15);
+        buffer.press(Action::Jump);
+        buffer.tick(0.1);
+        assert!(buffer.take(Action::Jump));
+        assert!(!buffer.take(Action::Jump));
+
+        buffer.press(Action::Jump);
+        buffer.tick(0.2);
+        assert!(!buffer.take(Action::Jump));
+    }
+
+    #[test]
+    fn dead_zone_and_smoothing_shape_mouse_turns() {
+        let mut look = MouseLook::new(1.0, 0.5, 0.0);
+        assert_eq!(look.rotation(0.4), 0.0);
+        assert_eq!(look.rotation(-0.5), 0.0);
+        assert_eq!(look.rotation(2.0), 2.0);
+
+        let mut look = MouseLook::new(1.0, 0.0, 0.5);
+        assert_eq!(look.rotation(4.0), 2.0);
+        // halfway between the last smoothed reading and this one
+        assert_eq!(look.rotation(0.0), 1.0);
+        assert_eq!(look.rotation(3.0), 2.0);
+    }
+}
@@ -1,11 +1,21 @@
 use pixel_canvas::canvas::CanvasInfo;
-use pixel_canvas::input::glutin::event::{ElementState, VirtualKeyCode};
+use pixel_canvas::input::glutin::event::{DeviceEvent, ElementState, VirtualKeyCode};
 use pixel_canvas::input::{Event, WindowEvent};
+use std::collections::HashSet;
 
 pub struct KeyboardState {
     pub scancode: u32,
     pub state: ElementState,
     pub virtual_key_code: VirtualKeyCode,
+    pub just_pressed: Option<VirtualKeyCode>,
+    /// Every key currently held down.
+    pub held: HashSet<VirtualKeyCode>,
+    /// Also set for keys without a virtual key code.
+    pub just_pressed_scancode: Option<u32>,
+    pub held_scancodes: HashSet<u32>,
+    /// Sideways mouse motion while the window had focus, since `take_mouse_motion`.
+    pub mouse_motion: f64,
+    pub focused: bool,
 }
 
 impl KeyboardState {
@@ -15,6 +25,12 @@ impl KeyboardState {
             scancode: 0,
             state: ElementState::Pressed,
             virtual_key_code: VirtualKeyCode::Key0,
+            just_pressed: None,
+            held: HashSet::new(),
+            just_pressed_scancode: None,
+            held_scancodes: HashSet::new(),
+            mouse_motion: 0.0,
+            focused: true,
         }
     }
 
@@ -29,14 +45,40 @@ impl KeyboardState {
                 event: WindowEvent::KeyboardInput { input, .. },
                 ..
             } => {
+                if let Some(code) = input.virtual_keycode {
+                    // key repeat sends more presses, only the first one counts
+                    let repeated = keyboard.state == ElementState::Pressed && keyboard.virtual_key_code == code;
+                    if input.state == ElementState::Pressed && !repeated {
+                        keyboard.just_pressed = Some(code);
+                    }
+                    match input.state {
+                        ElementState::Pressed => keyboard.held.insert(code),
+                        ElementState::Released => keyboard.held.remove(&code),
+                    };
+                }
+                let repeated = keyboard.state == ElementState::Pressed && keyboard.scancode == input.scancode;
+                if input.state == ElementState::Pressed && !repeated {
+                    keyboard.just_pressed_scancode = Some(input.scancode);
+                }
+                match input.state {
+                    ElementState::Pressed => keyboard.held_scancodes.insert(input.scancode),
+                    ElementState::Released => keyboard.held_scancodes.remove(&input.scancode),
+                };
                 keyboard.scancode = input.scancode;
                 keyboard.state = input.state;
-                match input.virtual_keycode {
-                    Some(code) => keyboard.virtual_key_code = code,
-                    _ => (),
+                if let Some(code) = input.virtual_keycode {
+                    keyboard.virtual_key_code = code;
                 }
                 true
             }
+            Event::WindowEvent { event: WindowEvent::Focused(focused), .. } => {
+                keyboard.focused = *focused;
+                true
+            }
+            Event::DeviceEvent { event: DeviceEvent::MouseMotion { delta: (motion, _) }, .. } if keyboard.focused => {
+                keyboard.mouse_motion += motion;
+                true
+            }
             _ => false,
         }
     }
@@ -45,6 +87,34 @@ impl KeyboardState {
         if self.state == ElementState::Pressed {
             return Some(self.virtual_key_code);
         }
-        return None;
+        None
+    }
+
+    pub fn is_held(&self, key: VirtualKeyCode) -> bool {
+        self.held.contains(&key)
+    }
+
+    /// The key pressed since the last call, if any, once per press unlike `key_pressed`.
+    pub fn take_just_pressed(&mut self) -> Option<VirtualKeyCode> {
+        self.just_pressed.take()
+    }
+
+    pub fn is_scancode_held(&self, scancode: u32) -> bool {
+        self.held_scancodes.contains(&scancode)
+    }
+
+    /// The scancode pressed since the last call, like `take_just_pressed`.
+    pub fn take_just_pressed_scancode(&mut self) -> Option<u32> {
+        self.just_pressed_scancode.take()
+    }
+
+    pub fn take_mouse_motion(&mut self) -> f64 {
+        std::mem::take(&mut self.mouse_motion)
+    }
+}
+
+impl Default for KeyboardState {
+    fn default() -> Self {
+        Self::new()
     }
 }
@@ -0,0 +1,83 @@
+pub mod app;
+pub mod ascii;
+pub mod bindings;
+pub mod bob;
+pub mod builder;
+pub mod column;
+pub mod decorate;
+pub mod easing;
+pub mod font;
+pub mod framelog;
+#[cfg(feature = "gamepad")]
+pub mod gamepad;
+pub mod hash;
+pub mod input;
+pub mod keyboard;
+pub mod life;
+pub mod lift;
+pub mod light;
+pub mod lint;
+pub mod manifest;
+pub mod map;
+pub mod maps;
+pub mod muzzle;
+pub mod overlay;
+pub mod player;
+pub mod portal;
+pub mod ppm;
+pub mod ray;
+pub mod render;
+pub mod rgba;
+pub mod settings;
+pub mod shake;
+pub mod sky;
+pub mod sound;
+pub mod sprite;
+pub mod state;
+pub mod stats;
+pub mod stream;
+#[cfg(feature = "stress")]
+pub mod stress;
+pub mod svg;
+pub mod taa;
+pub mod texture;
+pub mod timestep;
+pub mod trail;
+pub mod transition;
+pub mod trigger;
+pub mod vignette;
+pub mod world;
+
+pub use crate::app::App;
+pub use crate::bindings::{Action, KeyBindings};
+pub use crate::bob::HeadBob;
+pub use crate::builder::{BuildError, WorldBuilder};
+pub use crate::framelog::FrameLog;
+pub use crate::input::{ActionBuffer, InputState, MouseLook};
+pub use crate::keyboard::KeyboardState;
+pub use crate::life::{Life, LifeError, Quality};
+pub use crate::lift::Lift;
+pub use crate::light::Light;
+pub use crate::lint::MapWarning;
+pub use crate::manifest::ManifestError;
+pub use crate::map::{Map, MapError};
+pub use crate::muzzle::Muzzle;
+pub use crate::overlay::{Layer, Layers};
+pub use crate::player::Player;
+pub use crate::portal::Portal;
+pub use crate::ray::RayHit;
+pub use crate::render::Renderer;
+pub use crate::rgba::Rgba;
+pub use crate::settings::Settings;
+pub use crate::sky::Sky;
+pub use crate::sound::SoundEvent;
+pub use crate::sprite::Sprite;
+pub use crate::state::StateBag;
+pub use crate::stats::{FrameStats, SessionStats};
+pub use crate::taa::TemporalAa;
+pub use crate::texture::{Texture, WallPattern};
+pub use crate::timestep::FixedTimestep;
+pub use crate::trail::Trail;
+pub use crate::trigger::{Trigger, TriggerAction};
+pub use crate::vignette::Vignette;
+pub use crate::world::{Probe, Seat, World};
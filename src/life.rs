@@ -0,0 +1,258 @@
+use crate::easing::{smoothstep, Easing};
+use pixel_canvas::Color;
+use std::fmt;
+
+/// Trades image quality for speed.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Quality {
+    /// Always sample full size textures.
+    Low,
+    /// Sample smaller mip levels for distant walls.
+    High,
+}
+
+/// Field of view range in radians: narrower is hardly a view, wider bends past what the flat projection takes.
+pub const MIN_FOV_ANGLE: f64 = 0.05;
+pub const MAX_FOV_ANGLE: f64 = 2.0 * std::f64::consts::FRAC_PI_3;
+
+/// Ray depth range in map cells.
+pub const MIN_WALL_CHECK_DEPTH: f64 = 0.1;
+pub const MAX_WALL_CHECK_DEPTH: f64 = 1024.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LifeError {
+    FieldOfView(f64),
+    MaxDepth(f64),
+}
+
+impl fmt::Display for LifeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LifeError::FieldOfView(fov) => {
+                write!(f, "field of view {} is not between {:.2} and {:.2}", fov, MIN_FOV_ANGLE, MAX_FOV_ANGLE)
+            }
+            LifeError::MaxDepth(depth) => write!(
+                f,
+                "max wall check depth {} is not between {} and {}",
+                depth, MIN_WALL_CHECK_DEPTH, MAX_WALL_CHECK_DEPTH
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LifeError {}
+
+/// Default for `Life::zoom_time`.
+pub const ZOOM_TIME: f64 = 0.25;
+
+/// Default for `Life::far_fade`.
+pub const FAR_FADE: f64 = 3.0;
+
+/// Default for `Life::horizon_color`.
+pub const HORIZON_COLOR: Color = Color { r: 170, g: 200, b: 235 };
+
+/// Default for `Life::outline_color`.
+pub const OUTLINE_COLOR: Color = Color { r: 15, g: 15, b: 20 };
+
+/// Default for `Life::max_mirror_bounces`.
+pub const MAX_MIRROR_BOUNCES: u32 = 4;
+
+/// Default for `Life::max_pitch`.
+pub const MAX_PITCH: f64 = 0.25;
+
+/// Default for `Life::max_sprites`.
+pub const MAX_SPRITES: usize = 64;
+
+/// Default for `Life::wall_gradient`.
+pub const WALL_GRADIENT: f64 = 0.2;
+
+/// How far floor is drawn in full at `Quality::Low` when `Life::floor_distance_limit` isn't set, in map cells.
+pub const LOW_QUALITY_FLOOR_DISTANCE: f64 = 8.0;
+
+/// Default for `Life::floor_grid_color`.
+pub const FLOOR_GRID_COLOR: Color = Color { r: 40, g: 230, b: 255 };
+
+/// Default for `Life::floor_grid_width`, in map cells.
+pub const FLOOR_GRID_WIDTH: f64 = 0.03;
+
+/// Default for `Life::floor_fog_color`.
+pub const FLOOR_FOG_COLOR: Color = Color { r: 20, g: 6, b: 1 };
+
+#[derive(Clone)]
+pub struct Life {
+    /// Field of view this frame, eased between `base_fov_angle` and `zoom_fov_angle` by `update_zoom`.
+    pub fov_angle: f64,
+    pub base_fov_angle: f64,
+    pub zoom_fov_angle: f64,
+    /// Seconds to zoom all the way in or out.
+    pub zoom_time: f64,
+    pub zoom_easing: Easing,
+    /// How far zoomed in, from 0.0 to 1.0, before easing.
+    pub zoom: f64,
+    pub max_wall_check_depth: f64,
+    /// Cells before `max_wall_check_depth` walls fade into the sky, so the edge of sight is soft; 0.0 is off.
+    pub far_fade: f64,
+    /// How far away a wall can be and still be aimed at.
+    pub interaction_distance: f64,
+    pub quality: Quality,
+    /// Resolution of the 3D view as a fraction of the window's, above 0.0 up to 1.0, stretched to fill it.
+    pub internal_scale: f64,
+    /// Mirror bounces a ray may take before the next mirror is drawn as a plain wall.
+    pub max_mirror_bounces: u32,
+    /// How much darker wall tops are than bottoms, 0.0 even to 1.0 black; negative darkens the bottom.
+    pub wall_gradient: f64,
+    /// Most sprites drawn a frame, the nearest ones.
+    pub max_sprites: usize,
+    /// Tint the four sides of walls apart, see `render::face_tint`.
+    pub face_tint: bool,
+    /// Furthest the horizon moves from the middle, in screen heights: pitch shears rather than tilts, so walls distort past this.
+    pub max_pitch: f64,
+    /// Shown above the walls on a map without a sky and behind the far clip fade.
+    pub background: Color,
+    /// Draw a faint line across the 3D view at eye level, see `render::horizon_line_row`.
+    pub horizon_line: bool,
+    pub horizon_color: Color,
+    /// Outline walls where the distance jumps by more than this from column to column, 0.0 for none.
+    pub outline: f64,
+    pub outline_color: Color,
+    /// With `outline` on, outline the tops and bottoms of walls as well.
+    pub outline_wall_ends: bool,
+    /// Strength of the glow around lights in view, hidden by nearer walls, see `render::light_shaft_glow`; 0.0 is off.
+    pub light_shafts: f64,
+    /// Rows of floor under each wall blended towards the wall's bottom color, see `render::floor_blend`.
+    pub floor_blend_rows: usize,
+    /// Heading and FOV to cast rays across instead of the player's, for debugging; collision still uses the real position.
+    pub frozen_rays: Option<(f64, f64)>,
+    /// Fraction of a column each ray is nudged sideways by, for `TemporalAa`.
+    pub ray_jitter: f64,
+    /// Floor further than this is drawn flat in `floor_fog_color`; the ceiling is sky and isn't limited. `None` goes by `quality`.
+    pub floor_distance_limit: Option<f64>,
+    pub floor_fog_color: Color,
+    /// Spacing in map cells of lines drawn across the floor, lined up with the cells at 1.0; `None` draws plain floor.
+    pub floor_grid: Option<f64>,
+    pub floor_grid_color: Color,
+    /// Line width up close, in map cells; further away lines stay about a pixel wide.
+    pub floor_grid_width: f64,
+    /// Draw the field of view's edge rays on the map from above.
+    pub map_fov_edges: bool,
+    /// Mark where the ray straight ahead hits on the map from above.
+    pub map_center_hit: bool,
+}
+
+impl Life {
+    /// Out of range values are clamped into it, NaN falls back to 45 degrees or 16 cells; see `try_new`.
+    pub fn new(fov_angle: f64, max_wall_check_depth: f64) -> Self {
+        let fov_angle = if fov_angle.is_nan() { std::f64::consts::FRAC_PI_4 } else { fov_angle };
+        let max_wall_check_depth = if max_wall_check_depth.is_nan() { 16.0 } else { max_wall_check_depth };
+        Self::unchecked(
+            fov_angle.clamp(MIN_FOV_ANGLE, MAX_FOV_ANGLE),
+            max_wall_check_depth.clamp(MIN_WALL_CHECK_DEPTH, MAX_WALL_CHECK_DEPTH),
+        )
+    }
+
+    /// Like `new`, but out of range values are an error.
+    pub fn try_new(fov_angle: f64, max_wall_check_depth: f64) -> Result<Self, LifeError> {
+        if !(MIN_FOV_ANGLE..=MAX_FOV_ANGLE).contains(&fov_angle) {
+            return Err(LifeError::FieldOfView(fov_angle));
+        }
+        if !(MIN_WALL_CHECK_DEPTH..=MAX_WALL_CHECK_DEPTH).contains(&max_wall_check_depth) {
+            return Err(LifeError::MaxDepth(max_wall_check_depth));
+        }
+        Ok(Self::unchecked(fov_angle, max_wall_check_depth))
+    }
+
+    fn unchecked(fov_angle: f64, max_wall_check_depth: f64) -> Self {
+        Self {
+            fov_angle,
+            base_fov_angle: fov_angle,
+            zoom_fov_angle: fov_angle / 3.0,
+            zoom_time: ZOOM_TIME,
+            zoom_easing: smoothstep,
+            zoom: 0.0,
+            max_wall_check_depth,
+            far_fade: FAR_FADE,
+            interaction_distance: 2.0,
+            quality: Quality::High,
+            internal_scale: 1.0,
+            max_mirror_bounces: MAX_MIRROR_BOUNCES,
+            wall_gradient: WALL_GRADIENT,
+            max_sprites: MAX_SPRITES,
+            face_tint: false,
+            max_pitch: MAX_PITCH,
+            background: Color { r: 0, g: 0, b: 0 },
+            horizon_line: false,
+            horizon_color: HORIZON_COLOR,
+            outline: 0.0,
+            outline_color: OUTLINE_COLOR,
+            outline_wall_ends: false,
+            frozen_rays: None,
+            ray_jitter: 0.0,
+            floor_distance_limit: None,
+            floor_fog_color: FLOOR_FOG_COLOR,
+            light_shafts: 0.0,
+            floor_blend_rows: 0,
+            floor_grid: None,
+            floor_grid_color: FLOOR_GRID_COLOR,
+            floor_grid_width: FLOOR_GRID_WIDTH,
+            map_fov_edges: true,
+            map_center_hit: false,
+        }
+    }
+
+    /// `floor_distance_limit`, or what `quality` calls for when it isn't set.
+    pub fn floor_limit(&self) -> f64 {
+        match (self.floor_distance_limit, self.quality) {
+            (Some(limit), _) => limit,
+            (None, Quality::Low) => LOW_QUALITY_FLOOR_DISTANCE,
+            (None, Quality::High) => f64::INFINITY,
+        }
+    }
+
+    /// Zoom `dt` seconds further in or out.
+    pub fn update_zoom(&mut self, zooming: bool, dt: f64) {
+        let step = if self.zoom_time > 0.0 { dt / self.zoom_time } else { 1.0 };
+        self.zoom = if zooming { (self.zoom + step).min(1.0) } else { (self.zoom - step).max(0.0) };
+        self.fov_angle = self.base_fov_angle + (self.zoom_fov_angle - self.base_fov_angle) * (self.zoom_easing)(self.zoom);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fov_converges_to_target() {
+        let mut life = Life::new(1.0, 16.0);
+        life.zoom_fov_angle = 0.25;
+        let mut previous = life.fov_angle;
+        for _ in 0..10 {
+            life.update_zoom(true, 0.02);
+            assert!(life.fov_angle < previous);
+            previous = life.fov_angle;
+        }
+        for _ in 0..10 {
+            life.update_zoom(true, 0.02);
+        }
+        assert_eq!(life.fov_angle, 0.25);
+        for _ in 0..20 {
+            life.update_zoom(false, 0.02);
+        }
+        assert_eq!(life.fov_angle, 1.0);
+    }
+
+    #[test]
+    fn out_of_range_values_are_rejected_or_clamped() {
+        for fov in [0.0, -1.0, 3.0, f64::NAN] {
+            assert!(matches!(Life::try_new(fov, 16.0), Err(LifeError::FieldOfView(_))));
+        }
+        for depth in [0.0, -5.0, f64::INFINITY, f64::NAN] {
+            assert!(matches!(Life::try_new(1.0, depth), Err(LifeError::MaxDepth(_))));
+        }
+        assert!(Life::try_new(1.0, 16.0).is_ok());
+
+        let life = Life::new(-1.0, 1e9);
+        assert_eq!((life.fov_angle, life.max_wall_check_depth), (MIN_FOV_ANGLE, MAX_WALL_CHECK_DEPTH));
+        let life = Life::new(f64::NAN, f64::NAN);
+        assert_eq!((life.fov_angle, life.max_wall_check_depth), (std::f64::consts::FRAC_PI_4, 16.0));
+    }
+}
@@ -0,0 +1,50 @@
+/// Default for `Lift::speed`, in wall heights per second.
+pub const LIFT_SPEED: f64 = 0.25;
+
+/// Default for `Lift::wait`, in seconds.
+pub const LIFT_WAIT: f64 = 1.0;
+
+/// A cell whose floor moves between `low` and `high`, waiting at each end; keep `high` below eye level, only platforms under the horizon are drawn.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Lift {
+    pub x: u16,
+    pub y: u16,
+    pub low: f64,
+    pub high: f64,
+    /// In wall heights per second.
+    pub speed: f64,
+    /// Seconds it stops at either end.
+    pub wait: f64,
+    height: f64,
+    rising: bool,
+    waited: f64,
+}
+
+impl Lift {
+    /// A lift in cell `(x, y)` starting at `low` on its way up to `high`.
+    pub fn new(x: u16, y: u16, low: f64, high: f64) -> Self {
+        Self { x, y, low, high, speed: LIFT_SPEED, wait: LIFT_WAIT, height: low, rising: true, waited: 0.0 }
+    }
+
+    /// Height of the lift's floor right now.
+    pub fn height(&self) -> f64 {
+        self.height
+    }
+
+    /// Move the floor on by `dt` seconds.
+    pub fn update(&mut self, dt: f64) {
+        if self.waited < self.wait {
+            self.waited += dt;
+            return;
+        }
+        let target = if self.rising { self.high } else { self.low };
+        let step = self.speed.abs() * dt;
+        if (target - self.height).abs() <= step {
+            self.height = target;
+            self.rising = !self.rising;
+            self.waited = 0.0;
+        } else {
+            self.height += step * (target - self.height).signum();
+        }
+    }
+}
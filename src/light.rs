@@ -0,0 +1,65 @@
+use pixel_canvas::Color;
+
+/// A colored point light, brightening and tinting walls and floor around it.
+#[derive(Clone, Copy)]
+pub struct Light {
+    pub x: f64,
+    pub y: f64,
+    pub color: Color,
+    /// Strength at the light, 1.0 doubling a surface's brightness in the light's color.
+    pub intensity: f64,
+    /// Distance in map cells where the light stops reaching.
+    pub radius: f64,
+}
+
+impl Light {
+    pub fn new(x: f64, y: f64, color: Color, intensity: f64, radius: f64) -> Self {
+        Self { x, y, color, intensity, radius }
+    }
+
+    /// How strongly the light reaches `(x, y)`, falling off quadratically to zero at `radius`.
+    pub fn attenuation(&self, x: f64, y: f64) -> f64 {
+        let falloff = (1.0 - (x - self.x).hypot(y - self.y) / self.radius).max(0.0);
+        self.intensity * falloff * falloff
+    }
+}
+
+/// Sum of every light's color at `(x, y)` weighted by its attenuation, per channel.
+pub fn light_tint(lights: &[Light], x: f64, y: f64) -> [f64; 3] {
+    lights.iter().fold([0.0; 3], |tint, light| {
+        let attenuation = light.attenuation(x, y);
+        [
+            tint[0] + attenuation * light.color.r as f64 / 255.0,
+            tint[1] + attenuation * light.color.g as f64 / 255.0,
+            tint[2] + attenuation * light.color.b as f64 / 255.0,
+        ]
+    })
+}
+
+/// Light `base` by a tint from `light_tint`, channels saturating at 255.
+pub fn apply_tint(base: Color, tint: [f64; 3]) -> Color {
+    let channel = |value: u8, tint: f64| (value as f64 * (1.0 + tint)).min(255.0) as u8;
+    Color { r: channel(base.r, tint[0]), g: channel(base.g, tint[1]), b: channel(base.b, tint[2]) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn two_colored_lights_add_up() {
+        let red = Light::new(2.0, 2.0, Color { r: 255, g: 0, b: 0 }, 1.0, 4.0);
+        let blue = Light::new(2.0, 4.0, Color { r: 0, g: 0, b: 255 }, 1.0, 4.0);
+        let tint = light_tint(&[red, blue], 2.0, 3.0);
+        // a cell away from both, (1 - 1/4)² of each
+        assert!((tint[0] - 0.5625).abs() < 1e-9);
+        assert_eq!(tint[1], 0.0);
+        assert!((tint[2] - 0.5625).abs() < 1e-9);
+
+        let gray = Color { r: 100, g: 100, b: 100 };
+        let lit = apply_tint(gray, tint);
+        assert_eq!((lit.r, lit.g, lit.b), (156, 100, 156));
+        let bright = apply_tint(Color { r: 200, g: 200, b: 200 }, tint);
+        assert_eq!((bright.r, bright.g, bright.b), (255, 200, 255));
+    }
+}
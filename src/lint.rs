@@ -0,0 +1,195 @@
+use crate::map::{Map, FLOOR_GLYPH, LOW_CEILING_GLYPH, PILLAR_GLYPH, RAISED_GLYPH, SPAWN_GLYPH, WALL_KINDS};
+use std::collections::VecDeque;
+use std::fmt;
+
+/// A problem `Map::lint` found, regions given by their first cell in layout order and their size.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MapWarning {
+    /// The layout doesn't hold `width * height` cells. Nothing else is checked.
+    WrongSize { expected: usize, found: usize },
+    UnknownGlyph { x: u16, y: u16, glyph: char },
+    /// A border cell the player could walk through, off the edge of the map.
+    OpenBorder { x: u16, y: u16 },
+    MissingSpawn,
+    /// Open floor the player can't walk to from the spawn.
+    UnreachableArea { x: u16, y: u16, cells: usize },
+    /// Open floor that isn't even visible from anywhere the player can walk.
+    HiddenArea { x: u16, y: u16, cells: usize },
+}
+
+impl fmt::Display for MapWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MapWarning::WrongSize { expected, found } => {
+                write!(f, "layout has {} cells, expected {}", found, expected)
+            }
+            MapWarning::UnknownGlyph { x, y, glyph } => write!(f, "unknown glyph {:?} at ({}, {})", glyph, x, y),
+            MapWarning::OpenBorder { x, y } => write!(f, "border isn't closed at ({}, {})", x, y),
+            MapWarning::MissingSpawn => write!(f, "no spawn, mark one cell with {:?}", SPAWN_GLYPH),
+            MapWarning::UnreachableArea { x, y, cells } => {
+                write!(f, "{} cells starting at ({}, {}) can't be reached from the spawn", cells, x, y)
+            }
+            MapWarning::HiddenArea { x, y, cells } => {
+                write!(f, "{} cells starting at ({}, {}) can never be seen", cells, x, y)
+            }
+        }
+    }
+}
+
+impl Map {
+    /// Check the map for mistakes a level author would want to know about before playing it.
+    pub fn lint(&self) -> Vec<MapWarning> {
+        let mut warnings = Vec::new();
+
+        let expected = self.width as usize * self.height as usize;
+        if self.layout.len() != expected {
+            warnings.push(MapWarning::WrongSize { expected, found: self.layout.len() });
+            return warnings;
+        }
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let glyph = self.glyph(x, y);
+                let known = [FLOOR_GLYPH, SPAWN_GLYPH, RAISED_GLYPH, LOW_CEILING_GLYPH, PILLAR_GLYPH].contains(&glyph) || WALL_KINDS.iter().any(|kind| kind.glyph == glyph);
+                if !known {
+                    warnings.push(MapWarning::UnknownGlyph { x, y, glyph });
+                }
+
+                let border = x == 0 || y == 0 || x == self.width - 1 || y == self.height - 1;
+                if border && !self.blocks_movement(x as f64, y as f64) {
+                    warnings.push(MapWarning::OpenBorder { x, y });
+                }
+            }
+        }
+
+        let Some(spawn) = self.spawn() else {
+            warnings.push(MapWarning::MissingSpawn);
+            return warnings;
+        };
+
+        let reachable = self.flood_fill(vec![spawn], |x, y| !self.blocks_movement(x as f64, y as f64));
+        let walkable: Vec<(u16, u16)> = self.cells().filter(|&(x, y)| reachable[self.index(x, y)]).collect();
+        // sight carries on from anywhere the player can stand through anything see-through
+        let visible = self.flood_fill(walkable, |x, y| !self.blocks_sight(x as f64, y as f64));
+
+        let open = |x: u16, y: u16| !self.blocks_movement(x as f64, y as f64);
+        for (x, y, cells) in self.regions(|x, y| open(x, y) && !reachable[self.index(x, y)]) {
+            warnings.push(MapWarning::UnreachableArea { x, y, cells });
+        }
+        for (x, y, cells) in self.regions(|x, y| open(x, y) && !visible[self.index(x, y)]) {
+            warnings.push(MapWarning::HiddenArea { x, y, cells });
+        }
+
+        warnings
+    }
+
+    fn cells(&self) -> impl Iterator<Item = (u16, u16)> + '_ {
+        (0..self.height).flat_map(move |y| (0..self.width).map(move |x| (x, y)))
+    }
+
+    fn neighbours(&self, x: u16, y: u16) -> impl Iterator<Item = (u16, u16)> + '_ {
+        let candidates = [
+            (x.checked_sub(1), Some(y)),
+            (x.checked_add(1), Some(y)),
+            (Some(x), y.checked_sub(1)),
+            (Some(x), y.checked_add(1)),
+        ];
+        candidates
+            .into_iter()
+            .filter_map(|cell| match cell {
+                (Some(x), Some(y)) => Some((x, y)),
+                _ => None,
+            })
+            .filter(move |&(x, y)| !self.out_of_bounds(x, y))
+    }
+
+    /// Every cell connected to `start` through cells where `passable` holds,
+    /// as a per-cell flag. The start cells are always included.
+    fn flood_fill(&self, start: Vec<(u16, u16)>, passable: impl Fn(u16, u16) -> bool) -> Vec<bool> {
+        let mut reached = vec![false; self.layout.len()];
+        let mut queue: VecDeque<(u16, u16)> = start.into_iter().collect();
+        for &(x, y) in &queue {
+            reached[self.index(x, y)] = true;
+        }
+
+        while let Some((x, y)) = queue.pop_front() {
+            for (next_x, next_y) in self.neighbours(x, y) {
+                let index = self.index(next_x, next_y);
+                if !reached[index] && passable(next_x, next_y) {
+                    reached[index] = true;
+                    queue.push_back((next_x, next_y));
+                }
+            }
+        }
+
+        reached
+    }
+
+    /// Connected groups of cells where `selected` holds, as their first cell
+    /// and size.
+    fn regions(&self, selected: impl Fn(u16, u16) -> bool) -> Vec<(u16, u16, usize)> {
+        let mut seen = vec![false; self.layout.len()];
+        let mut regions = Vec::new();
+
+        for (x, y) in self.cells() {
+            if seen[self.index(x, y)] || !selected(x, y) {
+                continue;
+            }
+            let region = self.flood_fill(vec![(x, y)], &selected);
+            let cells = region.iter().filter(|&&reached| reached).count();
+            for (index, reached) in region.into_iter().enumerate() {
+                seen[index] |= reached;
+            }
+            regions.push((x, y, cells));
+        }
+
+        regions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lint(source: &str) -> Vec<MapWarning> {
+        Map::parse(source).unwrap().lint()
+    }
+
+    #[test]
+    fn clean_map_has_no_warnings() {
+        assert_eq!(lint("#####\n#P..#\n#####"), Vec::new());
+    }
+
+    #[test]
+    fn wrong_size() {
+        let mut map = Map::parse("#####\n#P..#\n#####").unwrap();
+        map.layout.pop();
+        assert_eq!(map.lint(), vec![MapWarning::WrongSize { expected: 15, found: 14 }]);
+    }
+
+    #[test]
+    fn unknown_glyph() {
+        assert_eq!(lint("#####\n#P?.#\n#####"), vec![MapWarning::UnknownGlyph { x: 2, y: 1, glyph: '?' }]);
+    }
+
+    #[test]
+    fn open_border() {
+        assert_eq!(lint("##.##\n#P..#\n#####"), vec![MapWarning::OpenBorder { x: 2, y: 0 }]);
+    }
+
+    #[test]
+    fn missing_spawn() {
+        assert_eq!(lint("#####\n#...#\n#####"), vec![MapWarning::MissingSpawn]);
+    }
+
+    #[test]
+    fn unreachable_area_seen_through_grate() {
+        assert_eq!(lint("#######\n#P.%..#\n#######"), vec![MapWarning::UnreachableArea { x: 4, y: 1, cells: 2 }]);
+    }
+
+    #[test]
+    fn hidden_area() {
+        let warnings = lint("#######\n#P.#..#\n#######");
+        assert!(warnings.contains(&MapWarning::HiddenArea { x: 4, y: 1, cells: 2 }));
+    }
+}
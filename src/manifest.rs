@@ -0,0 +1,278 @@
+/*
+    Textures for wall kinds come from a small text file, one assignment per
+    line:
+
+        // comments start with two slashes, blank lines are skipped
+        # = textures/bricks.ppm
+        % = textures/grate.ppm
+
+    The glyph on the left is a wall kind's, the path on the right a binary PPM
+    image relative to the manifest itself. Only walls take textures, a
+    `sprite` line is an error rather than being skipped.
+
+    Lines starting with `trigger` tie an action to a map cell instead, fired
+    when the player walks into it, either only the first time (`once`) or
+    every time (`repeat`):
+
+        trigger 3 4 once = open 5 4
+        trigger 3 4 once = message The gate grinds open
+        trigger 8 2 repeat = spawn 9 2
+        trigger 1 1 once = event lights-out
+
+    `open x y` turns a wall into floor, `spawn x y` puts a sprite in a cell,
+    and `message` and `event` pass their text on to the program, see
+    `World::on_trigger`. `set name value` and `add name amount` change a
+    named value in `World::state`, and `if name` makes a trigger wait until
+    the value is at least 1, or at least a given number:
+
+        trigger 2 6 once = set key 1
+        trigger 3 4 repeat if key = open 5 4
+        trigger 7 7 repeat = add coins 1
+        trigger 9 9 once if coins 3 = message You can afford it
+ */
+
+use crate::map::{Map, WALL_KINDS};
+use crate::texture::Texture;
+use crate::trigger::{spawned_sprite, Trigger, TriggerAction};
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Why a texture manifest couldn't be loaded. Lines are counted from 1.
+#[derive(Debug)]
+pub enum ManifestError {
+    Read { path: PathBuf, error: io::Error },
+    Syntax { line: usize },
+    UnknownKind { line: usize, glyph: char },
+    /// Sprites are drawn flat, there's nothing to give a texture to.
+    SpriteTexture { line: usize },
+    Texture { line: usize, path: PathBuf, error: io::Error },
+    Trigger { line: usize },
+    UnknownAction { line: usize, action: String },
+    /// A trigger or the cell its action is for is outside the map.
+    OffMap { line: usize, x: u16, y: u16 },
+}
+
+impl fmt::Display for ManifestError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ManifestError::Read { path, error } => write!(f, "can't read {}: {}", path.display(), error),
+            ManifestError::Syntax { line } => write!(f, "line {}: expected `glyph = path`", line),
+            ManifestError::UnknownKind { line, glyph } => write!(f, "line {}: '{}' isn't a wall kind", line, glyph),
+            ManifestError::SpriteTexture { line } => write!(f, "line {}: only wall kinds take textures, not sprites", line),
+            ManifestError::Texture { line, path, error } => {
+                write!(f, "line {}: can't load {}: {}", line, path.display(), error)
+            }
+            ManifestError::Trigger { line } => {
+                write!(f, "line {}: expected `trigger x y once|repeat [if name [n]] = action`", line)
+            },
+            ManifestError::UnknownAction { line, action } => write!(f, "line {}: '{}' isn't a trigger action", line, action),
+            ManifestError::OffMap { line, x, y } => write!(f, "line {}: cell ({}, {}) is outside the map", line, x, y),
+        }
+    }
+}
+
+impl std::error::Error for ManifestError {}
+
+/// The assignments in a manifest with their lines, paths left as written.
+pub fn parse_manifest(source: &str) -> Result<Vec<(usize, char, PathBuf)>, ManifestError> {
+    let mut entries = Vec::new();
+    for (index, text) in source.lines().enumerate() {
+        let line = index + 1;
+        let text = text.trim();
+        if text.is_empty() || text.starts_with("//") || is_trigger_line(text) {
+            continue;
+        }
+
+        if text.split_whitespace().next() == Some("sprite") {
+            return Err(ManifestError::SpriteTexture { line });
+        }
+        let (glyph, path) = text.split_once('=').ok_or(ManifestError::Syntax { line })?;
+        let (glyph, path) = (glyph.trim(), path.trim());
+        let mut chars = glyph.chars();
+        let (Some(glyph), None) = (chars.next(), chars.next()) else {
+            return Err(ManifestError::Syntax { line });
+        };
+        if path.is_empty() {
+            return Err(ManifestError::Syntax { line });
+        }
+        if !WALL_KINDS.iter().any(|kind| kind.glyph == glyph) {
+            return Err(ManifestError::UnknownKind { line, glyph });
+        }
+        entries.push((line, glyph, PathBuf::from(path)));
+    }
+    Ok(entries)
+}
+
+fn is_trigger_line(text: &str) -> bool {
+    text.split_whitespace().next() == Some("trigger")
+}
+
+/// The triggers in a manifest, in order, with the lines they're on.
+pub fn parse_triggers(source: &str) -> Result<Vec<(usize, Trigger)>, ManifestError> {
+    let mut triggers = Vec::new();
+    for (index, text) in source.lines().enumerate() {
+        let line = index + 1;
+        let text = text.trim();
+        if !is_trigger_line(text) {
+            continue;
+        }
+
+        let (cell, action) = text.split_once('=').ok_or(ManifestError::Trigger { line })?;
+        let coordinate = |word: Option<&str>| word.and_then(|word| word.parse::<u16>().ok());
+        let mut words = cell.split_whitespace().skip(1);
+        let (Some(x), Some(y), Some(when)) = (coordinate(words.next()), coordinate(words.next()), words.next()) else {
+            return Err(ManifestError::Trigger { line });
+        };
+        let requires = match (words.next(), words.next(), words.next(), words.next()) {
+            (None, ..) => None,
+            (Some("if"), Some(name), None, None) => Some((name, 1)),
+            (Some("if"), Some(name), Some(at_least), None) => {
+                Some((name, at_least.parse::<i64>().map_err(|_| ManifestError::Trigger { line })?))
+            }
+            _ => return Err(ManifestError::Trigger { line }),
+        };
+
+        let action = action.trim();
+        let (name, rest) = action.split_once(char::is_whitespace).unwrap_or((action, ""));
+        let rest = rest.trim();
+        let cell = || {
+            let mut words = rest.split_whitespace();
+            match (coordinate(words.next()), coordinate(words.next()), words.next()) {
+                (Some(x), Some(y), None) => Ok((x, y)),
+                _ => Err(ManifestError::Trigger { line }),
+            }
+        };
+        let action = match name {
+            "open" => {
+                let (x, y) = cell()?;
+                TriggerAction::Open { x, y }
+            }
+            "spawn" => {
+                let (x, y) = cell()?;
+                TriggerAction::Spawn(spawned_sprite(x, y))
+            }
+            "message" => TriggerAction::Message(rest.to_string()),
+            "event" if !rest.is_empty() => TriggerAction::Event(rest.to_string()),
+            "event" | "" => return Err(ManifestError::Trigger { line }),
+            "set" | "add" => {
+                let mut words = rest.split_whitespace();
+                let (Some(key), Some(Ok(number)), None) =
+                    (words.next(), words.next().map(str::parse::<i64>), words.next())
+                else {
+                    return Err(ManifestError::Trigger { line });
+                };
+                if name == "set" {
+                    TriggerAction::Set { name: key.to_string(), value: number }
+                } else {
+                    TriggerAction::Add { name: key.to_string(), amount: number }
+                }
+            }
+            _ => return Err(ManifestError::UnknownAction { line, action: name.to_string() }),
+        };
+
+        let mut trigger = match when {
+            "once" => Trigger::once(x, y, action),
+            "repeat" => Trigger::repeating(x, y, action),
+            _ => return Err(ManifestError::Trigger { line }),
+        };
+        if let Some((name, at_least)) = requires {
+            trigger = trigger.requiring(name, at_least);
+        }
+        triggers.push((line, trigger));
+    }
+    Ok(triggers)
+}
+
+/// Every texture the manifest at `path` lists, by glyph, the last one for a glyph listed twice.
+pub fn load_manifest(path: impl AsRef<Path>) -> Result<HashMap<char, Texture>, ManifestError> {
+    let path = path.as_ref();
+    load_textures_from(&read_manifest(path)?, path)
+}
+
+fn read_manifest(path: &Path) -> Result<String, ManifestError> {
+    std::fs::read_to_string(path).map_err(|error| ManifestError::Read { path: path.to_path_buf(), error })
+}
+
+/// The textures listed in `source`, the contents of the manifest at `path`.
+fn load_textures_from(source: &str, path: &Path) -> Result<HashMap<char, Texture>, ManifestError> {
+    let base = path.parent().unwrap_or(Path::new(""));
+    let mut textures = HashMap::new();
+    for (line, glyph, texture_path) in parse_manifest(source)? {
+        let texture_path = base.join(texture_path);
+        let texture = Texture::load_ppm(&texture_path)
+            .map_err(|error| ManifestError::Texture { line, path: texture_path, error })?;
+        textures.insert(glyph, texture);
+    }
+    Ok(textures)
+}
+
+impl Map {
+    /// Load the manifest's textures over any this map has for the same glyphs.
+    pub fn load_textures(&mut self, path: impl AsRef<Path>) -> Result<(), ManifestError> {
+        self.textures.extend(load_manifest(path)?);
+        Ok(())
+    }
+
+    /// Load the manifest's textures and triggers, or nothing if a trigger is off the map.
+    pub fn load_manifest(&mut self, path: impl AsRef<Path>) -> Result<(), ManifestError> {
+        let path = path.as_ref();
+        let source = read_manifest(path)?;
+        let triggers = parse_triggers(&source)?;
+        for (line, trigger) in &triggers {
+            let target = match trigger.action {
+                TriggerAction::Open { x, y } => Some((x, y)),
+                TriggerAction::Spawn(sprite) => Some((sprite.x as u16, sprite.y as u16)),
+                TriggerAction::Message(_)
+                | TriggerAction::Event(_)
+                | TriggerAction::Set { .. }
+                | TriggerAction::Add { .. } => None,
+            };
+            for (x, y) in std::iter::once((trigger.x, trigger.y)).chain(target) {
+                if self.out_of_bounds(x, y) {
+                    return Err(ManifestError::OffMap { line: *line, x, y });
+                }
+            }
+        }
+
+        self.textures.extend(load_textures_from(&source, path)?);
+        self.triggers.extend(triggers.into_iter().map(|(_, trigger)| trigger));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ppm::save_ppm;
+    use pixel_canvas::Color;
+
+    #[test]
+    fn manifest_maps_two_kinds_to_two_textures() {
+        let dir = std::env::temp_dir().join(format!("manifest-test-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("textures")).unwrap();
+        let red = [Color { r: 255, g: 0, b: 0 }; 4 * 2];
+        let blue = [Color { r: 0, g: 0, b: 255 }; 2 * 2];
+        save_ppm(dir.join("textures/bricks.ppm"), &red, 4, 2).unwrap();
+        save_ppm(dir.join("textures/grate.ppm"), &blue, 2, 2).unwrap();
+        let manifest = dir.join("textures.txt");
+        std::fs::write(&manifest, "// walls\n# = textures/bricks.ppm\n\n% = textures/grate.ppm\n").unwrap();
+
+        let textures = load_manifest(&manifest).unwrap();
+        assert_eq!(textures.len(), 2);
+        assert_eq!((textures[&'#'].width(), textures[&'#'].sample(0, 0.5, 0.5).r), (4, 255));
+        assert_eq!((textures[&'%'].width(), textures[&'%'].sample(0, 0.5, 0.5).b), (2, 255));
+
+        std::fs::write(&manifest, "# = textures/missing.ppm\n").unwrap();
+        assert!(matches!(load_manifest(&manifest), Err(ManifestError::Texture { line: 1, .. })));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn sprite_lines_are_reported() {
+        let error = parse_manifest("# = bricks.ppm\nsprite barrel = barrel.ppm\n").unwrap_err();
+        assert!(matches!(error, ManifestError::SpriteTexture { line: 2 }));
+        assert!(matches!(parse_manifest("x = bricks.ppm"), Err(ManifestError::UnknownKind { line: 1, glyph: 'x' })));
+    }
+}
@@ -0,0 +1,339 @@
+use crate::lift::Lift;
+use crate::light::Light;
+use crate::portal::Portal;
+use crate::sky::Sky;
+use crate::sprite::Sprite;
+use crate::stream::StreamedMap;
+use crate::texture::{Texture, WallPattern};
+use crate::trigger::Trigger;
+use std::collections::HashMap;
+use std::fmt;
+
+/// What a glyph in the map layout stands for.
+#[derive(Debug)]
+pub struct WallKind {
+    pub glyph: char,
+    /// The player can't walk into cells of this kind.
+    pub blocks_movement: bool,
+    /// Rays stop at cells of this kind, so they're drawn and hide what's behind.
+    pub blocks_sight: bool,
+    /// Rays bounce off cells of this kind instead of stopping, see `cast_ray`.
+    pub reflects: bool,
+    /// Color of glass that rays pass through and pick up some of, `None`
+    /// for anything that isn't glass.
+    pub glass: Option<[u8; 3]>,
+    /// Don't darken or fade with distance, to stay readable however far off.
+    pub fog_immune: bool,
+}
+
+/// Every glyph that isn't listed here is open floor.
+pub const WALL_KINDS: &[WallKind] = &[
+    // plain wall
+    WallKind { glyph: '#', blocks_movement: true, blocks_sight: true, reflects: false, glass: None, fog_immune: false },
+    // grate, can be seen through but not walked through
+    WallKind { glyph: '%', blocks_movement: true, blocks_sight: false, reflects: false, glass: None, fog_immune: false },
+    // curtain, hides what's behind it but can be walked through
+    WallKind { glyph: '+', blocks_movement: false, blocks_sight: true, reflects: false, glass: None, fog_immune: false },
+    // mirror, shows what's in front of it
+    WallKind { glyph: '=', blocks_movement: true, blocks_sight: true, reflects: true, glass: None, fog_immune: false },
+    // green glass, tints what's seen through it
+    WallKind { glyph: '~', blocks_movement: true, blocks_sight: false, reflects: false, glass: Some([60, 200, 120]), fog_immune: false },
+    // beacon, a wall that keeps its full brightness at any distance
+    WallKind { glyph: '*', blocks_movement: true, blocks_sight: true, reflects: false, glass: None, fog_immune: true },
+];
+
+/// Where the player starts, otherwise open floor.
+pub const SPAWN_GLYPH: char = 'P';
+
+/// Walkable floor raised `RAISED_FLOOR_HEIGHT` above the rest.
+pub const RAISED_GLYPH: char = '_';
+
+pub const RAISED_FLOOR_HEIGHT: f64 = 0.25;
+
+/// Open floor under a ceiling at `LOW_CEILING_HEIGHT`, to crawl through crouched.
+pub const LOW_CEILING_GLYPH: char = '-';
+
+pub const LOW_CEILING_HEIGHT: f64 = 0.5;
+
+/// A round pillar, `Map::pillar_radius` across, in the middle of an otherwise open cell.
+pub const PILLAR_GLYPH: char = 'o';
+
+/// Default for `Map::pillar_radius`.
+pub const PILLAR_RADIUS: f64 = 0.3;
+
+/// Default for `Map::hit_epsilon`, in map cells.
+pub const HIT_EPSILON: f64 = 1e-6;
+
+/// Plain open floor.
+pub const FLOOR_GLYPH: char = '.';
+
+/// Why a map source couldn't be loaded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MapError {
+    Empty,
+    /// Row `row` (from 0) doesn't have as many cells as the first one.
+    RaggedRow { row: usize, expected: usize, found: usize },
+    TooLarge,
+}
+
+impl fmt::Display for MapError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MapError::Empty => write!(f, "map has no rows"),
+            MapError::RaggedRow { row, expected, found } => {
+                write!(f, "row {} has {} cells, expected {}", row, found, expected)
+            }
+            MapError::TooLarge => write!(f, "map is larger than {} cells on a side", u16::MAX),
+        }
+    }
+}
+
+impl std::error::Error for MapError {}
+
+pub struct Map {
+    pub height: u16,
+    pub width: u16,
+    pub layout: Vec<char>,
+    /// Textures by wall glyph, wall kinds without one are drawn flat.
+    pub textures: HashMap<char, Texture>,
+    /// Patterns by wall glyph for kinds without a texture; kinds in neither are drawn flat.
+    pub patterns: HashMap<char, WallPattern>,
+    pub lights: Vec<Light>,
+    pub sprites: Vec<Sprite>,
+    /// Drawn instead of the plain ceiling when set.
+    pub sky: Option<Sky>,
+    /// Fired as the player walks into their cells, see `World::step`.
+    pub triggers: Vec<Trigger>,
+    /// Cells that lead to other cells, for rays and the player alike.
+    pub portals: Vec<Portal>,
+    /// Cells whose floor goes up and down, see `update_lifts`.
+    pub lifts: Vec<Lift>,
+    /// Radius of pillars, in map cells, up to 0.5 to touch the cell's sides.
+    pub pillar_radius: f64,
+    /// How close to a cell edge a ray's sample counts as on it, going with the cell the ray heads into so seams don't shimmer.
+    pub hit_epsilon: f64,
+    /// Where the cells come from instead of `layout`, see `Map::open_streamed`.
+    pub(crate) stream: Option<StreamedMap>,
+}
+
+impl Map {
+    pub fn new(height: u16, width: u16) -> Self {
+        let layout =
+        "################\
+        #..............#\
+        #..............#\
+        #......####....#\
+        #..............#\
+        #......#########\
+        #..............#\
+        #............###\
+        #.......P......#\
+        #..............#\
+        #..............#\
+        #.##...........#\
+        #......#.......#\
+        #......#.......#\
+        ################".chars().collect(); 
+        Self::from_layout(height, width, layout)
+    }
+
+    /// Load a map from text, one row per line, ignoring blank lines and whitespace around rows.
+    pub fn parse(source: &str) -> Result<Self, MapError> {
+        let rows: Vec<&str> = source.lines().map(str::trim).filter(|row| !row.is_empty()).collect();
+        let width = rows.first().ok_or(MapError::Empty)?.chars().count();
+
+        let mut layout = Vec::with_capacity(width * rows.len());
+        for (row, cells) in rows.iter().enumerate() {
+            let found = cells.chars().count();
+            if found != width {
+                return Err(MapError::RaggedRow { row, expected: width, found });
+            }
+            layout.extend(cells.chars());
+        }
+
+        let width = u16::try_from(width).map_err(|_| MapError::TooLarge)?;
+        let height = u16::try_from(rows.len()).map_err(|_| MapError::TooLarge)?;
+        Ok(Self::from_layout(height, width, layout))
+    }
+
+    pub(crate) fn from_layout(height: u16, width: u16, layout: Vec<char>) -> Self {
+        Self { height, width, layout, textures: HashMap::new(), patterns: HashMap::new(), lights: Vec::new(), sprites: Vec::new(), sky: None, triggers: Vec::new(), portals: Vec::new(), lifts: Vec::new(), pillar_radius: PILLAR_RADIUS, hit_epsilon: HIT_EPSILON, stream: None }
+    }
+
+    /// Position of cell `(x, y)` in `layout`, worked out in `usize` so large maps don't overflow.
+    pub(crate) fn index(&self, x: u16, y: u16) -> usize {
+        y as usize * self.width as usize + x as usize
+    }
+
+    pub fn glyph(&self, x: u16, y: u16) -> char {
+        match &self.stream {
+            Some(stream) => stream.glyph(x, y),
+            None => self.layout[self.index(x, y)],
+        }
+    }
+
+    /// Whether any cell could be `glyph`, always for a streamed map as it isn't read whole.
+    pub fn may_contain(&self, glyph: char) -> bool {
+        self.stream.is_some() || self.layout.contains(&glyph)
+    }
+
+    /// The cell marked with `SPAWN_GLYPH`, if there's one.
+    pub fn spawn(&self) -> Option<(u16, u16)> {
+        let index = self.layout.iter().position(|&glyph| glyph == SPAWN_GLYPH)?;
+        Some(((index % self.width as usize) as u16, (index / self.width as usize) as u16))
+    }
+
+    pub fn wall_kind(&self, x: f64, y: f64) -> Option<&'static WallKind> {
+        let glyph = self.glyph(x as u16, y as u16);
+        WALL_KINDS.iter().find(|kind| kind.glyph == glyph)
+    }
+
+    pub fn texture(&self, x: f64, y: f64) -> Option<&Texture> {
+        let glyph = self.glyph(x as u16, y as u16);
+        self.textures.get(&glyph)
+    }
+
+    /// The pattern to draw on the wall at `(x, y)` if it has no texture.
+    pub fn pattern(&self, x: f64, y: f64) -> WallPattern {
+        let glyph = self.glyph(x as u16, y as u16);
+        self.patterns.get(&glyph).copied().unwrap_or(WallPattern::Flat)
+    }
+
+    /// The portal at cell `(x, y)`, if there's one.
+    pub fn portal(&self, x: u16, y: u16) -> Option<&Portal> {
+        self.portals.iter().find(|portal| (portal.x, portal.y) == (x, y))
+    }
+
+    /// The lift at cell `(x, y)`, if there's one.
+    pub fn lift(&self, x: u16, y: u16) -> Option<&Lift> {
+        self.lifts.iter().find(|lift| (lift.x, lift.y) == (x, y))
+    }
+
+    /// Move every lift on by `dt` seconds. Whether any of them moved.
+    pub fn update_lifts(&mut self, dt: f64) -> bool {
+        let mut moved = false;
+        for lift in &mut self.lifts {
+            let height = lift.height();
+            lift.update(dt);
+            moved |= lift.height() != height;
+        }
+        moved
+    }
+
+    pub fn is_pillar(&self, x: u16, y: u16) -> bool {
+        !self.out_of_bounds(x, y) && self.glyph(x, y) == PILLAR_GLYPH
+    }
+
+    pub fn in_pillar(&self, x: f64, y: f64) -> bool {
+        if x < 0.0 || y < 0.0 || !self.is_pillar(x as u16, y as u16) {
+            return false;
+        }
+        (x - x.floor() - 0.5).hypot(y - y.floor() - 0.5) < self.pillar_radius
+    }
+
+    /// Floor height at `(x, y)` as a fraction of the wall height; a lift cell is where the lift is now.
+    pub fn floor_height(&self, x: f64, y: f64) -> f64 {
+        if x < 0.0 || y < 0.0 || self.out_of_bounds(x as u16, y as u16) {
+            return 0.0;
+        }
+        if let Some(lift) = self.lift(x as u16, y as u16) {
+            return lift.height();
+        }
+        if self.glyph(x as u16, y as u16) == RAISED_GLYPH { RAISED_FLOOR_HEIGHT } else { 0.0 }
+    }
+
+    /// In wall heights, 1.0 outside the map.
+    pub fn ceiling_height(&self, x: f64, y: f64) -> f64 {
+        if x < 0.0 || y < 0.0 || self.out_of_bounds(x as u16, y as u16) {
+            return 1.0;
+        }
+        if self.glyph(x as u16, y as u16) == LOW_CEILING_GLYPH { LOW_CEILING_HEIGHT } else { 1.0 }
+    }
+
+    pub fn has_raised_floor(&self) -> bool {
+        !self.lifts.is_empty() || self.may_contain(RAISED_GLYPH)
+    }
+
+    pub fn is_wall(&self, x: f64, y: f64) -> bool {
+        self.wall_kind(x, y).is_some()
+    }
+
+    /// Whether the player can't be at `(x, y)`. Everything off the map is solid.
+    pub fn blocks_movement(&self, x: f64, y: f64) -> bool {
+        if x < 0.0 || y < 0.0 || self.out_of_bounds(x as u16, y as u16) {
+            return true;
+        }
+        self.wall_kind(x, y).is_some_and(|kind| kind.blocks_movement) || self.in_pillar(x, y)
+    }
+
+    pub fn blocks_sight(&self, x: f64, y: f64) -> bool {
+        self.wall_kind(x, y).is_some_and(|kind| kind.blocks_sight)
+    }
+
+    pub fn reflects(&self, x: f64, y: f64) -> bool {
+        self.wall_kind(x, y).is_some_and(|kind| kind.reflects)
+    }
+
+    /// Color of the glass at `(x, y)`, if it's glass.
+    pub fn glass(&self, x: f64, y: f64) -> Option<[u8; 3]> {
+        self.wall_kind(x, y).and_then(|kind| kind.glass)
+    }
+
+    /// Turn the cell at `(x, y)` into floor, if it's on the map.
+    pub fn open(&mut self, x: u16, y: u16) {
+        if self.out_of_bounds(x, y) {
+            return;
+        }
+        match &mut self.stream {
+            Some(stream) => stream.set_glyph(x, y, FLOOR_GLYPH),
+            None => {
+                let index = self.index(x, y);
+                self.layout[index] = FLOOR_GLYPH;
+            }
+        }
+    }
+
+    pub fn out_of_bounds(&self, x: u16, y: u16) -> bool {
+        x >= self.width || y >= self.height
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ray::cast_ray;
+    use std::f64::consts::FRAC_PI_2;
+
+    #[test]
+    fn large_map_indexes_every_cell() {
+        let width = 300;
+        let mut rows = vec!["#".repeat(width); 300];
+        rows[298].replace_range(width - 3..width - 1, ".+");
+        let map = Map::parse(&rows.join("\n")).unwrap();
+        assert!(map.layout.len() > u16::MAX as usize);
+        assert_eq!(map.glyph(298, 298), '+');
+        assert!(map.blocks_sight(298.5, 298.5));
+        assert!(!map.blocks_movement(298.5, 298.5));
+        assert_eq!(map.texture(298.5, 298.5).map(|texture| texture.span), None);
+        assert_eq!(cast_ray(&map, 297.5, 298.5, FRAC_PI_2, 16.0, 0).cell, Some((298, 298)));
+        assert_eq!(map.lint(), vec![crate::lint::MapWarning::MissingSpawn]);
+    }
+
+    #[test]
+    fn grate_blocks_movement_but_not_sight() {
+        let map = Map::parse("#####\n#.%.#\n#####").unwrap();
+        assert!(map.blocks_movement(2.5, 1.5));
+        assert!(!map.blocks_sight(2.5, 1.5));
+        let hit = cast_ray(&map, 1.5, 1.5, FRAC_PI_2, 16.0, 0);
+        assert_eq!(hit.cell, Some((4, 1)));
+    }
+
+    #[test]
+    fn curtain_blocks_sight_but_not_movement() {
+        let map = Map::parse("#####\n#.+.#\n#####").unwrap();
+        assert!(!map.blocks_movement(2.5, 1.5));
+        assert!(map.blocks_sight(2.5, 1.5));
+        let hit = cast_ray(&map, 1.5, 1.5, FRAC_PI_2, 16.0, 0);
+        assert_eq!(hit.cell, Some((2, 1)));
+    }
+}
@@ -0,0 +1,83 @@
+use crate::light::Light;
+use crate::map::Map;
+use crate::sprite::Sprite;
+use pixel_canvas::Color;
+
+/// Maps built into the binary, one row per line. Cycled through with N.
+pub const DEMO_MAPS: &[&str] = &[
+    // the original rooms
+    "################
+     #..............#
+     #..............#
+     #......####....#
+     #..............#
+     #......#########
+     #..............#
+     #............###
+     #.......P......#
+     #..............#
+     #..............#
+     #.##...........#
+     #......#.......#
+     #......#.......#
+     ################",
+    // a single room with a raised dais and a glass screen
+    "############
+     #..........#
+     #...____...#
+     #...____...#
+     #....P.....#
+     #..........#
+     #..~~~~....#
+     #..........#
+     #..........#
+     ############",
+    // a maze, with a beacon at the far end
+    "################
+     #P.....#.......#
+     #.####.#.#####.#
+     #.#....#.....#.#
+     #.#.#######.#..#
+     #.#.......#.#.##
+     #.#######.#.#..#
+     #.......#.#.##.#
+     #######.#.#....#
+     #.......#.####.#
+     #.#######....#.#
+     #.#.....####.#.#
+     #.#.###......#.#
+     #...#...######.#
+     #.###.#........*
+     ################",
+    // a hall of pillars with a mirrored wall
+    "#==============#
+     #..............#
+     #.o..o..o..o...#
+     #..............#
+     #..............#
+     #.o..o..o..o...#
+     #.......P......#
+     #.o..o..o..o...#
+     #..............#
+     #..............#
+     #.o..o..o..o...#
+     ################",
+];
+
+/// Load one of `DEMO_MAPS` with its lights and sprites, printing anything `lint` finds.
+pub fn demo_map(index: usize) -> Map {
+    let mut map = Map::parse(DEMO_MAPS[index]).expect("built-in maps are well formed");
+    if index == 0 {
+        // a warm torch and a cold one to show off colored lighting
+        map.lights.push(Light::new(2.0, 1.5, Color { r: 255, g: 140, b: 40 }, 1.2, 5.0));
+        map.lights.push(Light::new(12.5, 12.5, Color { r: 60, g: 120, b: 255 }, 1.2, 5.0));
+        let mut barrel = Sprite::new(5.5, 9.5, 0.5, 0.6, Color { r: 60, g: 160, b: 60 });
+        barrel.solid = true;
+        map.sprites.push(barrel);
+        map.sprites.push(Sprite::new(11.5, 4.5, 0.4, 0.9, Color { r: 200, g: 180, b: 60 }));
+    }
+    for warning in map.lint() {
+        eprintln!("map {}: {}", index, warning);
+    }
+    map
+}
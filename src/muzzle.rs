@@ -0,0 +1,68 @@
+use crate::map::Map;
+use crate::player::Player;
+
+/// Default for `Muzzle::forward`, in map cells.
+pub const MUZZLE_FORWARD: f64 = 0.3;
+
+/// Default for `Muzzle::lateral`, in map cells.
+pub const MUZZLE_LATERAL: f64 = 0.1;
+
+/// How finely `Muzzle::point` backs off a wall, in map cells.
+const MUZZLE_BACKOFF_STEP: f64 = 0.02;
+
+/// Where shots leave from: ahead and to the right, where a gun in the right hand would be.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Muzzle {
+    pub forward: f64,
+    /// Negative is left.
+    pub lateral: f64,
+}
+
+impl Default for Muzzle {
+    fn default() -> Self {
+        Self { forward: MUZZLE_FORWARD, lateral: MUZZLE_LATERAL }
+    }
+}
+
+impl Muzzle {
+    /// Where a shot from `player` starts, pulled back towards them out of any wall.
+    pub fn point(&self, player: &Player, map: &Map) -> (f64, f64) {
+        let (dx, dy) = player.movement(self.forward, self.lateral);
+        let length = dx.hypot(dy);
+        let (x, y) = (player.player_x, player.player_y);
+        if length == 0.0 {
+            return (x, y);
+        }
+
+        let mut along = length;
+        while along > 0.0 && map.blocks_movement(x + dx * along / length, y + dy * along / length) {
+            along -= MUZZLE_BACKOFF_STEP;
+        }
+        let along = along.max(0.0);
+        (x + dx * along / length, y + dy * along / length)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::FRAC_PI_2;
+
+    #[test]
+    fn muzzle_is_ahead_by_the_offset() {
+        let map = Map::parse("#######\n#.....#\n#.....#\n#.....#\n#######").unwrap();
+        let muzzle = Muzzle { forward: 0.4, lateral: 0.0 };
+        // facing east, along +x
+        let player = Player::new(2.5, 2.5, FRAC_PI_2);
+        let (x, y) = muzzle.point(&player, &map);
+        assert!((x - 2.9).abs() < 1e-9 && (y - 2.5).abs() < 1e-9);
+
+        let (x, y) = Muzzle { forward: 0.4, lateral: 0.2 }.point(&player, &map);
+        assert!((x - 2.9).abs() < 1e-9 && (y - 2.3).abs() < 1e-9);
+
+        // up against the east wall the point is pulled back out of it
+        let player = Player::new(5.8, 2.5, FRAC_PI_2);
+        let (x, _) = muzzle.point(&player, &map);
+        assert!(x < 6.0 && x > 5.8);
+    }
+}
@@ -0,0 +1,255 @@
+use crate::font::{draw_text, line_height, text_width};
+use crate::player::Player;
+use crate::render::{draw_map, MapView, Viewport};
+use crate::world::World;
+use pixel_canvas::{Blend, Color};
+use std::f64::consts::{FRAC_PI_2, FRAC_PI_4, PI, TAU};
+
+/// Color of the newest trail position, older ones fade towards the floor.
+const TRAIL_COLOR: Color = Color { r: 80, g: 220, b: 255 };
+
+/// Draw a small map in the top left corner, with the player's trail fading with age.
+pub fn render_minimap(buffer: &mut [Color], width: usize, height: usize, world: &World, camera: &Player) {
+    let size = width.min(height) / 4;
+    let margin = 8.min(width.min(height) - size);
+    let viewport = Viewport { x: margin, y: height - size - margin, width: size, height: size };
+    let view = MapView::new(viewport, &world.map);
+
+    draw_map(buffer, width, &view, &world.map, camera, &world.life);
+
+    let count = world.seat.trail.len();
+    for (age, &(x, y)) in world.seat.trail.positions().enumerate() {
+        if let Some((pixel_x, pixel_y)) = view.to_buffer(x, y) {
+            let pixel = &mut buffer[pixel_y * width + pixel_x];
+            let strength = (age + 1) as f32 / count as f32;
+            *pixel = pixel.blend(TRAIL_COLOR, strength);
+        }
+    }
+}
+
+/// How far either side of straight ahead the compass reaches.
+const COMPASS_SPAN: f64 = FRAC_PI_2;
+const COMPASS_COLOR: Color = Color { r: 230, g: 230, b: 230 };
+const COMPASS_NORTH_COLOR: Color = Color { r: 255, g: 80, b: 60 };
+
+/// Cardinal points by heading (north is -y, towards row 0), as 5x5 letters with the leftmost pixel in bit 4.
+const CARDINALS: [(f64, [u8; 5]); 4] = [
+    (PI, [0b10001, 0b11001, 0b10101, 0b10011, 0b10001]),
+    (FRAC_PI_2, [0b11111, 0b10000, 0b11110, 0b10000, 0b11111]),
+    (0.0, [0b01111, 0b10000, 0b01110, 0b00001, 0b11110]),
+    (-FRAC_PI_2, [0b10001, 0b10001, 0b10101, 0b10101, 0b01010]),
+];
+
+/// How far `bearing` lies right of `heading`, in `[-π, π)` across the wrap.
+pub fn compass_offset(heading: f64, bearing: f64) -> f64 {
+    (bearing - heading + PI).rem_euclid(TAU) - PI
+}
+
+/// A strip along the top middle with the cardinal points and 45 degree ticks scrolling past a center mark.
+pub fn render_compass(buffer: &mut [Color], width: usize, height: usize, heading: f64) {
+    let scale = (width.min(height) / 256).max(1);
+    let strip_width = width / 3;
+    let strip_height = 9 * scale;
+    if strip_width == 0 || strip_height + 4 > height {
+        return;
+    }
+    let left = (width - strip_width) / 2;
+    let top = height - 4;
+    let bottom = top + 1 - strip_height;
+
+    // darken the strip so the markings stand out over anything
+    for y in bottom..=top {
+        for pixel in &mut buffer[y * width + left..y * width + left + strip_width] {
+            *pixel = pixel.blend(Color { r: 0, g: 0, b: 0 }, 0.6);
+        }
+    }
+
+    let to_column = |bearing: f64| -> Option<usize> {
+        let offset = compass_offset(heading, bearing);
+        if offset.abs() > COMPASS_SPAN {
+            return None;
+        }
+        let column = left as f64 + (offset / COMPASS_SPAN + 1.0) / 2.0 * (strip_width - 1) as f64;
+        Some(column.round() as usize)
+    };
+
+    // ticks along the bottom, then the letters above the ones that have them
+    for tick in 0..8 {
+        if let Some(x) = to_column(tick as f64 * FRAC_PI_4) {
+            for y in bottom..bottom + scale * 2 {
+                buffer[y * width + x] = COMPASS_COLOR;
+            }
+        }
+    }
+    for (bearing, rows) in CARDINALS {
+        let Some(center) = to_column(bearing) else {
+            continue;
+        };
+        let color = if bearing == PI { COMPASS_NORTH_COLOR } else { COMPASS_COLOR };
+        for (row, bits) in rows.iter().enumerate() {
+            for column in 0..5 {
+                if bits & (1 << (4 - column)) == 0 {
+                    continue;
+                }
+                for dy in 0..scale {
+                    for dx in 0..scale {
+                        let x = (center + (column * scale + dx)).checked_sub(5 * scale / 2);
+                        let y = top - 1 - row * scale - dy;
+                        if let Some(x) = x.filter(|&x| x >= left && x < left + strip_width) {
+                            buffer[y * width + x] = color;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // which way the player is facing
+    let center = left + strip_width / 2;
+    for y in bottom..=top {
+        if y < bottom + scale * 2 || y > top - scale {
+            buffer[y * width + center] = Color { r: 255, g: 220, b: 0 };
+        }
+    }
+}
+
+/// Default for `App::distance_columns`: the left edge, the middle and the right edge.
+pub const DISTANCE_COLUMNS: [f64; 3] = [0.0, 0.5, 1.0];
+
+const DISTANCE_COLOR: Color = Color { r: 255, g: 230, b: 120 };
+
+/// Print the 3D view's `depths`, one per column it was drawn with, at `columns` fractions of the way across.
+pub fn render_distances(buffer: &mut [Color], width: usize, height: usize, depths: &[f64], columns: &[f64]) {
+    let scale = (width.min(height) / 256).max(1);
+    let tick = 3 * scale;
+    let top = height.saturating_sub(line_height(scale) + tick + 4);
+    if width == 0 || top == 0 || depths.is_empty() {
+        return;
+    }
+
+    for &fraction in columns {
+        let x = ((width - 1) as f64 * fraction.clamp(0.0, 1.0)).round() as usize;
+        // a view drawn at a lower internal resolution has fewer columns
+        let depth = depths[x * depths.len() / width];
+
+        let text = format!("{:.2}", depth);
+        let left = x.saturating_sub(text_width(&text, scale) / 2).min(width.saturating_sub(text_width(&text, scale)));
+        draw_text(buffer, width, height, (left, top + tick), &text, scale, DISTANCE_COLOR);
+        for row_from_top in top..top + tick - scale {
+            buffer[(height - 1 - row_from_top) * width + x] = DISTANCE_COLOR;
+        }
+    }
+}
+
+/// Dim the frame and list `lines` over it, cutting off those that do not fit.
+pub fn render_help(buffer: &mut [Color], width: usize, height: usize, lines: &[String]) {
+    for pixel in buffer.iter_mut().take(width * height) {
+        *pixel = pixel.blend(Color { r: 0, g: 0, b: 0 }, 0.75);
+    }
+
+    let scale = (width.min(height) / 256).max(1);
+    let margin = 8 * scale;
+    for (index, line) in lines.iter().enumerate() {
+        let top = margin + index * line_height(scale);
+        if top >= height {
+            break;
+        }
+        draw_text(buffer, width, height, (margin, top), line, scale, Color { r: 255, g: 255, b: 255 });
+    }
+}
+
+/// One of the things an `App` draws over the scene, see `Layers`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Layer {
+    Minimap,
+    Compass,
+    /// Wall distances of a few columns, see `render_distances`.
+    Distances,
+    /// The last frame's ray counts.
+    Stats,
+    /// The program's own drawing, see `App::with_overlay`.
+    Custom,
+    /// The list of keys, which also pauses the game while it's shown.
+    Help,
+}
+
+/// Every `Layer` in the order `Layers::default` draws them, bottom first.
+pub const LAYER_ORDER: [Layer; 6] =
+    [Layer::Minimap, Layer::Compass, Layer::Distances, Layer::Stats, Layer::Custom, Layer::Help];
+
+/// Which overlays an `App` draws, each over the ones before it.
+#[derive(Clone, Debug)]
+pub struct Layers {
+    layers: Vec<(Layer, bool)>,
+}
+
+impl Default for Layers {
+    /// All of `LAYER_ORDER`, with only the minimap, compass and custom drawing on.
+    fn default() -> Self {
+        let on = |layer| matches!(layer, Layer::Minimap | Layer::Compass | Layer::Custom);
+        Self { layers: LAYER_ORDER.iter().map(|&layer| (layer, on(layer))).collect() }
+    }
+}
+
+impl Layers {
+    pub fn is_enabled(&self, layer: Layer) -> bool {
+        self.layers.iter().any(|&(listed, enabled)| listed == layer && enabled)
+    }
+
+    pub fn set_enabled(&mut self, layer: Layer, enabled: bool) {
+        for (listed, on) in &mut self.layers {
+            if *listed == layer {
+                *on = enabled;
+            }
+        }
+    }
+
+    pub fn toggle(&mut self, layer: Layer) {
+        self.set_enabled(layer, !self.is_enabled(layer));
+    }
+
+    /// Every layer in drawing order, bottom first, whether it's on or not.
+    pub fn order(&self) -> impl Iterator<Item = Layer> + '_ {
+        self.layers.iter().map(|&(layer, _)| layer)
+    }
+
+    /// The layers that are on, in drawing order.
+    pub fn enabled(&self) -> impl Iterator<Item = Layer> + '_ {
+        self.layers.iter().filter(|&&(_, enabled)| enabled).map(|&(layer, _)| layer)
+    }
+
+    /// Draw the layers in `order` first and the rest after them, each staying on or off.
+    pub fn set_order(&mut self, order: &[Layer]) {
+        let before = std::mem::take(&mut self.layers);
+        for layer in order.iter().copied().chain(before.iter().map(|&(layer, _)| layer)) {
+            if self.layers.iter().any(|&(listed, _)| listed == layer) {
+                continue;
+            }
+            if let Some(&entry) = before.iter().find(|&&(listed, _)| listed == layer) {
+                self.layers.push(entry);
+            }
+        }
+    }
+
+    /// Draw `layer` over all the others.
+    pub fn bring_to_front(&mut self, layer: Layer) {
+        if let Some(position) = self.layers.iter().position(|&(listed, _)| listed == layer) {
+            let entry = self.layers.remove(position);
+            self.layers.push(entry);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compass_offset_is_smooth_across_the_wrap() {
+        let before = compass_offset(TAU - 0.05, 0.0);
+        let after = compass_offset(0.05, 0.0);
+        assert!((before - 0.05).abs() < 1e-9);
+        assert!((after + 0.05).abs() < 1e-9);
+        assert!((compass_offset(0.0, FRAC_PI_2) - FRAC_PI_2).abs() < 1e-9);
+    }
+}
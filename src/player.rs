@@ -0,0 +1,234 @@
+use crate::easing::smoothstep;
+use crate::map::Map;
+use std::collections::HashSet;
+
+/// How far into an open cell `Player::resolve_overlap` puts the player, clear of the wall.
+pub const OVERLAP_MARGIN: f64 = 0.01;
+
+/// Most wall cells `Player::resolve_overlap` goes through from the one the player is in to find a way out.
+pub const OVERLAP_SEARCH: usize = 4;
+
+/// Range `Player::set_eye_height` keeps eye height in, as a fraction of the wall height.
+pub const MIN_EYE_HEIGHT: f64 = 0.1;
+pub const MAX_EYE_HEIGHT: f64 = 0.9;
+
+/// Jump take-off speed and gravity in wall heights per second (squared): a quarter wall high in under half a second.
+pub const JUMP_SPEED: f64 = 2.2;
+pub const GRAVITY: f64 = 10.0;
+
+/// Eye height when fully crouched, in wall heights, unless standing is lower already.
+pub const CROUCH_EYE_HEIGHT: f64 = 0.25;
+
+/// How long crouching all the way down or standing back up takes, in seconds.
+pub const CROUCH_TIME: f64 = 0.2;
+
+/// How far the top of the head, which has to fit under the ceiling, is above the eyes.
+pub const HEAD_ROOM: f64 = 0.1;
+
+/// How fast looking up or down tilts the view, in screen heights of horizon
+/// movement per second.
+pub const PITCH_SPEED: f64 = 0.5;
+
+#[derive(Clone, PartialEq)]
+pub struct Player {
+    pub player_x: f64,
+    pub player_y: f64,
+    pub vision_angle: f64,
+    /// Map cells per second walking forwards or backwards.
+    pub forward_speed: f64,
+    /// Map cells per second stepping sideways.
+    pub strafe_speed: f64,
+    /// Radians per second.
+    pub turn_speed: f64,
+    /// Fraction of the wall height, 0.5 halfway up; set it through `set_eye_height` to keep it in range.
+    pub eye_height: f64,
+    /// Height above the floor in wall heights.
+    pub jump_height: f64,
+    pub vertical_speed: f64,
+    /// Horizon shift in screen heights, positive looking up; see `Life::max_pitch`.
+    pub pitch: f64,
+    /// From 0.0 standing to 1.0 all the way down; `eye_height` stays the standing height.
+    pub crouch: f64,
+}
+
+impl Player {
+    pub fn new(starting_x: f64, starting_y: f64, starting_angle: f64) -> Self {
+        Self {
+            player_x: starting_x,
+            player_y: starting_y,
+            vision_angle: starting_angle,
+            forward_speed: 12.0,
+            strafe_speed: 12.0,
+            turn_speed: 6.0,
+            eye_height: 0.5,
+            jump_height: 0.0,
+            vertical_speed: 0.0,
+            pitch: 0.0,
+            crouch: 0.0,
+        }
+    }
+
+    pub fn rotate(&mut self, angle: f64) {
+        self.vision_angle += angle
+    }
+
+    pub fn walk(&mut self, step: f64) {
+        self.player_x += self.vision_angle.sin() * step;
+        self.player_y += self.vision_angle.cos() * step;
+    }
+
+    pub fn set_eye_height(&mut self, eye_height: f64) {
+        self.eye_height = eye_height.clamp(MIN_EYE_HEIGHT, MAX_EYE_HEIGHT);
+    }
+
+    /// Step sideways, positive to the right of where the player is looking.
+    pub fn strafe(&mut self, step: f64) {
+        self.player_x += self.vision_angle.cos() * step;
+        self.player_y -= self.vision_angle.sin() * step;
+    }
+
+    /// Eye height part way through crouching, eased at either end.
+    pub fn crouched_eye_height(&self) -> f64 {
+        let low = CROUCH_EYE_HEIGHT.min(self.eye_height);
+        self.eye_height + (low - self.eye_height) * smoothstep(self.crouch)
+    }
+
+    /// Top of the head above the floor as the player is now, jump included.
+    pub fn head_height(&self) -> f64 {
+        self.crouched_eye_height() + HEAD_ROOM + self.jump_height
+    }
+
+    /// Crouch further down if `down`, otherwise stand further up, for `dt` seconds.
+    pub fn update_crouch(&mut self, down: bool, dt: f64) {
+        let step = dt / CROUCH_TIME;
+        self.crouch = if down { (self.crouch + step).min(1.0) } else { (self.crouch - step).max(0.0) };
+    }
+
+    /// Offset for moving `forward` along the view and `sideways` to its
+    /// right, from where the player is looking right now.
+    pub fn movement(&self, forward: f64, sideways: f64) -> (f64, f64) {
+        let (sin, cos) = self.vision_angle.sin_cos();
+        (sin * forward + cos * sideways, cos * forward - sin * sideways)
+    }
+
+    pub fn is_airborne(&self) -> bool {
+        self.jump_height > 0.0 || self.vertical_speed > 0.0
+    }
+
+    /// Leave the floor, if the player is standing on it.
+    pub fn jump(&mut self) {
+        if !self.is_airborne() {
+            self.vertical_speed = JUMP_SPEED;
+        }
+    }
+
+    /// Rise or fall for `dt` seconds, stopping on the floor.
+    pub fn fall(&mut self, dt: f64) {
+        if !self.is_airborne() {
+            return;
+        }
+        self.vertical_speed -= GRAVITY * dt;
+        self.jump_height += self.vertical_speed * dt;
+        if self.jump_height <= 0.0 {
+            self.jump_height = 0.0;
+            self.vertical_speed = 0.0;
+        }
+    }
+
+    /// Move the player out of a wall into a cell they can stand in, through as few wall cells as
+    /// possible and then the shortest way, up to `OVERLAP_SEARCH` deep; returns whether they moved.
+    pub fn resolve_overlap(&mut self, map: &Map) -> bool {
+        let (x, y) = (self.player_x, self.player_y);
+        if !map.blocks_movement(x, y) {
+            return false;
+        }
+
+        // out across the edges of the cell the player is in first, then on
+        // through the walls around it a ring at a time, so an open cell that
+        // only touches a corner, or lies past more wall, doesn't count as nearer
+        let start = (x.floor() as i64, y.floor() as i64);
+        let mut seen = HashSet::from([start]);
+        let mut ring = vec![start];
+        for _ in 0..OVERLAP_SEARCH {
+            let mut walls = Vec::new();
+            let mut nearest: Option<(f64, f64, f64)> = None;
+            for (cell_x, cell_y) in ring {
+                for next in [(cell_x - 1, cell_y), (cell_x + 1, cell_y), (cell_x, cell_y - 1), (cell_x, cell_y + 1)] {
+                    if next.0 < 0 || next.1 < 0 || next.0 >= map.width as i64 || next.1 >= map.height as i64 || !seen.insert(next) {
+                        continue;
+                    }
+                    let (left, top) = (next.0 as f64, next.1 as f64);
+                    if map.blocks_movement(left + 0.5, top + 0.5) {
+                        walls.push(next);
+                        continue;
+                    }
+                    // the point of the cell closest to the player
+                    let exit_x = x.clamp(left + OVERLAP_MARGIN, left + 1.0 - OVERLAP_MARGIN);
+                    let exit_y = y.clamp(top + OVERLAP_MARGIN, top + 1.0 - OVERLAP_MARGIN);
+                    let distance = (exit_x - x).hypot(exit_y - y);
+                    if nearest.is_none_or(|(_, _, best)| distance < best) {
+                        nearest = Some((exit_x, exit_y, distance));
+                    }
+                }
+            }
+            if let Some((exit_x, exit_y, _)) = nearest {
+                self.player_x = exit_x;
+                self.player_y = exit_y;
+                return true;
+            }
+            ring = walls;
+        }
+        false
+    }
+
+    /// Position and angle part way from `self` to `other`.
+    pub fn lerp(&self, other: &Player, t: f64) -> Player {
+        Player {
+            player_x: self.player_x + (other.player_x - self.player_x) * t,
+            player_y: self.player_y + (other.player_y - self.player_y) * t,
+            vision_angle: self.vision_angle + (other.vision_angle - self.vision_angle) * t,
+            eye_height: self.eye_height + (other.eye_height - self.eye_height) * t,
+            jump_height: self.jump_height + (other.jump_height - self.jump_height) * t,
+            pitch: self.pitch + (other.pitch - self.pitch) * t,
+            crouch: self.crouch + (other.crouch - self.crouch) * t,
+            ..other.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn player_inside_a_wall_is_pushed_out() {
+        let map = Map::parse("#####\n#...#\n#.#.#\n#...#\n#####").unwrap();
+        let mut player = Player::new(2.3, 2.5, 0.0);
+        assert!(player.resolve_overlap(&map));
+        assert_eq!((player.player_x, player.player_y), (2.0 - OVERLAP_MARGIN, 2.5));
+        assert!(!map.blocks_movement(player.player_x, player.player_y));
+        assert!(!player.resolve_overlap(&map));
+
+        let mut nowhere = Player::new(0.5, 0.5, 0.0);
+        assert!(!nowhere.resolve_overlap(&Map::parse("##\n##").unwrap()));
+        assert_eq!((nowhere.player_x, nowhere.player_y), (0.5, 0.5));
+    }
+
+    #[test]
+    fn player_in_a_wall_leaves_by_an_edge_not_into_a_sealed_pocket() {
+        // the pocket at (1, 1) only meets the player's cell at a corner
+        let map = Map::parse("#####\n#.###\n###.#\n#...#\n#####").unwrap();
+        let mut player = Player::new(2.1, 2.05, 0.0);
+        assert!(player.resolve_overlap(&map));
+        assert_eq!((player.player_x, player.player_y), (3.0 + OVERLAP_MARGIN, 2.05));
+
+        // several walls deep is still found, further than `OVERLAP_SEARCH` is not
+        let thick = "#".repeat(13);
+        let map = Map::parse(&format!("{0}\n#{1}#\n{0}", thick, "#".repeat(10) + ".")).unwrap();
+        let mut player = Player::new(7.5, 1.5, 0.0);
+        assert!(player.resolve_overlap(&map));
+        assert_eq!((player.player_x, player.player_y), (11.0 + OVERLAP_MARGIN, 1.5));
+        let mut buried = Player::new(5.5, 1.5, 0.0);
+        assert!(!buried.resolve_overlap(&map));
+    }
+}
@@ -0,0 +1,28 @@
+/// A cell that rays and the player go into and come out of at the linked cell; it leads one way only.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Portal {
+    pub x: u16,
+    pub y: u16,
+    pub to_x: u16,
+    pub to_y: u16,
+    /// Radians added to the heading going through; keep it a multiple of a quarter turn to stay on the grid.
+    pub rotation: f64,
+}
+
+impl Portal {
+    pub fn new(x: u16, y: u16, to_x: u16, to_y: u16, rotation: f64) -> Self {
+        Self { x, y, to_x, to_y, rotation }
+    }
+
+    /// Where the point `(x, y)` in the portal's cell and heading `angle` come out, turned by `rotation`.
+    pub fn carry(&self, x: f64, y: f64, angle: f64) -> (f64, f64, f64) {
+        let (turned_x, turned_y) = self.turn(x - (self.x as f64 + 0.5), y - (self.y as f64 + 0.5));
+        (self.to_x as f64 + 0.5 + turned_x, self.to_y as f64 + 0.5 + turned_y, angle + self.rotation)
+    }
+
+    /// `(dx, dy)` turned by `rotation`, as adding it to a heading of `(sin, cos)` does.
+    pub fn turn(&self, dx: f64, dy: f64) -> (f64, f64) {
+        let (sin, cos) = self.rotation.sin_cos();
+        (dx * cos + dy * sin, dy * cos - dx * sin)
+    }
+}
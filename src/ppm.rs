@@ -0,0 +1,96 @@
+use pixel_canvas::Color;
+use std::fs::File;
+use std::io::{self, BufRead, BufWriter, Write};
+use std::path::Path;
+
+/// Write `buffer` as a binary PPM (P6), flipping rows since PPM starts at the top.
+pub fn write_ppm(out: &mut impl Write, buffer: &[Color], width: usize, height: usize) -> io::Result<()> {
+    write!(out, "P6\n{} {}\n255\n", width, height)?;
+    let mut row_bytes = Vec::with_capacity(width * 3);
+    for row in buffer.chunks(width).take(height).rev() {
+        row_bytes.clear();
+        for pixel in row {
+            row_bytes.extend_from_slice(&[pixel.r, pixel.g, pixel.b]);
+        }
+        out.write_all(&row_bytes)?;
+    }
+    Ok(())
+}
+
+pub fn save_ppm(path: impl AsRef<Path>, buffer: &[Color], width: usize, height: usize) -> io::Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    write_ppm(&mut out, buffer, width, height)?;
+    out.flush()
+}
+
+/// Read an 8 bit binary PPM as its width, height and pixels from the top row down.
+pub fn read_ppm(input: &mut impl BufRead) -> io::Result<(usize, usize, Vec<Color>)> {
+    let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+
+    // four whitespace separated fields, with comments running to the end of a line
+    let mut fields = Vec::new();
+    while fields.len() < 4 {
+        let mut field = Vec::new();
+        loop {
+            let mut byte = [0];
+            input.read_exact(&mut byte)?;
+            match byte[0] {
+                b'#' if field.is_empty() => {
+                    input.read_until(b'\n', &mut Vec::new())?;
+                }
+                byte if byte.is_ascii_whitespace() => {
+                    if !field.is_empty() {
+                        break;
+                    }
+                }
+                byte => field.push(byte),
+            }
+        }
+        fields.push(String::from_utf8(field).map_err(|_| invalid("header isn't text"))?);
+    }
+
+    if fields[0] != "P6" {
+        return Err(invalid("not a binary PPM"));
+    }
+    let number = |field: &str| field.parse::<usize>().map_err(|_| invalid("header field isn't a number"));
+    let (width, height, max) = (number(&fields[1])?, number(&fields[2])?, number(&fields[3])?);
+    if width == 0 || height == 0 {
+        return Err(invalid("image is empty"));
+    }
+    if max == 0 || max > 255 {
+        return Err(invalid("only 8 bit PPMs are supported"));
+    }
+
+    let mut data = vec![0; width * height * 3];
+    input.read_exact(&mut data)?;
+    let scale = |value: u8| (value as usize * 255 / max).min(255) as u8;
+    let pixels = data.chunks(3).map(|rgb| Color { r: scale(rgb[0]), g: scale(rgb[1]), b: scale(rgb[2]) }).collect();
+    Ok((width, height, pixels))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn written_image_reads_back() {
+        // bottom row red and green, top row blue and white
+        let buffer = [
+            Color { r: 255, g: 0, b: 0 },
+            Color { r: 0, g: 255, b: 0 },
+            Color { r: 0, g: 0, b: 255 },
+            Color { r: 255, g: 255, b: 255 },
+        ];
+        let mut bytes = Vec::new();
+        write_ppm(&mut bytes, &buffer, 2, 2).unwrap();
+        assert!(bytes.starts_with(b"P6\n2 2\n255\n"));
+        assert_eq!(bytes.len(), b"P6\n2 2\n255\n".len() + 12);
+
+        let (width, height, pixels) = read_ppm(&mut &bytes[..]).unwrap();
+        assert_eq!((width, height), (2, 2));
+        let rgb: Vec<_> = pixels.iter().map(|c| (c.r, c.g, c.b)).collect();
+        assert_eq!(rgb, [(0, 0, 255), (255, 255, 255), (255, 0, 0), (0, 255, 0)]);
+
+        assert!(read_ppm(&mut &b"P3\n2 2\n255\n"[..]).is_err());
+    }
+}
@@ -0,0 +1,510 @@
+use crate::life::Life;
+use crate::map::Map;
+use crate::player::Player;
+
+/// How much of the light through one pane takes on the glass's color.
+pub const GLASS_OPACITY: f64 = 0.35;
+
+/// Panes a ray passes before the next one stops it like a wall.
+pub const MAX_GLASS_LAYERS: u32 = 4;
+
+/// Distance between the samples `cast_ray` marches a ray forward by, in map cells.
+pub const RAY_STEP: f64 = 0.1;
+
+/// Portals a ray goes through before the next one stops it, so facing portals do not loop forever.
+pub const MAX_PORTAL_HOPS: u32 = 4;
+
+/// The color a ray picked up passing through glass on its way to what it hit.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GlassTint {
+    /// Glass color added along the way, already scaled by how much of it shows.
+    pub color: [f64; 3],
+    /// How much of what is behind still shows through.
+    pub transmittance: f64,
+    pub layers: u32,
+    /// Distance to the nearest pane.
+    pub distance: f64,
+}
+
+impl Default for GlassTint {
+    fn default() -> Self {
+        Self { color: [0.0; 3], transmittance: 1.0, layers: 0, distance: f64::INFINITY }
+    }
+}
+
+impl GlassTint {
+    /// Add a pane colored `color` at `distance`, behind the ones already passed.
+    pub fn pass(&mut self, color: [u8; 3], distance: f64) {
+        for (sum, channel) in self.color.iter_mut().zip(color) {
+            *sum += self.transmittance * GLASS_OPACITY * channel as f64;
+        }
+        self.transmittance *= 1.0 - GLASS_OPACITY;
+        self.distance = self.distance.min(distance);
+        self.layers += 1;
+    }
+
+    /// `rgb` as seen through the glass.
+    pub fn apply(&self, rgb: [u8; 3]) -> [u8; 3] {
+        let mut seen = [0; 3];
+        for ((seen, channel), glass) in seen.iter_mut().zip(rgb).zip(self.color) {
+            *seen = (glass + self.transmittance * channel as f64).round().min(255.0) as u8;
+        }
+        seen
+    }
+}
+
+/// Side of a wall cell a ray hit, by the way it faces; north is towards row 0.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Face {
+    North,
+    South,
+    East,
+    West,
+}
+
+/// Where a ray ended up.
+#[derive(Clone, Copy, Debug)]
+pub struct RayHit {
+    /// How far the ray travelled, counting every leg between mirror bounces.
+    pub distance: f64,
+    /// How far the ray got before its first bounce or portal, as far as the line of sight goes.
+    pub direct_distance: f64,
+    /// The wall cell that stopped the ray, `None` if it ran out of depth or left the map.
+    pub cell: Option<(u16, u16)>,
+    /// Where the ray stopped, in map coordinates.
+    pub point: (f64, f64),
+    pub bounces: u32,
+    /// Portals passed through, `point` being on the far side of the last one.
+    pub portals: u32,
+    /// Samples taken along the way, one every `RAY_STEP`.
+    pub steps: u32,
+    /// Map cells entered after the one the ray started in.
+    pub cells: u32,
+    pub glass: GlassTint,
+}
+
+impl RayHit {
+    /// Horizontal texture coordinate of the hit from its world position, so textures run on across cells and corners.
+    pub fn texture_u(&self, span: f64) -> f64 {
+        let Some((cell_x, cell_y)) = self.cell else {
+            return 0.0;
+        };
+        let offset_x = self.point.0 - cell_x as f64;
+        let offset_y = self.point.1 - cell_y as f64;
+
+        // snap the point onto the face, the ray may have stepped past it
+        let to_vertical_edge = offset_x.min(1.0 - offset_x);
+        let to_horizontal_edge = offset_y.min(1.0 - offset_y);
+        let (face_x, face_y) = if to_vertical_edge < to_horizontal_edge {
+            (cell_x as f64 + offset_x.round(), self.point.1.clamp(cell_y as f64, cell_y as f64 + 1.0))
+        } else {
+            (self.point.0.clamp(cell_x as f64, cell_x as f64 + 1.0), cell_y as f64 + offset_y.round())
+        };
+
+        ((face_x + face_y) / span).rem_euclid(1.0)
+    }
+
+    /// Side of the wall hit, the one the hit point is closest to.
+    pub fn face(&self) -> Option<Face> {
+        let (cell_x, cell_y) = self.cell?;
+        let offset_x = self.point.0 - cell_x as f64;
+        let offset_y = self.point.1 - cell_y as f64;
+
+        let face = if offset_x.min(1.0 - offset_x) < offset_y.min(1.0 - offset_y) {
+            if offset_x < 0.5 { Face::West } else { Face::East }
+        } else if offset_y < 0.5 {
+            Face::North
+        } else {
+            Face::South
+        };
+        Some(face)
+    }
+}
+
+/// Distance along a ray from `(x, y)` to the circle around `center`, grazes included; 0.0 inside, `None` if it misses.
+pub fn circle_hit(x: f64, y: f64, direction: (f64, f64), center: (f64, f64), radius: f64) -> Option<f64> {
+    let (from_x, from_y) = (x - center.0, y - center.1);
+    let along = from_x * direction.0 + from_y * direction.1;
+    let outside = from_x * from_x + from_y * from_y - radius * radius;
+    if outside <= 0.0 {
+        return Some(0.0);
+    }
+    let discriminant = along * along - outside;
+    if discriminant < 0.0 || along > 0.0 {
+        return None;
+    }
+    Some(-along - discriminant.sqrt())
+}
+
+/// Cell row or column of coordinate `v` along `direction`, taking the cell ahead within `epsilon` of an edge.
+pub fn cell_along(v: f64, direction: f64, epsilon: f64) -> f64 {
+    let edge = v.round();
+    if (v - edge).abs() > epsilon {
+        return v.floor();
+    }
+    if direction < -epsilon { edge - 1.0 } else { edge }
+}
+
+/// March a ray from `(x, y)` towards `angle` in `RAY_STEP`s until it hits a wall, following mirrors, glass and portals.
+pub fn cast_ray(map: &Map, x: f64, y: f64, angle: f64, max_depth: f64, max_bounces: u32) -> RayHit {
+    cast_ray_visiting(map, x, y, angle, max_depth, max_bounces, &mut |_, _| ())
+}
+
+/// `rays` rays spread evenly across a `fov` wide cone from `(x, y)`, left to right, for anything that needs to see, such as an enemy.
+pub fn cast_fov(map: &Map, x: f64, y: f64, angle: f64, fov: f64, rays: usize, max_depth: f64) -> Vec<RayHit> {
+    let start = angle - fov / 2.0;
+    (0..rays).map(|i| cast_ray(map, x, y, start + (i as f64 + 0.5) / rays as f64 * fov, max_depth, 0)).collect()
+}
+
+/// `cast_ray`, calling `visit` with each cell the ray enters up to the one that stops it.
+pub fn cast_ray_visiting(map: &Map, x: f64, y: f64, angle: f64, max_depth: f64, max_bounces: u32, visit: &mut impl FnMut(u16, u16)) -> RayHit {
+    let mut distance_to_wall: f64 = 0.0;
+    let mut test_x: u16;
+    let mut test_y: u16;
+
+    // ray unit vector (direction of ray vector)
+    let mut unit_ray_x = angle.sin();
+    let mut unit_ray_y = angle.cos();
+
+    // start of the current leg, the ray's origin or where it last bounced
+    let (mut origin_x, mut origin_y) = (x, y);
+    let mut leg_start: f64 = 0.0;
+    let mut direct_distance = max_depth;
+    let mut bounces = 0;
+    let mut portals = 0;
+    let (mut previous_x, mut previous_y) = (x as u16, y as u16);
+    let (mut steps, mut cells) = (0, 0);
+    let mut glass = GlassTint::default();
+    let mut in_glass = false;
+
+    // scalar horizon stepping
+    while distance_to_wall < max_depth {
+        distance_to_wall += RAY_STEP;
+        steps += 1;
+
+        // test point, all walls are in integer boundaries so we don't care for non-int values
+        let leg = distance_to_wall - leg_start;
+        test_x = cell_along(origin_x + unit_ray_x * leg, unit_ray_x, map.hit_epsilon) as u16;
+        test_y = cell_along(origin_y + unit_ray_y * leg, unit_ray_y, map.hit_epsilon) as u16;
+        let entered = (test_x, test_y) != (previous_x, previous_y);
+        if entered {
+            cells += 1;
+        }
+
+        if map.out_of_bounds(test_x, test_y) {
+            break;
+        }
+        if entered {
+            visit(test_x, test_y);
+        }
+        let mut blocked = false;
+        if let Some(portal) = map.portal(test_x, test_y).filter(|_| entered) {
+            if portals < MAX_PORTAL_HOPS {
+                // carry on from the same point of the linked cell
+                let (far_x, far_y, _) = portal.carry(origin_x + unit_ray_x * leg, origin_y + unit_ray_y * leg, 0.0);
+                (unit_ray_x, unit_ray_y) = portal.turn(unit_ray_x, unit_ray_y);
+                (origin_x, origin_y) = (far_x, far_y);
+                leg_start = distance_to_wall;
+                if bounces == 0 && portals == 0 {
+                    direct_distance = leg_start;
+                }
+                portals += 1;
+                (previous_x, previous_y) = (far_x as u16, far_y as u16);
+                in_glass = false;
+                if !map.out_of_bounds(previous_x, previous_y) {
+                    visit(previous_x, previous_y);
+                }
+                continue;
+            }
+            blocked = true;
+        }
+        if map.is_pillar(test_x, test_y) {
+            let center = (test_x as f64 + 0.5, test_y as f64 + 0.5);
+            // only once the step has gone past where it meets the pillar
+            if let Some(hit) = circle_hit(origin_x, origin_y, (unit_ray_x, unit_ray_y), center, map.pillar_radius).filter(|&hit| hit <= leg) {
+                let distance = leg_start + hit;
+                return RayHit {
+                    distance,
+                    direct_distance: if bounces == 0 && portals == 0 { distance } else { direct_distance },
+                    cell: Some((test_x, test_y)),
+                    point: (origin_x + unit_ray_x * hit, origin_y + unit_ray_y * hit),
+                    bounces,
+                    portals,
+                    steps,
+                    cells,
+                    glass,
+                };
+            }
+        }
+        if map.reflects(test_x as f64, test_y as f64) && bounces < max_bounces {
+            // back up to the last point outside the mirror and turn around
+            let back = leg - RAY_STEP;
+            origin_x += unit_ray_x * back;
+            origin_y += unit_ray_y * back;
+            leg_start += back;
+            distance_to_wall = leg_start;
+            if bounces == 0 {
+                direct_distance = leg_start;
+            }
+            if test_x != previous_x {
+                unit_ray_x = -unit_ray_x;
+            }
+            if test_y != previous_y {
+                unit_ray_y = -unit_ray_y;
+            }
+            bounces += 1;
+            continue;
+        }
+        blocked |= map.blocks_sight(test_x as f64, test_y as f64);
+        let pane = map.glass(test_x as f64, test_y as f64);
+        if let Some(color) = pane.filter(|_| entered && !in_glass) {
+            if glass.layers < MAX_GLASS_LAYERS {
+                glass.pass(color, distance_to_wall);
+            } else {
+                blocked = true;
+            }
+        }
+        in_glass = pane.is_some();
+        if blocked {
+            return RayHit {
+                distance: distance_to_wall,
+                direct_distance: if bounces == 0 && portals == 0 { distance_to_wall } else { direct_distance },
+                cell: Some((test_x, test_y)),
+                point: (origin_x + unit_ray_x * leg, origin_y + unit_ray_y * leg),
+                bounces,
+                portals,
+                steps,
+                cells,
+                glass,
+            };
+        }
+        (previous_x, previous_y) = (test_x, test_y);
+    }
+
+    let leg = max_depth - leg_start;
+    RayHit {
+        distance: max_depth,
+        direct_distance,
+        cell: None,
+        point: (origin_x + unit_ray_x * leg, origin_y + unit_ray_y * leg),
+        bounces,
+        portals,
+        steps,
+        cells,
+        glass,
+    }
+}
+
+/// Each point a plain march `step` apart samples and whether it counted as a wall, to check `cast_ray` against at a finer step.
+#[cfg(feature = "debug-raymarch")]
+pub fn raymarch_debug(map: &Map, x: f64, y: f64, angle: f64, max_depth: f64, step: f64) -> Vec<(f64, f64, bool)> {
+    let (unit_ray_x, unit_ray_y) = (angle.sin(), angle.cos());
+    let mut samples = Vec::new();
+    if step <= 0.0 {
+        return samples;
+    }
+
+    let mut distance: f64 = 0.0;
+    while distance < max_depth {
+        distance += step;
+        let (sample_x, sample_y) = (x + unit_ray_x * distance, y + unit_ray_y * distance);
+        let (test_x, test_y) = (sample_x as u16, sample_y as u16);
+        let wall = map.out_of_bounds(test_x, test_y) || map.blocks_sight(test_x as f64, test_y as f64);
+        samples.push((sample_x, sample_y, wall));
+        if wall {
+            break;
+        }
+    }
+    samples
+}
+
+/// The wall cell straight ahead of the player, if within `Life::interaction_distance`.
+pub fn aimed_cell(map: &Map, player: &Player, life: &Life) -> Option<(u16, u16)> {
+    let hit = cast_ray(map, player.player_x, player.player_y, player.vision_angle, life.interaction_distance, 0);
+    hit.cell
+}
+
+/// Raised floor a ray passes over as `(near, far, height)`, walked edge to edge so platform edges are exact.
+pub fn raised_spans(map: &Map, x: f64, y: f64, angle: f64, max_distance: f64) -> Vec<(f64, f64, f64)> {
+    let (unit_ray_x, unit_ray_y) = (angle.sin(), angle.cos());
+    let (mut cell_x, mut cell_y) = (x.floor(), y.floor());
+
+    // distance to the next vertical and horizontal cell edge, and between edges
+    let edge = |position: f64, cell: f64, direction: f64| {
+        if direction > 0.0 {
+            (cell + 1.0 - position) / direction
+        } else if direction < 0.0 {
+            (position - cell) / -direction
+        } else {
+            f64::INFINITY
+        }
+    };
+    let mut next_x = edge(x, cell_x, unit_ray_x);
+    let mut next_y = edge(y, cell_y, unit_ray_y);
+    let (delta_x, delta_y) = (1.0 / unit_ray_x.abs(), 1.0 / unit_ray_y.abs());
+
+    let mut spans: Vec<(f64, f64, f64)> = Vec::new();
+    let mut entered = 0.0;
+    while entered < max_distance {
+        if cell_x < 0.0 || cell_y < 0.0 || map.out_of_bounds(cell_x as u16, cell_y as u16) {
+            break;
+        }
+        let left = next_x.min(next_y).min(max_distance);
+        let height = map.floor_height(cell_x, cell_y);
+        if height > 0.0 {
+            match spans.last_mut() {
+                Some(span) if span.1 == entered && span.2 == height => span.1 = left,
+                _ => spans.push((entered, left, height)),
+            }
+        }
+
+        entered = left;
+        if next_x < next_y {
+            cell_x += unit_ray_x.signum();
+            next_x += delta_x;
+        } else {
+            cell_y += unit_ray_y.signum();
+            next_y += delta_y;
+        }
+    }
+
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::{FRAC_PI_2, PI};
+
+    fn room() -> Map {
+        Map::parse("######\n#....#\n#....#\n#....#\n######").unwrap()
+    }
+
+    #[test]
+    fn adjacent_cells_continue_texture() {
+        let map = room();
+        let left = cast_ray(&map, 2.999, 3.5, PI, 16.0, 0);
+        let right = cast_ray(&map, 3.001, 3.5, PI, 16.0, 0);
+        assert_eq!((left.cell, right.cell), (Some((2, 0)), Some((3, 0))));
+        assert!((left.texture_u(3.0) - right.texture_u(3.0)).abs() < 0.01);
+    }
+
+    #[test]
+    fn texture_continues_around_corner() {
+        let map = room();
+        let north = cast_ray(&map, 1.001, 2.5, PI, 16.0, 0);
+        let west = cast_ray(&map, 2.5, 1.001, -FRAC_PI_2, 16.0, 0);
+        assert_eq!((north.cell, west.cell), (Some((1, 0)), Some((0, 1))));
+        assert!((north.texture_u(3.0) - west.texture_u(3.0)).abs() < 0.01);
+    }
+
+    #[test]
+    fn mirror_head_on_reflects_straight_back() {
+        let map = Map::parse("#====#\n#....#\n#....#\n#....#\n######").unwrap();
+        let hit = cast_ray(&map, 2.5, 2.5, PI, 16.0, 4);
+        assert_eq!((hit.cell, hit.bounces), (Some((2, 4)), 1));
+        assert!((hit.point.0 - 2.5).abs() < 1e-9);
+        assert!((hit.distance - 4.5).abs() < 0.2);
+        assert!((hit.direct_distance - 1.5).abs() < 0.2);
+
+        let flat = cast_ray(&map, 2.5, 2.5, PI, 16.0, 0);
+        assert_eq!((flat.cell, flat.bounces), (Some((2, 0)), 0));
+    }
+
+    #[test]
+    fn open_ray_enters_more_cells_than_one_at_a_wall() {
+        let map = Map::parse("############\n#..........#\n############").unwrap();
+        let open = cast_ray(&map, 1.5, 1.5, FRAC_PI_2, 16.0, 0);
+        let near = cast_ray(&map, 1.5, 1.5, PI, 16.0, 0);
+        assert_eq!((open.cells, near.cells), (10, 1));
+        assert!(open.steps > near.steps);
+    }
+
+    #[test]
+    fn one_pane_of_glass_tints_the_far_wall() {
+        let map = Map::parse("######\n#.~..#\n######").unwrap();
+        let hit = cast_ray(&map, 1.5, 1.5, FRAC_PI_2, 16.0, 0);
+        assert_eq!((hit.cell, hit.glass.layers), (Some((5, 1)), 1));
+        assert!((hit.glass.distance - 0.5).abs() < 0.1);
+        assert!((hit.glass.transmittance - (1.0 - GLASS_OPACITY)).abs() < 1e-9);
+        // 35% of the green glass over 65% of a grey wall
+        assert_eq!(hit.glass.apply([100, 100, 100]), [86, 135, 107]);
+    }
+
+    #[cfg(feature = "debug-raymarch")]
+    #[test]
+    fn cast_ray_is_within_a_step_of_a_finer_march() {
+        let map = Map::parse("########\n#......#\n#..#...#\n#......#\n#....#.#\n########").unwrap();
+        for angle in [0.3, 1.1, 2.0, 2.9, 3.7, 4.4, 5.2, 6.0] {
+            // a tenth of `RAY_STEP`, so close to where the wall really starts
+            let samples = raymarch_debug(&map, 1.5, 1.5, angle, 16.0, RAY_STEP / 10.0);
+            let &(x, y, wall) = samples.last().unwrap();
+            assert!(wall);
+            let hit = cast_ray(&map, 1.5, 1.5, angle, 16.0, 0);
+            assert_eq!(hit.cell, Some((x as u16, y as u16)), "angle {}", angle);
+            let fine = RAY_STEP / 10.0 * samples.len() as f64;
+            assert!(hit.distance >= fine - 1e-9 && hit.distance < fine + RAY_STEP, "angle {}", angle);
+        }
+    }
+
+    #[test]
+    fn ray_through_a_portal_hits_the_linked_room() {
+        use crate::portal::Portal;
+
+        // the room behind the portal ends in a beacon rather than a plain wall
+        let mut map = Map::parse("##########\n#....#...*\n##########").unwrap();
+        map.portals.push(Portal::new(3, 1, 6, 1, 0.0));
+        let hit = cast_ray(&map, 1.5, 1.5, FRAC_PI_2, 16.0, 0);
+        assert_eq!(hit.cell, Some((9, 1)));
+        assert_eq!(hit.portals, 1);
+        assert!((hit.distance - 4.5).abs() < 0.05, "{}", hit.distance);
+    }
+
+    #[test]
+    fn ray_through_a_pillar_stops_at_its_near_side() {
+        let map = Map::parse("#######\n#.....#\n#.....#\n#..o..#\n#.....#\n#.....#\n#######").unwrap();
+        let hit = cast_ray(&map, 3.5, 5.5, PI, 16.0, 0);
+        assert_eq!(hit.cell, Some((3, 3)));
+        assert!((hit.distance - (2.0 - map.pillar_radius)).abs() < 1e-9, "{}", hit.distance);
+
+        // just past the edge the ray carries on to the far wall
+        let past = cast_ray(&map, 3.5 + map.pillar_radius + 0.01, 5.5, PI, 16.0, 0);
+        assert_eq!(past.cell, Some((3, 0)));
+    }
+
+    #[test]
+    fn cone_facing_a_wall_is_blocked_and_one_down_the_room_sees_far() {
+        let wall = "#".repeat(12);
+        let row = format!("#{}#", ".".repeat(10));
+        let map = Map::parse(&format!("{0}\n{1}\n{0}", wall, vec![row; 5].join("\n"))).unwrap();
+        let blocked = cast_fov(&map, 1.5, 3.5, 3.0 * FRAC_PI_2, 0.5, 8, 16.0);
+        let open = cast_fov(&map, 1.5, 3.5, FRAC_PI_2, 0.5, 8, 16.0);
+        assert_eq!((blocked.len(), open.len()), (8, 8));
+        assert!(blocked.iter().all(|hit| hit.cell.is_some() && hit.distance < 1.0));
+        assert!(open.iter().all(|hit| hit.distance > 5.0));
+    }
+
+    #[test]
+    fn hit_cell_is_stable_near_cell_edges() {
+        let map = Map::parse("########\n#......#\n#..#...#\n#......#\n#...#..#\n#......#\n########").unwrap();
+        let mut flips = 0;
+        for step_x in 8..56 {
+            for step_y in 8..48 {
+                let (x, y) = (step_x as f64 / 8.0, step_y as f64 / 8.0);
+                if map.is_wall(x, y) {
+                    continue;
+                }
+                for eighth in 0..8 {
+                    let angle = eighth as f64 * PI / 4.0;
+                    let cell = cast_ray(&map, x, y, angle, 16.0, 0).cell;
+                    for nudge in [-1e-12, 1e-12] {
+                        if cast_ray(&map, x, y, angle + nudge, 16.0, 0).cell != cell {
+                            flips += 1;
+                        }
+                    }
+                }
+            }
+        }
+        assert_eq!(flips, 0);
+    }
+}
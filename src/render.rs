@@ -0,0 +1,1560 @@
+/*
+    Simple Ray Casting Rendering
+
+    based on: https://en.wikipedia.org/wiki/Wolfenstein_3D
+
+    ############
+    ##\    #####
+    ## \   /   #
+    #   \ /    #
+    #    P     #
+    #         ##
+    ############
+
+    P = player location
+    # = any 3D object
+    \/ = player field of view
+
+    We take each column of the console screen buffer and map that
+    to a ray, cast within player's field of view. The most important
+    aspect is figuring out how far the ray travels before hitting a
+    surface, so we can use that to project the ilusion of size and 
+    distance.
+
+       screen width
+    |----------------|
+
+       player angle     
+    \       |       /              
+     \      |      /         
+      \     |     /          
+       \    |    /                   
+        \   |   /            
+         \  |  /             
+          \ | /              
+-FOV/2      P      +FOV/2    
+
+    starting angle for FOV = player angle - (FOV / 2)
+
+    FOV angle incremental step = (x <counter from 0 to screen width> / screen width) * FOV
+                                  -----------------\ /-------------------------------
+                                                    V
+                                                   % increment of FOV (1/FOV, 2/FOV, 3/FOV, etc...)
+
+    ray angle = starting angle for FOV + FOV angle incremental step
+
+    Notice here that the smaller the FOV is the larger the interation step will be in relation to
+    the total FOV size, this may need to be adjusted later on with a better formula for increment size.
+
+           #
+           | -------------
+    (check hit wall)     |
+           | ------------- 
+    (check hit wall)     |-----> distance to wall increments
+           | -------------
+    (check hit wall)     |
+           | -------------
+           P 
+
+    Once starting angle calculated, we test each incremental step "forward" in order to check if 
+    it hits a "wall" or not. This is how we can determine the approximate distance to the wall.
+    To actually check if a wall has been hit or not, we need to calculate the direction that the
+    player is looking towards, we can take a unit vector:
+
+      (eye direction x, eye direction y)
+    P ---------------------------------> (unit vector expressing player facing direction)
+
+    eye direction x = sin(ray angle)
+    eye direction y = cos(ray angle)
+    unit vector = (eye direction x, eye direction y)
+
+    and now we can get the testing point by steping forward following the unit vector direction
+
+    test_point_x = player x + eye direction x * distance to wall
+    test_point_y = player y + eye direction y * distance to wall
+
+      unit vector   (test point x, test point y)
+    P -----------> + --------------------------> (test point)
+
+    Next, we can create the ilusion of distance by making the ceiling and floor larger
+    the further away into the horizon the point is. For that we need to now calculate
+    the amount of space that ceiling and floor takes up in our console display
+
+    ceiling size = (screen height / 2.0) - (screen high / distance to wall)
+                    --------\ /---------    -------------\ /---------------
+                             V                            V
+              start with upper scren half     the further away we are the more ceiling we see
+
+    floor size = screen heightt = ceiling size
+ */
+
+use crate::column::{ColumnHook, ColumnPixels};
+use crate::life::{Life, Quality};
+use crate::light::{apply_tint, light_tint};
+use crate::map::{Map, WallKind, PILLAR_GLYPH};
+use crate::player::Player;
+use crate::ray::{aimed_cell, cast_ray, cast_ray_visiting, raised_spans, Face, RayHit};
+use crate::sprite::sprite_pixels;
+use crate::stats::FrameStats;
+use crate::texture::{Texture, WallPattern};
+use pixel_canvas::{Blend, Color};
+use std::collections::HashSet;
+use std::f64::consts::{PI, TAU};
+
+/// Number of rays fanned across the FOV to outline the wedge in the top-down view.
+const TOPDOWN_WEDGE_RAYS: usize = 128;
+
+/// Tint blended over the wall the player is aiming at.
+const AIM_HIGHLIGHT: Color = Color { r: 255, g: 200, b: 60 };
+
+/// Top and front faces of raised floor, before shading.
+const RAISED_TOP_COLOR: Color = Color { r: 150, g: 120, b: 90 };
+const RAISED_RISER_COLOR: Color = Color { r: 95, g: 70, b: 50 };
+
+/// How much light each mirror bounce keeps, so reflections read as reflections.
+const MIRROR_SHADE: f64 = 0.8;
+
+/// Radius of a light's glow one cell away as a fraction of the view's height, twice that up and down.
+const LIGHT_SHAFT_SIZE: f64 = 0.25;
+
+/// Which view is drawn into the window.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Renderer {
+    FirstPerson,
+    TopDown,
+    /// Wall distance per column, for debugging.
+    Depth,
+    /// Only the outlines of walls.
+    Wireframe,
+}
+
+impl Renderer {
+    /// `other`, or back to the 3D view if `other` is already active.
+    pub fn toggle(self, other: Renderer) -> Self {
+        if self == other {
+            Renderer::FirstPerson
+        } else {
+            other
+        }
+    }
+
+    /// Draw the view into the whole buffer and count what its rays cost, with a fresh `Scratch` for a one-off frame.
+    pub fn render(self, buffer: &mut [Color], width: usize, height: usize, map: &Map, player: &Player, life: &Life) -> FrameStats {
+        self.render_with(buffer, width, height, map, player, life, &mut Scratch::default())
+    }
+
+    /// `render`, drawing through `scratch` which the caller keeps between frames.
+    #[allow(clippy::too_many_arguments)]
+    pub fn render_with(self, buffer: &mut [Color], width: usize, height: usize, map: &Map, player: &Player, life: &Life, scratch: &mut Scratch) -> FrameStats {
+        let Scratch { internal, shaft_depths, .. } = scratch;
+        match self {
+            Renderer::FirstPerson => render_scaled(buffer, width, height, life.internal_scale, internal, |buffer, width, height| {
+                first_person(buffer, width, height, map, player, life, Extras { shaft_depths: Some(shaft_depths), ..Extras::default() })
+            }),
+            Renderer::TopDown => {
+                render_topdown(buffer, width, height, map, player, life);
+                FrameStats::default()
+            }
+            Renderer::Depth => render_scaled(buffer, width, height, life.internal_scale, internal, |buffer, width, height| {
+                render_depth(buffer, width, height, map, player, life)
+            }),
+            Renderer::Wireframe => render_scaled(buffer, width, height, life.internal_scale, internal, |buffer, width, height| {
+                render_wireframe(buffer, width, height, map, player, life)
+            }),
+        }
+    }
+
+    /// Draw into `viewport` of a buffer with rows `stride` apart, clipped to it, by way of `scratch` which the caller keeps between frames.
+    #[allow(clippy::too_many_arguments)]
+    pub fn render_frame_into(self, buffer: &mut [Color], stride: usize, viewport: Viewport, scratch: &mut Scratch, map: &Map, player: &Player, life: &Life) -> FrameStats {
+        // the view is drawn in one of the scratch buffers while `render_with` uses the others
+        let mut view = std::mem::take(&mut scratch.view);
+        view.clear();
+        view.resize(viewport.width * viewport.height, Color { r: 0, g: 0, b: 0 });
+        let stats = self.render_with(&mut view, viewport.width, viewport.height, map, player, life, scratch);
+
+        let visible = viewport.width.min(stride.saturating_sub(viewport.x));
+        for (row, pixels) in view.chunks(viewport.width.max(1)).enumerate() {
+            let start = (viewport.y + row) * stride + viewport.x;
+            let Some(target) = buffer.get_mut(start..start + visible) else {
+                break;
+            };
+            target.copy_from_slice(&pixels[..visible]);
+        }
+        scratch.view = view;
+        stats
+    }
+}
+
+/// Buffers drawing goes through on the way to the frame, kept by the caller so frames don't allocate.
+#[derive(Default)]
+pub struct Scratch {
+    pub(crate) internal: Vec<Color>,
+    view: Vec<Color>,
+    pub(crate) shaft_depths: Vec<f64>,
+}
+
+/// Resolution the 3D view is drawn at for `Life::internal_scale`, from one pixel to the window size.
+pub fn internal_size(width: usize, height: usize, scale: f64) -> (usize, usize) {
+    let scaled = |size: usize| ((size as f64 * scale).round() as usize).clamp(1, size.max(1));
+    (scaled(width), scaled(height))
+}
+
+/// Run `draw` in `internal` at the internal resolution for `scale` and stretch the result over the whole buffer.
+pub fn render_scaled<T>(buffer: &mut [Color], width: usize, height: usize, scale: f64, internal: &mut Vec<Color>, draw: impl FnOnce(&mut [Color], usize, usize) -> T) -> T {
+    let (internal_width, internal_height) = internal_size(width, height, scale);
+    if (internal_width, internal_height) == (width, height) {
+        return draw(buffer, width, height);
+    }
+
+    internal.clear();
+    internal.resize(internal_width * internal_height, Color { r: 0, g: 0, b: 0 });
+    let result = draw(internal, internal_width, internal_height);
+
+    // nearest neighbour, each window pixel takes the internal pixel it falls in
+    for (y, row) in buffer.chunks_mut(width).take(height).enumerate() {
+        let source_row = &internal[(y * internal_height / height) * internal_width..][..internal_width];
+        for (x, pixel) in row.iter_mut().enumerate() {
+            *pixel = source_row[x * internal_width / width];
+        }
+    }
+    result
+}
+
+/// Row of the horizon, the middle row tilted by `pitch` (clamped to `max_pitch`) and shifted by eye height.
+pub fn horizon_row(height: usize, pitch: f64, max_pitch: f64, eye_height: f64) -> f64 {
+    let max_pitch = max_pitch.max(0.0);
+    height as f64 / 2.0 - pitch.clamp(-max_pitch, max_pitch) * height as f64 + eye_offset(height, eye_height)
+}
+
+/// Rows the horizon rises by for eyes above mid-wall, one screen height per wall height.
+pub fn eye_offset(height: usize, eye_height: f64) -> f64 {
+    (eye_height - 0.5) * height as f64
+}
+
+/// How much of `Life::horizon_color` the horizon line is made of.
+pub const HORIZON_LINE_OPACITY: f32 = 0.4;
+
+/// Row the horizon line is drawn on, `None` when it's off the screen.
+pub fn horizon_line_row(height: usize, pitch: f64, max_pitch: f64, eye_height: f64) -> Option<usize> {
+    let row = horizon_row(height, pitch, max_pitch, eye_height).floor();
+    (row >= 0.0 && row < height as f64).then_some(row as usize)
+}
+
+/// Rows where a wall `distance` away starts and ends, its part below `eye_height` under the horizon.
+pub fn wall_band(height: usize, horizon: f64, distance: f64, eye_height: f64) -> (f64, f64) {
+    // a ray starting inside a pillar stops at 0.0
+    let wall_height = 2.0 * height as f64 / distance.max(1e-6);
+    let floor_upper_boundary = horizon - wall_height * eye_height;
+    let ceiling_lower_boundary = horizon + wall_height * (1.0 - eye_height);
+    (floor_upper_boundary, ceiling_lower_boundary)
+}
+
+/// Rows `start..end` a wall band covers, clamped to the screen; floor below `start`, ceiling from `end` up.
+pub fn wall_rows(floor_upper_boundary: f64, ceiling_lower_boundary: f64, height: usize) -> (usize, usize) {
+    let clamp = |boundary: f64| boundary.round().clamp(0.0, height as f64) as usize;
+    (clamp(floor_upper_boundary), clamp(ceiling_lower_boundary))
+}
+
+/// Brightness of floor row `y` under a wall starting at `floor_upper_boundary`, 1.0 at the bottom to 0.05 there, within `[0, 1]`.
+pub fn floor_shade(y: f64, floor_upper_boundary: f64) -> f64 {
+    if floor_upper_boundary <= 0.0 {
+        return 1.0;
+    }
+    (y * (- 0.95 / floor_upper_boundary) + 1.0).clamp(0.0, 1.0)
+}
+
+/// Distance to the floor seen at row `y`, the inverse of where `wall_band`
+/// puts the bottom of a wall. Only meaningful below the horizon.
+pub fn floor_distance(height: usize, horizon: f64, y: f64, eye_height: f64) -> f64 {
+    2.0 * height as f64 * eye_height / (horizon - y)
+}
+
+/// Whether floor point `(x, y)` is within `half_width` of a line of a grid `spacing` cells apart through the origin.
+pub fn on_grid_line(x: f64, y: f64, spacing: f64, half_width: f64) -> bool {
+    let off_line = |v: f64| {
+        let lines = v / spacing;
+        (lines - lines.round()).abs() * spacing
+    };
+    spacing > 0.0 && (off_line(x) <= half_width || off_line(y) <= half_width)
+}
+
+/// Share of the wall's color the floor `row` rows under it takes, falling in even steps to 0 past `rows`.
+pub fn floor_blend(row: usize, rows: usize) -> f64 {
+    if row == 0 || row > rows {
+        return 0.0;
+    }
+    (rows + 1 - row) as f64 / (rows + 1) as f64
+}
+
+/// Share of a light's glow `(dx, dy)` pixels from it, falling off to none at `radius` across and twice that up and down.
+pub fn light_shaft_glow(dx: f64, dy: f64, radius: f64) -> f64 {
+    if radius <= 0.0 {
+        return 0.0;
+    }
+    let falloff = (1.0 - (dx / radius).hypot(dy / (2.0 * radius))).max(0.0);
+    falloff * falloff
+}
+
+/// What a ray below the horizon meets first.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FloorHit {
+    /// Level ground or the wall, as without raised floor.
+    Ground,
+    Top(f64),
+    Riser(f64),
+}
+
+/// Follow a ray from eye height `eye`, dropping `slope` a unit, over `spans` until `limit`.
+pub fn floor_hit(spans: &[(f64, f64, f64)], eye: f64, slope: f64, limit: f64) -> FloorHit {
+    let limit = limit.min(eye / slope);
+    for &(near, far, height) in spans {
+        if near >= limit {
+            break;
+        }
+        if eye - near * slope <= height {
+            return FloorHit::Riser(near);
+        }
+        let top = (eye - height) / slope;
+        if top < far.min(limit) {
+            return FloorHit::Top(top);
+        }
+    }
+    FloorHit::Ground
+}
+
+/// Channel multipliers for a wall side under `Life::face_tint`, each side a little cooler, warmer or darker.
+pub fn face_tint(face: Face) -> [f64; 3] {
+    match face {
+        Face::North => [1.0, 1.0, 1.0],
+        Face::South => [0.9, 0.9, 0.95],
+        Face::East => [0.97, 0.93, 0.88],
+        Face::West => [0.85, 0.87, 0.9],
+    }
+}
+
+/// Brightness of a pillar whose surface faces the ray by `facing`, 1.0 face on and 0.0 edge on.
+pub fn pillar_shade(facing: f64) -> f64 {
+    0.45 + 0.55 * facing.clamp(0.0, 1.0)
+}
+
+/// Unit vector from the middle of the pillar `hit` stopped at through the hit point.
+pub fn pillar_normal(hit: &RayHit) -> Option<(f64, f64)> {
+    let (cell_x, cell_y) = hit.cell?;
+    let (dx, dy) = (hit.point.0 - (cell_x as f64 + 0.5), hit.point.1 - (cell_y as f64 + 0.5));
+    let length = dx.hypot(dy);
+    (length > 0.0).then(|| (dx / length, dy / length))
+}
+
+/// Brightness `v` of the way down a wall for `Life::wall_gradient` `strength`.
+pub fn wall_gradient(strength: f64, v: f64) -> f64 {
+    let v = v.clamp(0.0, 1.0);
+    let from_dark_end = if strength >= 0.0 { v } else { 1.0 - v };
+    1.0 - strength.abs().min(1.0) * (1.0 - from_dark_end)
+}
+
+/// How far a wall has faded, 0.0 up to `max_depth - band` rising to 1.0 at `max_depth`.
+pub fn far_fade(distance: f64, max_depth: f64, band: f64) -> f64 {
+    if band <= 0.0 {
+        return 0.0;
+    }
+    ((distance - (max_depth - band)) / band).clamp(0.0, 1.0)
+}
+
+/// Of two neighbouring columns whose distances differ by more than `threshold`, the nearer one, see `Life::outline`.
+pub fn outline_columns(hits: &[RayHit], threshold: f64) -> Vec<bool> {
+    let mut edges = vec![false; hits.len()];
+    for (x, pair) in hits.windows(2).enumerate() {
+        if (pair[0].distance - pair[1].distance).abs() > threshold {
+            let nearer = if pair[0].distance < pair[1].distance { x } else { x + 1 };
+            edges[nearer] = true;
+        }
+    }
+    edges
+}
+
+/// Angle of column `x`'s ray across the FOV, or `life.frozen_rays`, nudged by `life.ray_jitter`.
+pub fn column_angle(player: &Player, life: &Life, width: usize, x: usize) -> f64 {
+    let (heading, fov_angle) = life.frozen_rays.unwrap_or((player.vision_angle, life.fov_angle));
+    // starting ray angle for FOV swip
+    let start_of_fov_angle = heading - (fov_angle / 2.0);
+    start_of_fov_angle + ((x as f64 + life.ray_jitter) / width as f64) * fov_angle
+}
+
+/// One ray per screen column, left to right, doubling as the frame's depth buffer.
+pub fn cast_columns(map: &Map, player: &Player, life: &Life, width: usize) -> Vec<RayHit> {
+    (0..width)
+        .map(|x| {
+            let ray_angle = column_angle(player, life, width, x);
+            cast_ray(map, player.player_x, player.player_y, ray_angle, life.max_wall_check_depth, life.max_mirror_bounces)
+        })
+        .collect()
+}
+
+/// Column and row, from the bottom, where the floor under `(x, y)` shows, placed like walls; `None` behind or outside the FOV.
+pub fn project_point(player: &Player, life: &Life, width: usize, height: usize, x: f64, y: f64) -> Option<(i32, i32)> {
+    let dx = x - player.player_x;
+    let dy = y - player.player_y;
+    let distance = dx.hypot(dy);
+    let angle = (dx.atan2(dy) - player.vision_angle + PI).rem_euclid(TAU) - PI;
+    if angle.abs() > life.fov_angle / 2.0 || distance < 1e-3 {
+        return None;
+    }
+
+    let column = (angle / life.fov_angle + 0.5) * width as f64;
+    let horizon = horizon_row(height, player.pitch, life.max_pitch, player.eye_height);
+    let (floor_line, _) = wall_band(height, horizon, distance, player.eye_height);
+    Some((column.floor() as i32, floor_line.floor() as i32))
+}
+
+/// Cells a frame's rays pass through or stop at, plus the player's; sprites and raised floor don't stop them.
+pub fn visible_cells(map: &Map, player: &Player, life: &Life, width: usize) -> HashSet<(u16, u16)> {
+    let mut cells = HashSet::new();
+    let (x, y) = (player.player_x, player.player_y);
+    if x >= 0.0 && y >= 0.0 && !map.out_of_bounds(x as u16, y as u16) {
+        cells.insert((x as u16, y as u16));
+    }
+    for column in 0..width {
+        let ray_angle = column_angle(player, life, width, column);
+        cast_ray_visiting(map, x, y, ray_angle, life.max_wall_check_depth, life.max_mirror_bounces, &mut |cell_x, cell_y| {
+            cells.insert((cell_x, cell_y));
+        });
+    }
+    cells
+}
+
+/// Share of `width` columns whose ray stops at the wall at cell `(x, y)`, mirrored views included.
+pub fn cell_visible_fraction(map: &Map, player: &Player, life: &Life, width: usize, x: u16, y: u16) -> f64 {
+    if width == 0 {
+        return 0.0;
+    }
+    let hits = cast_columns(map, player, life, width);
+    let columns = hits.iter().filter(|hit| hit.cell == Some((x, y))).count();
+    columns as f64 / width as f64
+}
+
+/// Draw the ray cast 3D view, rows going from the bottom of the window up.
+pub fn render_first_person(buffer: &mut [Color], width: usize, height: usize, map: &Map, player: &Player, life: &Life) -> FrameStats {
+    first_person(buffer, width, height, map, player, life, Extras::default())
+}
+
+/// `render_first_person`, handing each column to `columns` before the
+/// sprites are drawn, see `ColumnHook`.
+pub fn render_first_person_with(buffer: &mut [Color], width: usize, height: usize, map: &Map, player: &Player, life: &Life, columns: &mut ColumnHook) -> FrameStats {
+    first_person(buffer, width, height, map, player, life, Extras { columns: Some(columns), ..Extras::default() })
+}
+
+/// `render_first_person`, also filling `coverage` with how much of each pixel isn't `life.background`.
+pub(crate) fn render_first_person_coverage(buffer: &mut [Color], width: usize, height: usize, map: &Map, player: &Player, life: &Life, coverage: &mut [f32]) -> FrameStats {
+    first_person(buffer, width, height, map, player, life, Extras { coverage: Some(coverage), ..Extras::default() })
+}
+
+/// What `first_person` can take and hand back besides the frame.
+#[derive(Default)]
+pub(crate) struct Extras<'a> {
+    pub(crate) columns: Option<&'a mut ColumnHook>,
+    pub(crate) coverage: Option<&'a mut [f32]>,
+    /// Distance to the nearest wall or sprite drawn in each column.
+    pub(crate) depths: Option<&'a mut Vec<f64>>,
+    /// Where the light shafts' per pixel depths go, kept between frames.
+    pub(crate) shaft_depths: Option<&'a mut Vec<f64>>,
+}
+
+pub(crate) fn first_person(buffer: &mut [Color], width: usize, height: usize, map: &Map, player: &Player, life: &Life, extras: Extras) -> FrameStats {
+    let Extras { columns, mut coverage, depths: mut column_depths, shaft_depths } = extras;
+    // standing on raised floor or jumping lifts the eyes along with it
+    let mut lifted = player.clone();
+    lifted.eye_height += map.floor_height(player.player_x, player.player_y) + player.jump_height;
+    // sprites stay where frozen rays put the walls
+    if let Some((heading, _)) = life.frozen_rays {
+        lifted.vision_angle = heading;
+    }
+    let player = &lifted;
+    let horizon = horizon_row(height, player.pitch, life.max_pitch, player.eye_height);
+
+    // LOOP HELPER VARIABLES
+    let mut ceiling_lower_boundary: f64;
+    let mut floor_upper_boundary: f64;
+    let mut distance_to_wall: f64;
+    let mut pixel_color: Color;
+    let mut wall_color_shade: u8;
+    let mut shade_multiplier: f64;
+
+    let hits = cast_columns(map, player, life, width);
+
+    // texture, mip level and horizontal texture coordinate for each column
+    let textures: Vec<Option<(&Texture, usize, f64)>> = hits
+        .iter()
+        .map(|hit| {
+            let (cell_x, cell_y) = hit.cell?;
+            let texture = map.texture(cell_x as f64, cell_y as f64)?;
+            let level = match life.quality {
+                Quality::Low => 0,
+                Quality::High => texture.mip_for_height(2.0 * height as f64 / hit.distance),
+            };
+            // a pillar's texture wraps once around it
+            let u = if map.is_pillar(cell_x, cell_y) {
+                pillar_normal(hit).map_or(0.0, |(normal_x, normal_y)| normal_x.atan2(normal_y) / TAU + 0.5)
+            } else {
+                hit.texture_u(texture.span)
+            };
+            Some((texture, level, u))
+        })
+        .collect();
+
+    // pattern and the place along the wall for walls without a texture
+    let patterns: Vec<Option<(WallPattern, f64)>> = hits
+        .iter()
+        .zip(&textures)
+        .map(|(hit, texture)| {
+            let (cell_x, cell_y) = hit.cell?;
+            let pattern = map.pattern(cell_x as f64, cell_y as f64);
+            if texture.is_some() || pattern == WallPattern::Flat {
+                return None;
+            }
+            Some((pattern, hit.texture_u(1.0)))
+        })
+        .collect();
+
+    // per side tint, the same all the way up a column, and pillars shaded
+    // darker the more their surface turns away from the ray
+    let face_tints: Vec<[f64; 3]> = hits
+        .iter()
+        .enumerate()
+        .map(|(x, hit)| match (hit.cell, hit.face()) {
+            (Some((cell_x, cell_y)), _) if map.is_pillar(cell_x, cell_y) => {
+                let ray_angle = column_angle(player, life, width, x);
+                let facing = pillar_normal(hit).map_or(1.0, |(normal_x, normal_y)| -(normal_x * ray_angle.sin() + normal_y * ray_angle.cos()));
+                [pillar_shade(facing); 3]
+            }
+            (_, Some(face)) if life.face_tint => face_tint(face),
+            _ => [1.0; 3],
+        })
+        .collect();
+    let tinted = life.face_tint || map.may_contain(PILLAR_GLYPH);
+
+    // beacons and the like skip distance shading and the far fade
+    let fog_immune: Vec<bool> = hits
+        .iter()
+        .map(|hit| {
+            hit.cell
+                .and_then(|(cell_x, cell_y)| map.wall_kind(cell_x as f64, cell_y as f64))
+                .is_some_and(|kind| kind.fog_immune)
+        })
+        .collect();
+
+    // wall under the crosshair, highlighted wherever it shows up
+    let aimed = aimed_cell(map, player, life);
+
+    // lights are the same all the way up a wall, the floor needs a look up per pixel
+    let lit = !map.lights.is_empty();
+    let floor_limit = life.floor_limit();
+    let wall_tints: Vec<[f64; 3]> = hits.iter().map(|hit| light_tint(&map.lights, hit.point.0, hit.point.1)).collect();
+    let directions: Vec<(f64, f64)> = (0..width)
+        .map(|x| {
+            let ray_angle = column_angle(player, life, width, x);
+            (ray_angle.sin(), ray_angle.cos())
+        })
+        .collect();
+
+    let spans: Vec<Vec<(f64, f64, f64)>> = if map.has_raised_floor() {
+        (0..width)
+            .map(|x| {
+                let ray_angle = column_angle(player, life, width, x);
+                raised_spans(map, player.player_x, player.player_y, ray_angle, hits[x].distance)
+            })
+            .collect()
+    } else {
+        vec![Vec::new(); width]
+    };
+
+    let sky_color = |x: usize, y: usize| match &map.sky {
+        Some(sky) => {
+            // the top of the sky at the top of the screen when looking straight ahead
+            let v = 1.0 - (y as f64 - horizon) / (height as f64 / 2.0);
+            sky.sample_from(column_angle(player, life, width, x), v, player.player_x, player.player_y)
+        }
+        None => life.background,
+    };
+
+    // for `floor_blend_rows`: how many rows of plain floor lie right under
+    // each column's wall, and whether the bottom row of the wall shows it
+    let mut floor_run = vec![0; width];
+    let mut wall_base = vec![false; width];
+    // for `light_shafts`: how far away whatever hides a light behind each
+    // pixel is, the floor and sky hiding none
+    let mut own_depths = Vec::new();
+    let depths = shaft_depths.unwrap_or(&mut own_depths);
+    depths.clear();
+    if life.light_shafts > 0.0 && lit {
+        depths.resize(width * height, f64::INFINITY);
+    }
+
+    for (y, row) in buffer.chunks_mut(width).take(height).enumerate() {
+        for (x, pixel) in row.iter_mut().enumerate() {
+            distance_to_wall = hits[x].distance;
+
+            (floor_upper_boundary, ceiling_lower_boundary) = wall_band(height, horizon, distance_to_wall, player.eye_height);
+            let (wall_start, wall_end) = wall_rows(floor_upper_boundary, ceiling_lower_boundary, height);
+
+            let slope = (horizon - (y as f64 + 0.5)) / (2.0 * height as f64);
+            let raised = if slope > 0.0 && !spans[x].is_empty() {
+                floor_hit(&spans[x], player.eye_height, slope, distance_to_wall)
+            } else {
+                FloorHit::Ground
+            };
+
+            // how far away what the pixel shows is, to tell if it's behind glass
+            let depth;
+            let mut alpha = 1.0;
+
+            // raised floor, in front of the floor or wall the column would show
+            if let FloorHit::Top(distance) | FloorHit::Riser(distance) = raised {
+                depth = distance;
+                if y < wall_start {
+                    floor_run[x] = 0;
+                }
+                if distance > floor_limit {
+                    pixel_color = life.floor_fog_color;
+                } else {
+                    let base = if let FloorHit::Top(_) = raised { RAISED_TOP_COLOR } else { RAISED_RISER_COLOR };
+                    shade_multiplier = ((-13.4375 * distance + 235.0) / 235.0).clamp(0.0, 1.0);
+                    pixel_color = Color {
+                        r: (base.r as f64 * shade_multiplier) as u8,
+                        g: (base.g as f64 * shade_multiplier) as u8,
+                        b: (base.b as f64 * shade_multiplier) as u8,
+                    };
+                    if lit {
+                        let (unit_ray_x, unit_ray_y) = directions[x];
+                        let tint = light_tint(
+                            &map.lights,
+                            player.player_x + unit_ray_x * distance,
+                            player.player_y + unit_ray_y * distance,
+                        );
+                        pixel_color = apply_tint(pixel_color, tint);
+                    }
+                }
+                // floor
+            } else if y < wall_start {
+                depth = floor_distance(height, horizon, y as f64 + 0.5, player.eye_height);
+                floor_run[x] += 1;
+                if depth > floor_limit {
+                    pixel_color = life.floor_fog_color;
+                } else {
+                    shade_multiplier = floor_shade(y as f64, floor_upper_boundary);
+                    pixel_color = Color { 
+                        r: (140.0 * shade_multiplier) as u8, 
+                        g: (40.0 * shade_multiplier) as u8, 
+                        b: (5.0 * shade_multiplier) as u8
+                    };
+                    if lit {
+                        let (unit_ray_x, unit_ray_y) = directions[x];
+                        let tint = light_tint(
+                            &map.lights,
+                            player.player_x + unit_ray_x * depth,
+                            player.player_y + unit_ray_y * depth,
+                        );
+                        pixel_color = apply_tint(pixel_color, tint);
+                    }
+                    if let Some(spacing) = life.floor_grid {
+                        // a row covers depth² / 2hE of floor, keep lines at least that wide
+                        let row_depth = depth * depth / (2.0 * height as f64 * player.eye_height);
+                        let half_width = (life.floor_grid_width / 2.0).max(row_depth / 2.0);
+                        let (unit_ray_x, unit_ray_y) = directions[x];
+                        let (floor_x, floor_y) = (player.player_x + unit_ray_x * depth, player.player_y + unit_ray_y * depth);
+                        if on_grid_line(floor_x, floor_y, spacing, half_width) {
+                            pixel_color = life.floor_grid_color;
+                        }
+                    }
+                }
+                // wall
+            } else if y < wall_end {
+                depth = distance_to_wall;
+                wall_base[x] |= y == wall_start;
+                let shade_distance = if fog_immune[x] { 0.0 } else { distance_to_wall };
+                wall_color_shade = (-13.4375 * shade_distance + 235.0) as u8;
+                // 0.0 at the top of the wall, 1.0 at the bottom
+                let v = (ceiling_lower_boundary - y as f64) / (ceiling_lower_boundary - floor_upper_boundary);
+                let gradient = wall_gradient(life.wall_gradient, v);
+                pixel_color = match textures[x] {
+                    Some((texture, level, u)) => {
+                        let texel = texture.sample(level, u, v);
+                        let shade = wall_color_shade as f64 / 255.0;
+                        Color {
+                            r: (texel.r as f64 * shade) as u8,
+                            g: (texel.g as f64 * shade) as u8,
+                            b: (texel.b as f64 * shade) as u8,
+                        }
+                    }
+                    None => {
+                        let shade = match patterns[x] {
+                            Some((pattern, u)) => (wall_color_shade as f64 * pattern.shade(u, v)) as u8,
+                            None => wall_color_shade,
+                        };
+                        Color { r: shade, g: shade, b: shade }
+                    }
+                };
+                let [tint_r, tint_g, tint_b] = face_tints[x];
+                if gradient != 1.0 || tinted {
+                    pixel_color = Color {
+                        r: (pixel_color.r as f64 * gradient * tint_r) as u8,
+                        g: (pixel_color.g as f64 * gradient * tint_g) as u8,
+                        b: (pixel_color.b as f64 * gradient * tint_b) as u8,
+                    };
+                }
+                if lit {
+                    pixel_color = apply_tint(pixel_color, wall_tints[x]);
+                }
+                if hits[x].bounces > 0 {
+                    pixel_color = pixel_color.blend(Color { r: 0, g: 0, b: 0 }, 1.0 - MIRROR_SHADE.powi(hits[x].bounces as i32) as f32);
+                }
+                if aimed.is_some() && hits[x].cell == aimed {
+                    pixel_color = pixel_color.blend(AIM_HIGHLIGHT, 0.3);
+                }
+                // the far floor is as good as black, only the sky shows through
+                let fade = far_fade(distance_to_wall, life.max_wall_check_depth, life.far_fade);
+                if fade > 0.0 && !fog_immune[x] {
+                    let behind = if y as f64 >= horizon { sky_color(x, y) } else { Color { r: 0, g: 0, b: 0 } };
+                    pixel_color = pixel_color.blend(behind, fade as f32);
+                    if y as f64 >= horizon && map.sky.is_none() {
+                        alpha = 1.0 - fade as f32;
+                    }
+                }
+                // ceiling
+            } else {
+                depth = f64::INFINITY;
+                pixel_color = sky_color(x, y);
+                if map.sky.is_none() {
+                    alpha = 0.0;
+                }
+            }
+
+            let glass = &hits[x].glass;
+            if depth > glass.distance {
+                let [r, g, b] = glass.apply([pixel_color.r, pixel_color.g, pixel_color.b]);
+                pixel_color = Color { r, g, b };
+                alpha = 1.0 - glass.transmittance as f32 * (1.0 - alpha);
+            }
+            if let Some(coverage) = coverage.as_deref_mut() {
+                coverage[y * width + x] = alpha;
+            }
+
+            if !depths.is_empty() && (y >= wall_start || raised != FloorHit::Ground) {
+                depths[y * width + x] = depth;
+            }
+
+            *pixel = pixel_color;
+        }
+    }
+
+    if life.floor_blend_rows > 0 {
+        for (x, hit) in hits.iter().enumerate() {
+            if hit.cell.is_none() || !wall_base[x] {
+                continue;
+            }
+            let (floor_upper_boundary, ceiling_lower_boundary) = wall_band(height, horizon, hit.distance, player.eye_height);
+            let (wall_start, _) = wall_rows(floor_upper_boundary, ceiling_lower_boundary, height);
+            let wall_color = buffer[wall_start * width + x];
+            for row in 1..=life.floor_blend_rows.min(floor_run[x]) {
+                let index = (wall_start - row) * width + x;
+                let blend = floor_blend(row, life.floor_blend_rows) as f32;
+                buffer[index] = buffer[index].blend(wall_color, blend);
+                if let Some(coverage) = coverage.as_deref_mut() {
+                    coverage[index] += (coverage[wall_start * width + x] - coverage[index]) * blend;
+                }
+            }
+        }
+    }
+
+    if life.outline > 0.0 {
+        let edges = outline_columns(&hits, life.outline);
+        for (x, hit) in hits.iter().enumerate() {
+            if hit.cell.is_none() {
+                continue;
+            }
+            let (floor_upper_boundary, ceiling_lower_boundary) = wall_band(height, horizon, hit.distance, player.eye_height);
+            let (wall_start, wall_end) = wall_rows(floor_upper_boundary, ceiling_lower_boundary, height);
+            let mut rows = Vec::new();
+            if edges[x] {
+                rows.extend(wall_start..wall_end);
+            } else if life.outline_wall_ends && wall_start < wall_end {
+                // only ends that are on screen, not ones cut off by its edge
+                if wall_start > 0 {
+                    rows.push(wall_start);
+                }
+                if wall_end < height {
+                    rows.push(wall_end - 1);
+                }
+            }
+            for y in rows {
+                buffer[y * width + x] = life.outline_color;
+                if let Some(coverage) = coverage.as_deref_mut() {
+                    coverage[y * width + x] = 1.0;
+                }
+            }
+        }
+    }
+
+    if let Some(columns) = columns {
+        for (x, hit) in hits.iter().enumerate() {
+            let (floor_upper_boundary, ceiling_lower_boundary) = wall_band(height, horizon, hit.distance, player.eye_height);
+            let (wall_start, wall_end) = wall_rows(floor_upper_boundary, ceiling_lower_boundary, height);
+            columns(map, hit, &mut ColumnPixels::new(buffer, width, height, x, wall_start..wall_end));
+        }
+    }
+
+    if life.horizon_line {
+        if let Some(row) = horizon_line_row(height, player.pitch, life.max_pitch, player.eye_height) {
+            for pixel in &mut buffer[row * width..(row + 1) * width] {
+                *pixel = pixel.blend(life.horizon_color, HORIZON_LINE_OPACITY);
+            }
+            if let Some(coverage) = coverage.as_deref_mut() {
+                for alpha in &mut coverage[row * width..(row + 1) * width] {
+                    *alpha += (1.0 - *alpha) * HORIZON_LINE_OPACITY;
+                }
+            }
+        }
+    }
+
+    if !depths.is_empty() {
+        draw_light_shafts(buffer, width, height, map, player, life, depths);
+    }
+
+    if let Some(column_depths) = column_depths.as_deref_mut() {
+        column_depths.clear();
+        column_depths.extend(hits.iter().map(|hit| hit.distance));
+    }
+    sprite_pixels(width, height, map, player, life, &hits, |index, color, distance| {
+        buffer[index] = color;
+        if let Some(coverage) = coverage.as_deref_mut() {
+            coverage[index] = 1.0;
+        }
+        if let Some(column_depths) = column_depths.as_deref_mut() {
+            let column = &mut column_depths[index % width];
+            *column = column.min(distance);
+        }
+    });
+    FrameStats::from_hits(&hits)
+}
+
+/// Add each light's glow around where it shows in the view, at half a wall's
+/// height, leaving out pixels showing something nearer in `depths`.
+fn draw_light_shafts(buffer: &mut [Color], width: usize, height: usize, map: &Map, player: &Player, life: &Life, depths: &[f64]) {
+    let horizon = horizon_row(height, player.pitch, life.max_pitch, player.eye_height);
+    for light in &map.lights {
+        let Some((column, _)) = project_point(player, life, width, height, light.x, light.y) else {
+            continue;
+        };
+        let distance = (light.x - player.player_x).hypot(light.y - player.player_y);
+        let (floor_line, ceiling_line) = wall_band(height, horizon, distance, player.eye_height);
+        let (center_x, center_y) = (column as f64 + 0.5, (floor_line + ceiling_line) / 2.0);
+        let radius = height as f64 * LIGHT_SHAFT_SIZE / distance.max(1.0);
+        let strength = life.light_shafts * light.intensity;
+
+        let rows = (center_y - 2.0 * radius).max(0.0) as usize..((center_y + 2.0 * radius).max(0.0) as usize + 1).min(height);
+        let columns = (center_x - radius).max(0.0) as usize..((center_x + radius).max(0.0) as usize + 1).min(width);
+        for y in rows {
+            for x in columns.clone() {
+                let index = y * width + x;
+                if depths[index] < distance {
+                    continue;
+                }
+                let glow = strength * light_shaft_glow(x as f64 + 0.5 - center_x, y as f64 + 0.5 - center_y, radius);
+                if glow <= 0.0 {
+                    continue;
+                }
+                let add = |value: u8, light: u8| (value as f64 + light as f64 * glow).min(255.0) as u8;
+                let pixel = &mut buffer[index];
+                *pixel = Color { r: add(pixel.r, light.color.r), g: add(pixel.g, light.color.g), b: add(pixel.b, light.color.b) };
+            }
+        }
+    }
+}
+
+/// A rectangle of the buffer, `y` being its bottom row as in pixel_canvas.
+#[derive(Clone, Copy, Debug)]
+pub struct Viewport {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+/// Maps between map coordinates and pixels of a map drawn from above, square cells centered in `viewport`.
+pub struct MapView {
+    pub viewport: Viewport,
+    pub cell_size: f64,
+    offset_x: f64,
+    offset_y: f64,
+}
+
+impl MapView {
+    pub fn new(viewport: Viewport, map: &Map) -> Self {
+        let cell_size = (viewport.width as f64 / map.width as f64).min(viewport.height as f64 / map.height as f64);
+        let offset_x = (viewport.width as f64 - cell_size * map.width as f64) / 2.0;
+        let offset_y = (viewport.height as f64 - cell_size * map.height as f64) / 2.0;
+        Self { viewport, cell_size, offset_x, offset_y }
+    }
+
+    /// Map coordinates at the center of buffer pixel `(x, y)`.
+    pub fn to_map(&self, x: usize, y: usize) -> (f64, f64) {
+        // image rows go bottom to top, map rows go top to bottom
+        let local_x = (x - self.viewport.x) as f64;
+        let local_y = (self.viewport.y + self.viewport.height - 1 - y) as f64;
+        ((local_x + 0.5 - self.offset_x) / self.cell_size, (local_y + 0.5 - self.offset_y) / self.cell_size)
+    }
+
+    /// Buffer pixel showing map coordinates `(x, y)`, if it's inside the viewport.
+    pub fn to_buffer(&self, x: f64, y: f64) -> Option<(usize, usize)> {
+        let local_x = x * self.cell_size + self.offset_x;
+        let local_y = y * self.cell_size + self.offset_y;
+        if local_x < 0.0 || local_y < 0.0 {
+            return None;
+        }
+        let (local_x, local_y) = (local_x as usize, local_y as usize);
+        if local_x >= self.viewport.width || local_y >= self.viewport.height {
+            return None;
+        }
+        Some((self.viewport.x + local_x, self.viewport.y + self.viewport.height - 1 - local_y))
+    }
+}
+
+/// Color of pillars seen from above.
+const PILLAR_COLOR: Color = Color { r: 200, g: 200, b: 200 };
+
+const FOV_EDGE_COLOR: Color = Color { r: 255, g: 235, b: 120 };
+
+const CENTER_HIT_COLOR: Color = Color { r: 255, g: 60, b: 40 };
+
+/// Headings of the left and right edges of the field of view.
+pub fn fov_edges(heading: f64, fov_angle: f64) -> [f64; 2] {
+    [heading - fov_angle / 2.0, heading + fov_angle / 2.0]
+}
+
+/// Color of a wall kind seen from above, telling apart what it blocks.
+pub fn wall_color(kind: &WallKind) -> Color {
+    if let Some([r, g, b]) = kind.glass {
+        return Color { r, g, b };
+    }
+    match (kind.blocks_movement, kind.blocks_sight) {
+        _ if kind.reflects => Color { r: 170, g: 230, b: 240 },
+        _ if kind.fog_immune => Color { r: 240, g: 200, b: 60 },
+        (true, true) => Color { r: 200, g: 200, b: 200 },
+        (true, false) => Color { r: 120, g: 160, b: 200 },
+        (false, _) => Color { r: 140, g: 90, b: 140 },
+    }
+}
+
+/// Draw the whole map from above, scaled to fit the window.
+pub fn render_topdown(buffer: &mut [Color], width: usize, height: usize, map: &Map, player: &Player, life: &Life) {
+    let viewport = Viewport { x: 0, y: 0, width, height };
+    draw_map(buffer, width, &MapView::new(viewport, map), map, player, life);
+}
+
+/// Draw the map from above into `view`'s viewport of a buffer `width` pixels wide.
+pub fn draw_map(buffer: &mut [Color], width: usize, view: &MapView, map: &Map, player: &Player, life: &Life) {
+    // fan of rays across the FOV so the wedge stops where the walls are
+    let [start_of_fov_angle, _] = fov_edges(player.vision_angle, life.fov_angle);
+    let wedge: Vec<f64> = (0..TOPDOWN_WEDGE_RAYS)
+        .map(|i| {
+            let ray_angle = start_of_fov_angle + ((i as f64 + 0.5) / TOPDOWN_WEDGE_RAYS as f64) * life.fov_angle;
+            cast_ray(map, player.player_x, player.player_y, ray_angle, life.max_wall_check_depth, 0).distance
+        })
+        .collect();
+
+    let viewport = view.viewport;
+    for y in viewport.y..viewport.y + viewport.height {
+        for x in viewport.x..viewport.x + viewport.width {
+            let pixel = &mut buffer[y * width + x];
+            let (map_x, map_y) = view.to_map(x, y);
+
+            if map_x < 0.0 || map_y < 0.0 || map.out_of_bounds(map_x as u16, map_y as u16) {
+                *pixel = Color { r: 0, g: 0, b: 0 };
+                continue;
+            }
+
+            let dx = map_x - player.player_x;
+            let dy = map_y - player.player_y;
+            let distance = dx.hypot(dy);
+
+            // angle of this point measured from the start of the FOV, same
+            // convention as the player's heading (x = sin, y = cos)
+            let fov_offset = (dx.atan2(dy) - start_of_fov_angle).rem_euclid(std::f64::consts::TAU);
+            let in_wedge = fov_offset < life.fov_angle
+                && distance < wedge[((fov_offset / life.fov_angle) * TOPDOWN_WEDGE_RAYS as f64) as usize];
+
+            *pixel = if distance < 0.25 {
+                // player
+                Color { r: 255, g: 220, b: 0 }
+            } else if let Some(kind) = map.wall_kind(map_x, map_y) {
+                wall_color(kind)
+            } else if map.in_pillar(map_x, map_y) {
+                PILLAR_COLOR
+            } else if in_wedge {
+                Color { r: 200, g: 150, b: 40 }
+            } else if map.floor_height(map_x, map_y) > 0.0 {
+                Color { r: 150, g: 70, b: 25 }
+            } else {
+                Color { r: 90, g: 25, b: 3 }
+            };
+        }
+    }
+
+    let (x, y) = (player.player_x, player.player_y);
+    if life.map_fov_edges {
+        for angle in fov_edges(player.vision_angle, life.fov_angle) {
+            // only as far as the first mirror or portal, where the ray turns
+            let distance = cast_ray(map, x, y, angle, life.max_wall_check_depth, 0).direct_distance;
+            let end = (x + angle.sin() * distance, y + angle.cos() * distance);
+            draw_map_line(buffer, width, view, (x, y), end, FOV_EDGE_COLOR);
+        }
+    }
+    if life.map_center_hit {
+        let hit = cast_ray(map, x, y, player.vision_angle, life.max_wall_check_depth, 0);
+        if hit.cell.is_some() {
+            let distance = hit.direct_distance;
+            let (hit_x, hit_y) = (x + player.vision_angle.sin() * distance, y + player.vision_angle.cos() * distance);
+            if let Some((pixel_x, pixel_y)) = view.to_buffer(hit_x, hit_y) {
+                // a small square, clipped to the viewport
+                let viewport = view.viewport;
+                for mark_y in pixel_y.saturating_sub(1)..=pixel_y + 1 {
+                    for mark_x in pixel_x.saturating_sub(1)..=pixel_x + 1 {
+                        if (viewport.x..viewport.x + viewport.width).contains(&mark_x)
+                            && (viewport.y..viewport.y + viewport.height).contains(&mark_y)
+                        {
+                            buffer[mark_y * width + mark_x] = CENTER_HIT_COLOR;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Draw a line between map points `from` and `to` into `view`, a step of
+/// about half a pixel at a time, leaving out whatever falls outside it.
+fn draw_map_line(buffer: &mut [Color], width: usize, view: &MapView, from: (f64, f64), to: (f64, f64), color: Color) {
+    let length = (to.0 - from.0).hypot(to.1 - from.1) * view.cell_size;
+    let steps = (length * 2.0).ceil().max(1.0) as usize;
+    for step in 0..=steps {
+        let t = step as f64 / steps as f64;
+        let point = (from.0 + (to.0 - from.0) * t, from.1 + (to.1 - from.1) * t);
+        if let Some((x, y)) = view.to_buffer(point.0, point.1) {
+            buffer[y * width + x] = color;
+        }
+    }
+}
+
+/// Draw each column's wall distance in gray, white at the player to black at `max_wall_check_depth`.
+pub fn render_depth(buffer: &mut [Color], width: usize, height: usize, map: &Map, player: &Player, life: &Life) -> FrameStats {
+    let hits = cast_columns(map, player, life, width);
+    let shades: Vec<Color> = hits
+        .iter()
+        .map(|hit| {
+            let shade = (255.0 * (1.0 - hit.distance / life.max_wall_check_depth)).clamp(0.0, 255.0) as u8;
+            Color { r: shade, g: shade, b: shade }
+        })
+        .collect();
+
+    for row in buffer.chunks_mut(width).take(height) {
+        row.copy_from_slice(&shades);
+    }
+    FrameStats::from_hits(&hits)
+}
+
+/// Wall outlines on black: tops, bottoms and a line wherever the hit cell changes between columns.
+pub fn render_wireframe(buffer: &mut [Color], width: usize, height: usize, map: &Map, player: &Player, life: &Life) -> FrameStats {
+    let hits = cast_columns(map, player, life, width);
+    buffer[..width * height].fill(Color { r: 0, g: 0, b: 0 });
+    let horizon = horizon_row(height, player.pitch, life.max_pitch, player.eye_height);
+
+    for (x, hit) in hits.iter().enumerate() {
+        if hit.cell.is_none() {
+            continue;
+        }
+        let (floor_upper_boundary, ceiling_lower_boundary) = wall_band(height, horizon, hit.distance, player.eye_height);
+        let (wall_start, wall_end) = wall_rows(floor_upper_boundary, ceiling_lower_boundary, height);
+        if wall_start >= wall_end {
+            continue;
+        }
+
+        let shade = (-13.4375 * hit.distance + 235.0).clamp(40.0, 235.0) as u8;
+        let color = Color { r: shade, g: shade, b: shade };
+        let edge = (x > 0 && hits[x - 1].cell != hit.cell) || (x + 1 < width && hits[x + 1].cell != hit.cell);
+
+        if edge {
+            for y in wall_start..wall_end {
+                buffer[y * width + x] = color;
+            }
+        } else {
+            buffer[wall_start * width + x] = color;
+            buffer[(wall_end - 1) * width + x] = color;
+        }
+    }
+
+    FrameStats::from_hits(&hits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::light::Light;
+    use crate::map::RAISED_FLOOR_HEIGHT;
+    use std::f64::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+
+    #[test]
+    fn internal_buffer_matches_scale() {
+        assert_eq!(internal_size(320, 200, 0.5), (160, 100));
+        assert_eq!(internal_size(320, 200, 1.0), (320, 200));
+        assert_eq!(internal_size(320, 200, 0.0), (1, 1));
+
+        let mut buffer = vec![Color { r: 0, g: 0, b: 0 }; 320 * 200];
+        let mut scratch = Vec::new();
+        let drawn = render_scaled(&mut buffer, 320, 200, 0.25, &mut scratch, |internal, width, height| {
+            internal.fill(Color { r: 255, g: 0, b: 0 });
+            (internal.len(), width, height)
+        });
+        assert_eq!(drawn, (80 * 50, 80, 50));
+        assert!(buffer.iter().all(|pixel| pixel.r == 255));
+
+        // the next frame draws in the same buffer
+        let kept = scratch.as_ptr();
+        render_scaled(&mut buffer, 320, 200, 0.25, &mut scratch, |_, _, _| ());
+        assert_eq!(scratch.as_ptr(), kept);
+    }
+
+    #[test]
+    fn floor_shade_stays_in_range_for_close_walls() {
+        let height = 200;
+        for distance in [1e-3, 0.01, 0.1, 0.5] {
+            let (floor_upper_boundary, _) = wall_band(height, 100.0, distance, 0.5);
+            for y in 0..height {
+                let shade = floor_shade(y as f64, floor_upper_boundary);
+                assert!(shade.is_finite() && (0.0..=1.0).contains(&shade), "{} at row {}", shade, y);
+            }
+        }
+        assert_eq!(floor_shade(0.0, 50.0), 1.0);
+        assert!((floor_shade(50.0, 50.0) - 0.05).abs() < 1e-9);
+    }
+
+    #[test]
+    fn wall_taller_than_screen_is_clamped() {
+        // half a cell away a wall is four screens tall
+        let (floor_upper_boundary, ceiling_lower_boundary) = wall_band(200, 100.0, 0.5, 0.5);
+        assert!(floor_upper_boundary < 0.0 && ceiling_lower_boundary > 200.0);
+        assert_eq!(wall_rows(floor_upper_boundary, ceiling_lower_boundary, 200), (0, 200));
+
+        let (floor_upper_boundary, ceiling_lower_boundary) = wall_band(200, 100.0, 4.0, 0.5);
+        assert_eq!(wall_rows(floor_upper_boundary, ceiling_lower_boundary, 200), (50, 150));
+    }
+
+    #[test]
+    fn eye_height_moves_horizon() {
+        let standing = horizon_row(200, 0.0, 0.25, 0.5);
+        let low = horizon_row(200, 0.0, 0.25, 0.3);
+        assert_eq!(standing, 100.0);
+        assert!((standing - low - 40.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn wall_band_at_zero_distance_is_finite() {
+        let (floor_line, ceiling_line) = wall_band(200, 100.0, 0.0, 0.5);
+        let v = (ceiling_line - 50.0) / (ceiling_line - floor_line);
+        assert!(v.is_finite());
+    }
+
+    #[test]
+    fn floor_ray_lands_on_raised_cell() {
+        let map = Map::parse("#######\n#..._.#\n#######").unwrap();
+        let spans = raised_spans(&map, 1.5, 1.5, std::f64::consts::FRAC_PI_2, 10.0);
+        assert_eq!(spans, vec![(2.5, 3.5, RAISED_FLOOR_HEIGHT)]);
+
+        // steep enough to reach the riser, just right for the top, or too shallow to come down on it
+        assert_eq!(floor_hit(&spans, 0.5, 0.15, 10.0), FloorHit::Riser(2.5));
+        assert_eq!(floor_hit(&spans, 0.5, 0.08, 10.0), FloorHit::Top(0.25 / 0.08));
+        assert_eq!(floor_hit(&spans, 0.5, 0.05, 10.0), FloorHit::Ground);
+        // a wall in front hides it
+        assert_eq!(floor_hit(&spans, 0.5, 0.08, 2.0), FloorHit::Ground);
+    }
+
+    #[test]
+    fn two_viewports_share_one_buffer() {
+        let map = Map::parse("########\n#......#\n#..#...#\n#......#\n########").unwrap();
+        let life = Life::new(1.0, 16.0);
+        let (left_player, right_player) = (Player::new(1.5, 3.5, 2.5), Player::new(6.5, 1.5, -0.8));
+        let (width, height) = (40, 24);
+        let mut buffer = vec![Color { r: 1, g: 2, b: 3 }; width * height];
+        let left = Viewport { x: 0, y: 0, width: 20, height };
+        let right = Viewport { x: 20, y: 0, width: 20, height };
+        let mut scratch = Scratch::default();
+        Renderer::FirstPerson.render_frame_into(&mut buffer, width, left, &mut scratch, &map, &left_player, &life);
+        Renderer::FirstPerson.render_frame_into(&mut buffer, width, right, &mut scratch, &map, &right_player, &life);
+
+        let rgb = |color: &Color| (color.r, color.g, color.b);
+        for (viewport, player) in [(left, &left_player), (right, &right_player)] {
+            let mut alone = vec![Color { r: 0, g: 0, b: 0 }; viewport.width * height];
+            Renderer::FirstPerson.render(&mut alone, viewport.width, height, &map, player, &life);
+            for y in 0..height {
+                let row = &buffer[y * width + viewport.x..][..viewport.width];
+                assert!(row.iter().map(rgb).eq(alone[y * viewport.width..][..viewport.width].iter().map(rgb)));
+            }
+        }
+    }
+
+    #[test]
+    fn wall_gradient_darkens_the_top() {
+        assert_eq!((wall_gradient(0.4, 0.0), wall_gradient(0.4, 1.0)), (0.6, 1.0));
+        assert_eq!((wall_gradient(-0.4, 0.0), wall_gradient(-0.4, 1.0)), (1.0, 0.6));
+
+        let map = Map::parse("#######\n#.....#\n#######").unwrap();
+        let player = Player::new(1.5, 1.5, std::f64::consts::FRAC_PI_2);
+        let (width, height) = (20, 100);
+        let column = |gradient: f64| {
+            let mut life = Life::new(0.5, 16.0);
+            life.wall_gradient = gradient;
+            let mut buffer = vec![Color { r: 0, g: 0, b: 0 }; width * height];
+            render_first_person(&mut buffer, width, height, &map, &player, &life);
+            buffer.iter().skip(width / 2).step_by(width).map(|c| c.r as f64 + c.g as f64 + c.b as f64).collect::<Vec<_>>()
+        };
+        let (even, shaded) = (column(0.0), column(0.5));
+        let (floor_upper_boundary, ceiling_lower_boundary) = wall_band(height, 50.0, 4.5, 0.5);
+        let (bottom, top) = (floor_upper_boundary.ceil() as usize, ceiling_lower_boundary.floor() as usize - 1);
+        for row in [bottom, top] {
+            let v = (ceiling_lower_boundary - row as f64) / (ceiling_lower_boundary - floor_upper_boundary);
+            assert!((shaded[row] / even[row] - wall_gradient(0.5, v)).abs() < 0.01);
+        }
+        assert!(shaded[top] / even[top] < 0.6 && shaded[bottom] / even[bottom] > 0.95);
+    }
+
+    #[test]
+    fn each_face_gets_its_tint() {
+        let map = Map::parse("######\n#....#\n#....#\n#....#\n######").unwrap();
+        let faces = [(0.0, Face::North), (PI, Face::South), (FRAC_PI_2, Face::West), (-FRAC_PI_2, Face::East)];
+        for (angle, face) in faces {
+            assert_eq!(cast_ray(&map, 2.5, 2.5, angle, 16.0, 0).face(), Some(face));
+        }
+        assert_eq!(face_tint(Face::North), [1.0, 1.0, 1.0]);
+        assert_eq!(face_tint(Face::South), [0.9, 0.9, 0.95]);
+        assert_eq!(face_tint(Face::East), [0.97, 0.93, 0.88]);
+        assert_eq!(face_tint(Face::West), [0.85, 0.87, 0.9]);
+    }
+
+    #[test]
+    fn pitch_past_the_clamp_stops_the_horizon() {
+        assert_eq!(horizon_row(200, 0.1, 0.25, 0.5), 80.0);
+        assert_eq!(horizon_row(200, 0.25, 0.25, 0.5), 50.0);
+        assert_eq!(horizon_row(200, 0.9, 0.25, 0.5), 50.0);
+        assert_eq!(horizon_row(200, -0.9, 0.25, 0.5), 150.0);
+        // a negative limit doesn't let the horizon off at all
+        assert_eq!(horizon_row(200, 0.9, -1.0, 0.5), 100.0);
+    }
+
+    #[test]
+    fn walls_fade_out_near_max_depth() {
+        assert_eq!(far_fade(10.0, 16.0, 3.0), 0.0);
+        assert_eq!(far_fade(13.0, 16.0, 3.0), 0.0);
+        assert!((far_fade(14.5, 16.0, 3.0) - 0.5).abs() < 1e-9);
+        assert!((far_fade(15.7, 16.0, 3.0) - 0.9).abs() < 1e-9);
+        assert_eq!(far_fade(16.0, 16.0, 3.0), 1.0);
+        assert_eq!(far_fade(20.0, 16.0, 3.0), 1.0);
+        assert_eq!(far_fade(15.9, 16.0, 0.0), 0.0);
+    }
+
+    #[test]
+    fn horizon_line_follows_the_pitch() {
+        let map = Map::parse("########\n#......#\n#......#\n#......#\n########").unwrap();
+        let mut player = Player::new(1.5, 2.5, FRAC_PI_2);
+        player.pitch = 0.1;
+        let mut life = Life::new(1.0, 16.0);
+        let (width, height) = (40, 60);
+        let mut plain = vec![Color { r: 0, g: 0, b: 0 }; width * height];
+        render_first_person(&mut plain, width, height, &map, &player, &life);
+        life.horizon_line = true;
+        let mut lined = plain.clone();
+        render_first_person(&mut lined, width, height, &map, &player, &life);
+
+        // 30 rows up the middle, less a tenth of the screen for the pitch
+        assert_eq!(horizon_line_row(height, player.pitch, life.max_pitch, player.eye_height), Some(24));
+        let rgb = |color: &Color| (color.r, color.g, color.b);
+        let changed: Vec<usize> = (0..width * height).filter(|&i| rgb(&plain[i]) != rgb(&lined[i])).map(|i| i / width).collect();
+        assert_eq!(changed.len(), width);
+        assert!(changed.iter().all(|&row| row == 24));
+    }
+
+    #[test]
+    fn wall_ahead_is_visible_and_wall_behind_is_not() {
+        let map = Map::new(15, 16);
+        let player = Player::new(8.5, 8.5, 0.0);
+        let cells = visible_cells(&map, &player, &Life::new(FRAC_PI_4, 16.0), 64);
+        assert!(cells.contains(&(8, 14)));
+        assert!(!cells.contains(&(8, 5)));
+        assert!(cells.contains(&(8, 8)));
+    }
+
+    #[test]
+    fn fog_immune_wall_keeps_its_color_far_away() {
+        let wall_at = |glyph: char, distance: usize| {
+            let map = Map::parse(&format!("{0}\n#{1}{2}\n{0}", "#".repeat(distance + 2), ".".repeat(distance), glyph)).unwrap();
+            let player = Player::new(1.5, 1.5, FRAC_PI_2);
+            let (width, height) = (40, 30);
+            let mut life = Life::new(FRAC_PI_4, 16.0);
+            // nothing aimed at, so nothing highlighted
+            life.interaction_distance = 0.0;
+            let mut buffer = vec![Color { r: 0, g: 0, b: 0 }; width * height];
+            render_first_person(&mut buffer, width, height, &map, &player, &life);
+            let pixel = buffer[height / 2 * width + width / 2];
+            (pixel.r, pixel.g, pixel.b)
+        };
+        assert_eq!(wall_at('*', 15), wall_at('*', 2));
+        assert!(wall_at('#', 15).0 < wall_at('#', 2).0 / 2);
+    }
+
+    #[test]
+    fn point_ahead_projects_to_the_middle_column() {
+        let player = Player::new(5.0, 5.0, FRAC_PI_2);
+        let life = Life::new(FRAC_PI_4, 16.0);
+        let (width, height) = (200, 100);
+        let (column, near_row) = project_point(&player, &life, width, height, 8.0, 5.0).unwrap();
+        assert_eq!(column, 100);
+        let (_, far_row) = project_point(&player, &life, width, height, 15.0, 5.0).unwrap();
+        // floor further off shows closer to the horizon in the middle
+        assert!(near_row < far_row && far_row < 50);
+        assert_eq!(project_point(&player, &life, width, height, 2.0, 5.0), None);
+    }
+
+    #[test]
+    fn depth_step_gets_an_outline_column() {
+        let map = Map::parse("##########\n#........#\n#........#\n#....#...#\n#........#\n#........#\n#........#\n##########").unwrap();
+        // a block in front of the far wall, straight ahead
+        let player = Player::new(5.5, 6.5, PI);
+        let mut life = Life::new(FRAC_PI_4, 16.0);
+        let (width, height) = (60, 40);
+        let hits = cast_columns(&map, &player, &life, width);
+        let edges: Vec<usize> = outline_columns(&hits, 1.0).iter().enumerate().filter(|(_, &edge)| edge).map(|(x, _)| x).collect();
+        assert_eq!(edges.len(), 2);
+        assert!(edges.iter().all(|&x| hits[x].cell == Some((5, 3))));
+
+        life.outline = 1.0;
+        life.outline_color = Color { r: 255, g: 0, b: 255 };
+        let mut buffer = vec![Color { r: 0, g: 0, b: 0 }; width * height];
+        render_first_person(&mut buffer, width, height, &map, &player, &life);
+        let (floor_upper_boundary, ceiling_lower_boundary) = wall_band(height, height as f64 / 2.0, hits[edges[0]].distance, player.eye_height);
+        let (wall_start, wall_end) = wall_rows(floor_upper_boundary, ceiling_lower_boundary, height);
+        let pixel = |y: usize| buffer[y * width + edges[0]];
+        assert!((wall_start..wall_end).all(|y| (pixel(y).r, pixel(y).g, pixel(y).b) == (255, 0, 255)));
+    }
+
+    #[test]
+    fn column_depths_take_the_nearest_of_wall_and_sprite() {
+        let mut map = Map::parse("#######\n#.....#\n#.....#\n#.....#\n#.....#\n#######").unwrap();
+        map.sprites.push(crate::sprite::Sprite::new(3.5, 2.5, 0.5, 0.6, Color { r: 200, g: 0, b: 0 }));
+        let player = Player::new(3.5, 4.5, PI);
+        let life = Life::new(FRAC_PI_4, 16.0);
+        let (width, height) = (40, 30);
+        let mut buffer = vec![Color { r: 0, g: 0, b: 0 }; width * height];
+        let mut depths = Vec::new();
+        first_person(&mut buffer, width, height, &map, &player, &life, Extras { depths: Some(&mut depths), ..Extras::default() });
+
+        assert_eq!(depths.len(), width);
+        assert!((depths[width / 2] - 2.0).abs() < 1e-6, "the sprite in the middle");
+        assert!((depths[0] - 3.5).abs() < 0.5, "the far wall at the edge");
+    }
+
+    #[test]
+    fn drawn_fov_edges_are_half_the_fov_from_the_heading() {
+        let wall = "#".repeat(21);
+        let row = format!("#{}#", ".".repeat(19));
+        let map = Map::parse(&format!("{0}\n{1}\n{0}", wall, vec![row; 19].join("\n"))).unwrap();
+        let player = Player::new(10.5, 10.5, 0.0);
+        let mut life = Life::new(FRAC_PI_2, 16.0);
+        life.map_fov_edges = true;
+        let (width, height) = (210, 210);
+        let mut buffer = vec![Color { r: 0, g: 0, b: 0 }; width * height];
+        render_topdown(&mut buffer, width, height, &map, &player, &life);
+
+        let view = MapView::new(Viewport { x: 0, y: 0, width, height }, &map);
+        let mut widest: f64 = 0.0;
+        for (index, pixel) in buffer.iter().enumerate() {
+            if (pixel.r, pixel.g, pixel.b) != (FOV_EDGE_COLOR.r, FOV_EDGE_COLOR.g, FOV_EDGE_COLOR.b) {
+                continue;
+            }
+            let (x, y) = view.to_map(index % width, index / width);
+            let (dx, dy) = (x - player.player_x, y - player.player_y);
+            if dx.hypot(dy) > 3.0 {
+                widest = widest.max(dx.atan2(dy).abs());
+            }
+        }
+        assert!((widest - FRAC_PI_4).abs() < 0.03, "{}", widest);
+    }
+
+    #[test]
+    fn floor_past_the_limit_is_drawn_in_the_fog_color() {
+        let map = Map::parse(&format!("{0}\n#{1}#\n{0}", "#".repeat(24), ".".repeat(22))).unwrap();
+        let player = Player::new(1.5, 1.5, FRAC_PI_2);
+        let (width, height) = (40, 60);
+        let mut life = Life::new(FRAC_PI_4, 16.0);
+        life.interaction_distance = 0.0;
+        life.floor_distance_limit = Some(4.0);
+        life.floor_fog_color = Color { r: 255, g: 0, b: 255 };
+        let mut buffer = vec![Color { r: 0, g: 0, b: 0 }; width * height];
+        render_first_person(&mut buffer, width, height, &map, &player, &life);
+
+        let horizon = horizon_row(height, 0.0, life.max_pitch, player.eye_height);
+        // the wall at the end of the corridor starts a few rows under the horizon
+        for y in 0..25 {
+            let pixel = buffer[y * width + width / 2];
+            let fogged = (pixel.r, pixel.g, pixel.b) == (255, 0, 255);
+            assert_eq!(fogged, floor_distance(height, horizon, y as f64 + 0.5, player.eye_height) > 4.0, "row {}", y);
+        }
+    }
+
+    #[test]
+    fn floor_pixel_on_a_grid_line_gets_the_line_color() {
+        let map = Map::parse(&format!("{0}\n#{1}#\n{0}", "#".repeat(14), ".".repeat(12))).unwrap();
+        // an odd width puts the middle column's ray straight ahead
+        let (width, height) = (41, 60);
+        let (row, between) = (10, 5);
+        let depth = |y: usize| floor_distance(height, height as f64 / 2.0, y as f64 + 0.5, 0.5);
+        // the floor at `row` straight ahead is on the line at x = 5
+        let player = Player::new(5.0 - depth(row), 1.5, FRAC_PI_2);
+        let mut life = Life::new(FRAC_PI_4, 16.0);
+        life.interaction_distance = 0.0;
+        life.floor_grid = Some(1.0);
+        let grid = (life.floor_grid_color.r, life.floor_grid_color.g, life.floor_grid_color.b);
+        let mut buffer = vec![Color { r: 0, g: 0, b: 0 }; width * height];
+        render_first_person(&mut buffer, width, height, &map, &player, &life);
+
+        let at = |y: usize| buffer[y * width + width / 2];
+        let on = at(row);
+        assert_eq!((on.r, on.g, on.b), grid);
+        // and at `between` it is clear of the lines
+        let floor_x = player.player_x + depth(between);
+        assert!((floor_x - floor_x.round()).abs() > 0.2, "{}", floor_x);
+        let off = at(between);
+        assert_ne!((off.r, off.g, off.b), grid);
+    }
+
+    #[test]
+    fn blended_floor_moves_steadily_towards_the_wall() {
+        let map = Map::parse("#######\n#.....#\n#.....#\n#.....#\n#######").unwrap();
+        let player = Player::new(2.5, 2.5, FRAC_PI_2);
+        let (width, height) = (41, 120);
+        let mut life = Life::new(FRAC_PI_4, 16.0);
+        life.interaction_distance = 0.0;
+        life.floor_blend_rows = 4;
+        let mut buffer = vec![Color { r: 0, g: 0, b: 0 }; width * height];
+        render_first_person(&mut buffer, width, height, &map, &player, &life);
+
+        let column: Vec<(u8, u8, u8)> = (0..height).map(|y| buffer[y * width + width / 2]).map(|c| (c.r, c.g, c.b)).collect();
+        // walls are gray, floor is brown
+        let wall_start = column.iter().position(|&(r, g, b)| r == g && g == b).unwrap();
+        // the plain floor row under the four blended ones, up to the wall
+        let run = &column[wall_start - 5..=wall_start];
+        let steady = |channel: fn(&(u8, u8, u8)) -> u8| {
+            let values: Vec<u8> = run.iter().map(channel).collect();
+            values.windows(2).all(|pair| pair[0] <= pair[1]) || values.windows(2).all(|pair| pair[0] >= pair[1])
+        };
+        assert!(steady(|c| c.0) && steady(|c| c.1) && steady(|c| c.2), "{:?}", run);
+        assert_ne!(column[wall_start - 1], column[wall_start - 5]);
+    }
+
+    #[test]
+    fn cell_ahead_fills_more_of_the_view_than_one_to_the_side() {
+        let map = Map::parse("#######\n#.....#\n#.....#\n#.....#\n#.....#\n#.....#\n#######").unwrap();
+        let player = Player::new(3.5, 3.5, FRAC_PI_2);
+        let life = Life::new(FRAC_PI_4, 16.0);
+        let ahead = cell_visible_fraction(&map, &player, &life, 200, 6, 3);
+        let side = cell_visible_fraction(&map, &player, &life, 200, 6, 2);
+        let behind = cell_visible_fraction(&map, &player, &life, 200, 0, 3);
+        assert!(ahead > side && side > 0.0, "ahead {} side {}", ahead, side);
+        assert_eq!(behind, 0.0);
+    }
+
+    #[test]
+    fn light_behind_a_near_wall_gives_no_glow() {
+        let glow_changes = |source: &str| {
+            let mut map = Map::parse(source).unwrap();
+            map.lights.push(Light::new(6.5, 2.5, Color { r: 255, g: 200, b: 120 }, 1.0, 4.0));
+            let player = Player::new(1.5, 2.5, FRAC_PI_2);
+            let (width, height) = (80, 60);
+            let mut life = Life::new(FRAC_PI_4, 16.0);
+            life.interaction_distance = 0.0;
+            let mut plain = vec![Color { r: 0, g: 0, b: 0 }; width * height];
+            render_first_person(&mut plain, width, height, &map, &player, &life);
+            life.light_shafts = 1.0;
+            let mut glowing = plain.clone();
+            render_first_person(&mut glowing, width, height, &map, &player, &life);
+            plain.iter().zip(&glowing).filter(|(a, b)| (a.r, a.g, a.b) != (b.r, b.g, b.b)).count()
+        };
+        assert_eq!(glow_changes("#########\n#..#....#\n#..#....#\n#..#....#\n#########"), 0);
+        assert!(glow_changes("#########\n#.......#\n#.......#\n#.......#\n#########") > 0);
+    }
+
+    #[test]
+    fn light_shafts_reuse_the_scratch_depths() {
+        let mut map = Map::parse("#########\n#.......#\n#.......#\n#.......#\n#########").unwrap();
+        map.lights.push(Light::new(6.5, 2.5, Color { r: 255, g: 200, b: 120 }, 1.0, 4.0));
+        let player = Player::new(1.5, 2.5, FRAC_PI_2);
+        let (width, height) = (80, 60);
+        let mut life = Life::new(FRAC_PI_4, 16.0);
+        life.light_shafts = 1.0;
+        let mut alone = vec![Color { r: 0, g: 0, b: 0 }; width * height];
+        Renderer::FirstPerson.render(&mut alone, width, height, &map, &player, &life);
+
+        let mut scratch = Scratch::default();
+        let mut buffer = vec![Color { r: 0, g: 0, b: 0 }; width * height];
+        Renderer::FirstPerson.render_with(&mut buffer, width, height, &map, &player, &life, &mut scratch);
+        let kept = scratch.shaft_depths.as_ptr();
+        Renderer::FirstPerson.render_with(&mut buffer, width, height, &map, &player, &life, &mut scratch);
+        assert_eq!(scratch.shaft_depths.as_ptr(), kept);
+        assert_eq!(scratch.shaft_depths.len(), width * height);
+        assert!(buffer.iter().zip(&alone).all(|(a, b)| (a.r, a.g, a.b) == (b.r, b.g, b.b)));
+    }
+
+    #[test]
+    fn frame_into_the_middle_of_a_bigger_buffer_leaves_the_rest() {
+        let map = Map::parse("########\n#......#\n#..#...#\n#......#\n########").unwrap();
+        let player = Player::new(1.5, 3.5, 2.5);
+        let life = Life::new(1.0, 16.0);
+        let (stride, rows) = (64, 40);
+        let viewport = Viewport { x: 20, y: 10, width: 24, height: 18 };
+        let mut buffer = vec![Color { r: 1, g: 2, b: 3 }; stride * rows];
+        let mut scratch = Scratch::default();
+        Renderer::FirstPerson.render_frame_into(&mut buffer, stride, viewport, &mut scratch, &map, &player, &life);
+
+        let mut alone = vec![Color { r: 0, g: 0, b: 0 }; viewport.width * viewport.height];
+        Renderer::FirstPerson.render(&mut alone, viewport.width, viewport.height, &map, &player, &life);
+        for (index, pixel) in buffer.iter().enumerate() {
+            let (x, y) = (index % stride, index / stride);
+            let inside = (viewport.x..viewport.x + viewport.width).contains(&x) && (viewport.y..viewport.y + viewport.height).contains(&y);
+            let expected = if inside { alone[(y - viewport.y) * viewport.width + x - viewport.x] } else { Color { r: 1, g: 2, b: 3 } };
+            assert_eq!((pixel.r, pixel.g, pixel.b), (expected.r, expected.g, expected.b), "pixel ({}, {})", x, y);
+        }
+
+        // the next frame draws in the same scratch without growing it
+        let kept = scratch.view.as_ptr();
+        Renderer::FirstPerson.render_frame_into(&mut buffer, stride, viewport, &mut scratch, &map, &player, &life);
+        assert_eq!(scratch.view.as_ptr(), kept);
+    }
+
+    #[test]
+    fn brick_fallback_varies_across_a_textureless_wall() {
+        let row_across = |pattern: WallPattern| {
+            let mut map = Map::parse("#######\n#.....#\n#.....#\n#.....#\n#######").unwrap();
+            map.patterns.insert('#', pattern);
+            let player = Player::new(3.0, 2.5, FRAC_PI_2);
+            let (width, height) = (80, 120);
+            let mut life = Life::new(FRAC_PI_4, 16.0);
+            life.interaction_distance = 0.0;
+            let mut buffer = vec![Color { r: 0, g: 0, b: 0 }; width * height];
+            render_first_person(&mut buffer, width, height, &map, &player, &life);
+            // a row partway up the wall ahead, away from the mortar between courses
+            buffer[(height / 2 + 7) * width..][width / 4..width * 3 / 4].iter().map(|c| c.r as f64).collect::<Vec<_>>()
+        };
+        let flat = row_across(WallPattern::Flat);
+        let bricks = row_across(WallPattern::Bricks);
+        // the flat wall only changes with distance, so what's left is the pattern
+        let shades: HashSet<u64> = bricks.iter().zip(&flat).map(|(brick, flat)| (brick / flat * 20.0).round() as u64).collect();
+        assert!(shades.len() > 1, "{:?}", shades);
+    }
+}
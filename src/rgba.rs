@@ -0,0 +1,85 @@
+use crate::life::Life;
+use crate::map::Map;
+use crate::player::Player;
+use crate::render::{internal_size, render_first_person_coverage, Renderer};
+use crate::stats::FrameStats;
+use pixel_canvas::Color;
+
+/// A pixel with straight, not premultiplied, alpha: 255 is opaque, 0 shows
+/// whatever the frame is laid over.
+pub type Rgba = [u8; 4];
+
+/// `color` drawn over black with `coverage` of it showing, as straight alpha.
+fn straight(color: Color, coverage: f32) -> Rgba {
+    // added light, such as a glow over empty background, covers as much as it brightens
+    let brightest = color.r.max(color.g).max(color.b);
+    let alpha = ((coverage.clamp(0.0, 1.0) * 255.0).round() as u8).max(brightest);
+    if alpha == 0 {
+        return [0, 0, 0, 0];
+    }
+    let unpremultiply = |channel: u8| (channel as u32 * 255 / alpha as u32).min(255) as u8;
+    [unpremultiply(color.r), unpremultiply(color.g), unpremultiply(color.b), alpha]
+}
+
+impl Renderer {
+    /// `render` with an alpha channel: on a map without a sky whatever would
+    /// show `life.background` is transparent, partly so through the far fade or glass.
+    pub fn render_rgba(self, buffer: &mut [Rgba], width: usize, height: usize, map: &Map, player: &Player, life: &Life) -> FrameStats {
+        if self != Renderer::FirstPerson {
+            let mut frame = vec![Color { r: 0, g: 0, b: 0 }; width * height];
+            let stats = self.render(&mut frame, width, height, map, player, life);
+            for (pixel, color) in buffer.iter_mut().zip(frame) {
+                *pixel = [color.r, color.g, color.b, 255];
+            }
+            return stats;
+        }
+
+        let (internal_width, internal_height) = internal_size(width, height, life.internal_scale);
+        let mut frame = vec![Color { r: 0, g: 0, b: 0 }; internal_width * internal_height];
+        let mut coverage = vec![1.0; internal_width * internal_height];
+        let mut life = life.clone();
+        life.background = Color { r: 0, g: 0, b: 0 };
+        let stats = render_first_person_coverage(&mut frame, internal_width, internal_height, map, player, &life, &mut coverage);
+
+        for (y, row) in buffer.chunks_mut(width).take(height).enumerate() {
+            let source_row = (y * internal_height / height) * internal_width;
+            for (x, pixel) in row.iter_mut().enumerate() {
+                let source = source_row + x * internal_width / width;
+                *pixel = straight(frame[source], coverage[source]);
+            }
+        }
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sprite::Sprite;
+    use std::f64::consts::FRAC_PI_4;
+
+    #[test]
+    fn background_is_transparent_and_the_rest_opaque() {
+        let source = "#######\n#.....#\n#.....#\n#.....#\n#.....#\n#.....#\n#######";
+        let mut map = Map::parse(source).unwrap();
+        let player = Player::new(3.5, 1.5, 0.0);
+        let life = Life::new(FRAC_PI_4, 16.0);
+        let (width, height) = (80, 60);
+        let render = |map: &Map| {
+            let mut buffer = vec![[0; 4]; width * height];
+            Renderer::FirstPerson.render_rgba(&mut buffer, width, height, map, &player, &life);
+            buffer
+        };
+
+        let empty = render(&map);
+        let column = |buffer: &[Rgba], y: usize| buffer[y * width + width / 2];
+        assert_eq!(column(&empty, 0)[3], 255, "floor");
+        assert_eq!(column(&empty, height / 2)[3], 255, "wall");
+        assert_eq!(column(&empty, height - 1), [0, 0, 0, 0], "ceiling");
+
+        map.sprites.push(Sprite::new(3.5, 2.5, 0.5, 3.0, Color { r: 200, g: 0, b: 0 }));
+        let with_sprite = render(&map);
+        let covered = (0..height).filter(|&y| column(&empty, y)[3] == 0 && column(&with_sprite, y)[3] == 255).count();
+        assert!(covered > 0, "sprite over the ceiling is opaque");
+    }
+}
@@ -0,0 +1,292 @@
+/*
+    Settings the player changes while playing are kept in a small text file
+    between runs, one `name = value` per line, the same way as a texture
+    manifest:
+
+        // comments start with two slashes, blank lines are skipped
+        fov = 0.785
+        turn_speed = 6
+        forward_speed = 12
+        strafe_speed = 8
+        quality = high
+        vignette = true
+        minimap = false
+
+    `bind` lines list the key bindings, by key name or, after a `#`, by
+    scancode in hex, as the help overlay shows them:
+
+        bind Forward = W
+        bind Forward = #11
+        bind Jump = Space
+
+    Any `bind` line replaces all the bindings the file would otherwise keep,
+    so a binding left out of the file stays unbound. Lines that can't be read
+    are skipped and the setting keeps the value it had, so a file from an
+    older version or one cut short still loads whatever it can.
+ */
+
+use crate::app::App;
+use crate::bindings::{key_from_name, Action, KeyBindings};
+use crate::life::{Quality, MAX_FOV_ANGLE, MIN_FOV_ANGLE};
+use crate::overlay::Layer;
+use crate::taa::TemporalAa;
+use crate::vignette::Vignette;
+use std::f64::consts::FRAC_PI_4;
+use std::fmt::Write;
+
+/// What `App::with_settings_file` keeps between runs.
+#[derive(Clone)]
+pub struct Settings {
+    /// Field of view when not zooming, in radians.
+    pub fov: f64,
+    /// How fast the turn keys turn, see `Player::turn_speed`.
+    pub turn_speed: f64,
+    pub forward_speed: f64,
+    pub strafe_speed: f64,
+    pub zoom_slows_movement: bool,
+    pub quality: Quality,
+    pub bindings: KeyBindings,
+    pub horizon_line: bool,
+    pub vignette: bool,
+    pub temporal_aa: bool,
+    pub minimap: bool,
+    pub compass: bool,
+    pub stats: bool,
+    pub distances: bool,
+}
+
+/// What a new `App` starts with for the classic key bindings.
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            fov: FRAC_PI_4,
+            turn_speed: 6.0,
+            forward_speed: 12.0,
+            strafe_speed: 12.0,
+            zoom_slows_movement: false,
+            quality: Quality::High,
+            bindings: KeyBindings::classic(),
+            horizon_line: false,
+            vignette: false,
+            temporal_aa: false,
+            minimap: true,
+            compass: true,
+            stats: false,
+            distances: false,
+        }
+    }
+}
+
+impl Settings {
+    /// The settings `app` has right now.
+    pub fn from_app(app: &App) -> Self {
+        Self {
+            fov: app.world.life.base_fov_angle,
+            turn_speed: app.world.seat.player.turn_speed,
+            forward_speed: app.world.seat.player.forward_speed,
+            strafe_speed: app.world.seat.player.strafe_speed,
+            zoom_slows_movement: app.world.zoom_slows_movement,
+            quality: app.world.life.quality,
+            bindings: app.bindings.clone(),
+            horizon_line: app.world.life.horizon_line,
+            vignette: app.vignette.is_some(),
+            temporal_aa: app.temporal_aa.is_some(),
+            minimap: app.layers.is_enabled(Layer::Minimap),
+            compass: app.layers.is_enabled(Layer::Compass),
+            stats: app.layers.is_enabled(Layer::Stats),
+            distances: app.layers.is_enabled(Layer::Distances),
+        }
+    }
+
+    /// Give `app` these settings, keeping a vignette or temporal AA that's already on as it is.
+    pub fn apply(&self, app: &mut App) {
+        let fov = self.fov.clamp(MIN_FOV_ANGLE, MAX_FOV_ANGLE);
+        // zooming keeps narrowing the view by as much as it did
+        let life = &mut app.world.life;
+        if life.base_fov_angle > 0.0 {
+            life.zoom_fov_angle *= fov / life.base_fov_angle;
+        }
+        app.world.life.base_fov_angle = fov;
+        app.world.life.fov_angle = fov;
+        app.world.seat.player.turn_speed = self.turn_speed;
+        app.world.seat.player.forward_speed = self.forward_speed;
+        app.world.seat.player.strafe_speed = self.strafe_speed;
+        app.world.zoom_slows_movement = self.zoom_slows_movement;
+        app.world.life.quality = self.quality;
+        app.bindings = self.bindings.clone();
+        app.world.life.horizon_line = self.horizon_line;
+        match (self.vignette, app.vignette.is_some()) {
+            (true, false) => app.vignette = Some(Vignette::default()),
+            (false, true) => app.vignette = None,
+            _ => {}
+        }
+        match (self.temporal_aa, app.temporal_aa.is_some()) {
+            (true, false) => app.temporal_aa = Some(TemporalAa::default()),
+            (false, true) => app.temporal_aa = None,
+            _ => {}
+        }
+        app.layers.set_enabled(Layer::Minimap, self.minimap);
+        app.layers.set_enabled(Layer::Compass, self.compass);
+        app.layers.set_enabled(Layer::Stats, self.stats);
+        app.layers.set_enabled(Layer::Distances, self.distances);
+        app.world.mark_dirty();
+    }
+
+    /// Change the settings `text` sets, returning the numbers of the lines skipped, from 1.
+    pub fn read(&mut self, text: &str) -> Vec<usize> {
+        let mut skipped = Vec::new();
+        let mut bindings: Option<KeyBindings> = None;
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with("//") {
+                continue;
+            }
+            let read = match line.split_once('=') {
+                Some((name, value)) => {
+                    let (name, value) = (name.trim(), value.trim());
+                    match name.strip_prefix("bind ") {
+                        Some(action) => read_binding(bindings.get_or_insert_with(KeyBindings::new), action.trim(), value),
+                        None => self.read_value(name, value),
+                    }
+                }
+                None => false,
+            };
+            if !read {
+                skipped.push(index + 1);
+            }
+        }
+        if let Some(bindings) = bindings {
+            self.bindings = bindings;
+        }
+        skipped
+    }
+
+    /// Set `name` from `value`, returning whether both made sense.
+    fn read_value(&mut self, name: &str, value: &str) -> bool {
+        let number = value.parse::<f64>().ok().filter(|number| number.is_finite());
+        let flag = value.parse::<bool>().ok();
+        let set_flag = |setting: &mut bool| flag.map(|flag| *setting = flag).is_some();
+        match name {
+            "fov" => number.map(|fov| self.fov = fov).is_some(),
+            "turn_speed" => number.map(|speed| self.turn_speed = speed).is_some(),
+            "forward_speed" => number.map(|speed| self.forward_speed = speed).is_some(),
+            "strafe_speed" => number.map(|speed| self.strafe_speed = speed).is_some(),
+            "quality" => match value {
+                "low" => {
+                    self.quality = Quality::Low;
+                    true
+                }
+                "high" => {
+                    self.quality = Quality::High;
+                    true
+                }
+                _ => false,
+            },
+            "zoom_slows_movement" => set_flag(&mut self.zoom_slows_movement),
+            "horizon_line" => set_flag(&mut self.horizon_line),
+            "vignette" => set_flag(&mut self.vignette),
+            "temporal_aa" => set_flag(&mut self.temporal_aa),
+            "minimap" => set_flag(&mut self.minimap),
+            "compass" => set_flag(&mut self.compass),
+            "stats" => set_flag(&mut self.stats),
+            "distances" => set_flag(&mut self.distances),
+            _ => false,
+        }
+    }
+
+    /// The settings as text `read` takes back, every one of them spelled out.
+    pub fn to_text(&self) -> String {
+        let mut text = String::from("// saved on closing the window, lines that can't be read are skipped\n");
+        let quality = match self.quality {
+            Quality::Low => "low",
+            Quality::High => "high",
+        };
+        // writing to a String can't fail
+        let _ = writeln!(text, "fov = {}", self.fov);
+        let _ = writeln!(text, "turn_speed = {}", self.turn_speed);
+        let _ = writeln!(text, "forward_speed = {}", self.forward_speed);
+        let _ = writeln!(text, "strafe_speed = {}", self.strafe_speed);
+        let _ = writeln!(text, "quality = {}", quality);
+        for (name, flag) in [
+            ("zoom_slows_movement", self.zoom_slows_movement),
+            ("horizon_line", self.horizon_line),
+            ("vignette", self.vignette),
+            ("temporal_aa", self.temporal_aa),
+            ("minimap", self.minimap),
+            ("compass", self.compass),
+            ("stats", self.stats),
+            ("distances", self.distances),
+        ] {
+            let _ = writeln!(text, "{} = {}", name, flag);
+        }
+        for action in Action::ALL {
+            for key in self.bindings.keys(action) {
+                let _ = writeln!(text, "bind {:?} = {:?}", action, key);
+            }
+            for scancode in self.bindings.scancodes(action) {
+                let _ = writeln!(text, "bind {:?} = #{:x}", action, scancode);
+            }
+        }
+        text
+    }
+}
+
+/// Add a `bind action = key` line's binding, returning whether the action and key are both known.
+fn read_binding(bindings: &mut KeyBindings, action: &str, key: &str) -> bool {
+    let Some(action) = Action::ALL.into_iter().find(|known| format!("{:?}", known) == action) else {
+        return false;
+    };
+    if let Some(hex) = key.strip_prefix('#') {
+        return match u32::from_str_radix(hex, 16) {
+            Ok(scancode) => {
+                bindings.bind_scancode(action, scancode);
+                true
+            }
+            Err(_) => false,
+        };
+    }
+    match key_from_name(key) {
+        Some(key) => {
+            bindings.bind(action, key);
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pixel_canvas::input::glutin::event::VirtualKeyCode;
+
+    #[test]
+    fn settings_survive_a_round_trip_through_text() {
+        let mut bindings = KeyBindings::new();
+        bindings.bind(Action::Forward, VirtualKeyCode::I);
+        bindings.bind_scancode(Action::Jump, 0x39);
+        let saved = Settings {
+            fov: 1.1,
+            turn_speed: 3.5,
+            strafe_speed: 7.0,
+            quality: Quality::Low,
+            bindings,
+            vignette: true,
+            minimap: false,
+            ..Settings::default()
+        };
+        let mut loaded = Settings::default();
+        let skipped = loaded.read(&saved.to_text());
+        assert!(skipped.is_empty(), "{:?}", skipped);
+        assert_eq!(loaded.to_text(), saved.to_text());
+        assert_eq!((loaded.fov, loaded.turn_speed, loaded.strafe_speed), (1.1, 3.5, 7.0));
+        assert_eq!(loaded.bindings.keys(Action::Forward).collect::<Vec<_>>(), vec![VirtualKeyCode::I]);
+    }
+
+    #[test]
+    fn unreadable_lines_keep_what_was_there() {
+        let mut settings = Settings::default();
+        let skipped = settings.read("fov = wide\nturn_speed = 2\nnonsense\nminimap = maybe");
+        assert_eq!(skipped, vec![1, 3, 4]);
+        assert_eq!((settings.fov, settings.turn_speed, settings.minimap), (FRAC_PI_4, 2.0, true));
+    }
+}
@@ -0,0 +1,96 @@
+use crate::player::Player;
+
+/// Default for `Shake::amplitude`, in radians of view angle.
+pub const SHAKE_AMPLITUDE: f64 = 0.03;
+
+/// Cap on `Shake::amplitude`, past which the view gets hard to follow.
+pub const MAX_SHAKE_AMPLITUDE: f64 = 0.1;
+
+/// Default for `Shake::decay`, per second.
+pub const SHAKE_DECAY: f64 = 12.0;
+
+const SHAKE_CUTOFF: f64 = 0.001;
+
+/// A short jolt of the view angle from bumping into things, dying away over a few frames.
+#[derive(Clone, Debug)]
+pub struct Shake {
+    /// Radians the view swings at the start of a shake.
+    pub amplitude: f64,
+    /// How quickly the shake dies away, per second.
+    pub decay: f64,
+    /// From 1.0 right after a kick down to 0.0 when settled.
+    strength: f64,
+    time: f64,
+}
+
+impl Default for Shake {
+    fn default() -> Self {
+        Self { amplitude: SHAKE_AMPLITUDE, decay: SHAKE_DECAY, strength: 0.0, time: 0.0 }
+    }
+}
+
+impl Shake {
+    pub fn kick(&mut self) {
+        self.strength = 1.0;
+        self.time = 0.0;
+    }
+
+    pub fn update(&mut self, dt: f64) {
+        if self.strength == 0.0 {
+            return;
+        }
+        self.time += dt;
+        self.strength *= (-self.decay * dt).exp();
+        if self.strength < SHAKE_CUTOFF {
+            self.strength = 0.0;
+        }
+    }
+
+    pub fn strength(&self) -> f64 {
+        self.strength
+    }
+
+    /// Swing `camera`'s view angle, never its position, which may be right up against a wall.
+    pub fn apply(&self, camera: &mut Player) {
+        if self.strength == 0.0 {
+            return;
+        }
+        let amplitude = self.amplitude.clamp(0.0, MAX_SHAKE_AMPLITUDE) * self.strength;
+        let t = self.time;
+        // two sines at unrelated rates stand in for noise
+        camera.vision_angle += amplitude * ((t * 71.0).sin() + (t * 53.0 + 1.7).sin()) / 2.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shake_decays_to_zero() {
+        let mut shake = Shake::default();
+        shake.kick();
+        let mut last = shake.strength();
+        for _ in 0..120 {
+            shake.update(1.0 / 60.0);
+            assert!(shake.strength() <= last);
+            last = shake.strength();
+        }
+        assert_eq!(shake.strength(), 0.0);
+
+        let (mut camera, still) = (Player::new(1.5, 1.5, 0.0), Player::new(1.5, 1.5, 0.0));
+        shake.apply(&mut camera);
+        assert_eq!(camera.vision_angle, still.vision_angle);
+    }
+
+    #[test]
+    fn shake_moves_only_the_angle() {
+        let mut shake = Shake::default();
+        shake.kick();
+        shake.update(0.01);
+        let mut camera = Player::new(1.5, 1.5, 0.0);
+        shake.apply(&mut camera);
+        assert_eq!((camera.player_x, camera.player_y), (1.5, 1.5));
+        assert!(camera.vision_angle != 0.0 && camera.vision_angle.abs() <= SHAKE_AMPLITUDE);
+    }
+}
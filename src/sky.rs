@@ -0,0 +1,105 @@
+use crate::texture::Texture;
+use pixel_canvas::Color;
+use std::f64::consts::TAU;
+
+/// A backdrop where the ceiling would be, wrapped around the player like a far off cylinder.
+pub struct Sky {
+    pub texture: Texture,
+    /// Times the texture goes around a full turn; keep it whole to avoid a seam.
+    pub repeats: f64,
+    /// Texture widths the sky slides per map cell the player moves sideways to the view, 0.0 for none.
+    pub parallax: f64,
+}
+
+impl Sky {
+    pub fn new(texture: Texture) -> Self {
+        Self { texture, repeats: 1.0, parallax: 0.0 }
+    }
+
+    /// A generated blue sky with a band of clouds that lines up at its left and right edges.
+    pub fn clouds(width: usize, height: usize) -> Self {
+        let pixels = (0..width * height)
+            .map(|i| {
+                let (x, y) = (i % width, i / width);
+                let across = x as f64 / width as f64 * TAU;
+                // 0 at the top of the texture, 1 at the horizon
+                let down = y as f64 / height as f64;
+
+                let zenith = Color { r: 20, g: 45, b: 110 };
+                let horizon = Color { r: 150, g: 180, b: 215 };
+                let sky = Color {
+                    r: (zenith.r as f64 + (horizon.r as f64 - zenith.r as f64) * down) as u8,
+                    g: (zenith.g as f64 + (horizon.g as f64 - zenith.g as f64) * down) as u8,
+                    b: (zenith.b as f64 + (horizon.b as f64 - zenith.b as f64) * down) as u8,
+                };
+
+                let cover = (3.0 * across).sin() * 0.5 + (7.0 * across + 1.3).sin() * 0.3 + (13.0 * across + 4.0).sin() * 0.2;
+                let band = 1.0 - ((down - 0.6) / 0.25).powi(2);
+                let cloud = (cover * band).clamp(0.0, 1.0);
+                Color {
+                    r: (sky.r as f64 + (235.0 - sky.r as f64) * cloud) as u8,
+                    g: (sky.g as f64 + (235.0 - sky.g as f64) * cloud) as u8,
+                    b: (sky.b as f64 + (240.0 - sky.b as f64) * cloud) as u8,
+                }
+            })
+            .collect();
+
+        Self::new(Texture::new(width, height, pixels))
+    }
+
+    /// Horizontal texture coordinate looking along `angle`, in `[0, 1)`, the same a full turn on.
+    pub fn offset(&self, angle: f64) -> f64 {
+        (angle / TAU * self.repeats).rem_euclid(1.0)
+    }
+
+    /// Shift of the texture coordinate looking along `angle` from `(x, y)`, by the position across the view.
+    pub fn parallax_offset(&self, angle: f64, x: f64, y: f64) -> f64 {
+        self.parallax * (x * angle.cos() - y * angle.sin())
+    }
+
+    /// The sky looking along `angle`, `v` from the top of the screen (0.0) to the horizon (1.0).
+    pub fn sample(&self, angle: f64, v: f64) -> Color {
+        self.sample_from(angle, v, 0.0, 0.0)
+    }
+
+    /// `sample` as seen from `(x, y)`, shifted by `parallax_offset`.
+    pub fn sample_from(&self, angle: f64, v: f64, x: f64, y: f64) -> Color {
+        let u = (self.offset(angle) + self.parallax_offset(angle, x, y)).rem_euclid(1.0);
+        self.texture.sample(0, u, v.clamp(0.0, 0.999))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn full_turn_wraps_offset_back() {
+        let mut sky = Sky::clouds(64, 16);
+        for repeats in [1.0, 3.0] {
+            sky.repeats = repeats;
+            for angle in [0.0, 0.7, -2.0, 5.5] {
+                assert!((sky.offset(angle + TAU) - sky.offset(angle)).abs() < 1e-9);
+                assert!((sky.offset(angle - TAU) - sky.offset(angle)).abs() < 1e-9);
+            }
+        }
+        sky.repeats = 1.0;
+        // either side of the seam lands at either end of the texture
+        assert!(sky.offset(-1e-6) > 0.999);
+        assert!(sky.offset(1e-6) < 0.001);
+    }
+
+    #[test]
+    fn sideways_move_shifts_the_sample_by_the_parallax() {
+        let mut sky = Sky::clouds(64, 16);
+        sky.parallax = 0.02;
+        let angle: f64 = 0.6;
+        let (sideways_x, sideways_y) = (angle.cos(), -angle.sin());
+        let from = sky.parallax_offset(angle, 3.0, 4.0);
+        let moved = sky.parallax_offset(angle, 3.0 + 2.0 * sideways_x, 4.0 + 2.0 * sideways_y);
+        assert!((moved - from - 0.04).abs() < 1e-9);
+        // walking along the view leaves it where it is
+        let ahead = sky.parallax_offset(angle, 3.0 + angle.sin(), 4.0 + angle.cos());
+        assert!((ahead - from).abs() < 1e-9);
+    }
+}
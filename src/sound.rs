@@ -0,0 +1,92 @@
+use crate::player::Player;
+
+/// Distance walked between two footsteps, in map cells.
+pub const FOOTSTEP_STRIDE: f64 = 0.8;
+
+/// A noise handed to the `World::on_sound` callback; the engine plays nothing itself.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SoundEvent {
+    pub name: &'static str,
+    /// Where it happened, in map coordinates.
+    pub x: f64,
+    pub y: f64,
+    /// In map cells.
+    pub distance: f64,
+    /// From 1.0 right next to the player down towards 0.0 far away, see `attenuation`.
+    pub volume: f64,
+    /// From -1.0 fully left to 1.0 fully right, see `pan`.
+    pub pan: f64,
+}
+
+impl SoundEvent {
+    /// A sound made at `(x, y)` as heard by `listener`.
+    pub fn heard_by(name: &'static str, x: f64, y: f64, listener: &Player) -> Self {
+        let distance = (x - listener.player_x).hypot(y - listener.player_y);
+        Self { name, x, y, distance, volume: attenuation(distance), pan: pan(listener, x, y) }
+    }
+}
+
+/// Distance within which sounds are heard at full volume, in map cells.
+pub const SOUND_FULL_VOLUME_DISTANCE: f64 = 1.0;
+
+/// Full volume within `SOUND_FULL_VOLUME_DISTANCE`, then half as loud at twice as far.
+pub fn attenuation(distance: f64) -> f64 {
+    SOUND_FULL_VOLUME_DISTANCE / distance.max(SOUND_FULL_VOLUME_DISTANCE)
+}
+
+/// Left to right balance of a sound at `(x, y)` for `listener`, 0.0 ahead, behind or underfoot.
+pub fn pan(listener: &Player, x: f64, y: f64) -> f64 {
+    let (dx, dy) = (x - listener.player_x, y - listener.player_y);
+    if dx == 0.0 && dy == 0.0 {
+        return 0.0;
+    }
+    // headings are measured from +y towards +x, the same as `vision_angle`
+    (dx.atan2(dy) - listener.vision_angle).sin()
+}
+
+/// Receives every `SoundEvent` as it happens.
+pub type SoundHook = Box<dyn FnMut(&SoundEvent)>;
+
+/// Counts steps from the distance walked, so footsteps keep pace with the player.
+pub struct Footsteps {
+    pub stride: f64,
+    walked: f64,
+}
+
+impl Footsteps {
+    pub fn new(stride: f64) -> Self {
+        Self { stride, walked: 0.0 }
+    }
+
+    /// Add `distance` walked and return how many footsteps it completed.
+    pub fn advance(&mut self, distance: f64) -> u32 {
+        self.walked += distance;
+        let steps = (self.walked / self.stride).floor();
+        self.walked -= steps * self.stride;
+        steps as u32
+    }
+}
+
+impl Default for Footsteps {
+    fn default() -> Self {
+        Self::new(FOOTSTEP_STRIDE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sound_to_the_right_pans_right() {
+        let player = Player::new(5.0, 5.0, 0.7);
+        let (dx, dy) = player.movement(0.0, 3.0);
+        let event = SoundEvent::heard_by("door", 5.0 + dx, 5.0 + dy, &player);
+        assert!((event.pan - 1.0).abs() < 1e-9);
+        assert!((event.volume - 1.0 / 3.0).abs() < 1e-9);
+
+        let (dx, dy) = player.movement(2.0, 1.0);
+        assert!(pan(&player, 5.0 + dx, 5.0 + dy) > 0.0);
+        assert!(pan(&player, 5.0 - dx, 5.0 - dy) < 0.0);
+    }
+}
@@ -0,0 +1,213 @@
+use crate::life::Life;
+use crate::map::Map;
+use crate::player::Player;
+use crate::ray::RayHit;
+use crate::render::{horizon_row, wall_band};
+use pixel_canvas::Color;
+use std::f64::consts::{PI, TAU};
+
+/// A flat picture standing in the map, facing the player, drawn as a blob filling its ellipse.
+#[derive(Clone, Copy)]
+pub struct Sprite {
+    pub x: f64,
+    pub y: f64,
+    /// Size as a fraction of a map cell, 1.0 being as tall as a wall.
+    pub width: f64,
+    pub height: f64,
+    pub color: Color,
+    /// The player bumps into solid sprites instead of walking through them.
+    pub solid: bool,
+}
+
+impl Sprite {
+    pub fn new(x: f64, y: f64, width: f64, height: f64, color: Color) -> Self {
+        Self { x, y, width, height, color, solid: false }
+    }
+}
+
+/// Fraction of the move `(dx, dy)` that fits before touching a solid sprite; moves away are never held back.
+pub fn solid_contact(sprites: &[Sprite], x: f64, y: f64, dx: f64, dy: f64) -> f64 {
+    sprites
+        .iter()
+        .filter(|sprite| sprite.solid)
+        .map(|sprite| {
+            let radius = sprite.width / 2.0;
+            let (from_x, from_y) = (x - sprite.x, y - sprite.y);
+            // solve |from + t * move| = radius for the first t
+            let a = dx * dx + dy * dy;
+            let b = 2.0 * (from_x * dx + from_y * dy);
+            let c = from_x * from_x + from_y * from_y - radius * radius;
+            if a == 0.0 || b >= 0.0 {
+                return 1.0;
+            }
+            if c <= 0.0 {
+                return 0.0;
+            }
+            let discriminant = b * b - 4.0 * a * c;
+            if discriminant < 0.0 {
+                return 1.0;
+            }
+            ((-b - discriminant.sqrt()) / (2.0 * a)).clamp(0.0, 1.0)
+        })
+        .fold(1.0, f64::min)
+}
+
+/// The nearest sprite, as a circle, a ray passes through within `max_distance`: its index and distance.
+pub fn pick_sprite(sprites: &[Sprite], x: f64, y: f64, angle: f64, max_distance: f64) -> Option<(usize, f64)> {
+    let (unit_ray_x, unit_ray_y) = (angle.sin(), angle.cos());
+
+    sprites
+        .iter()
+        .enumerate()
+        .filter_map(|(index, sprite)| {
+            let radius = sprite.width / 2.0;
+            let (to_x, to_y) = (sprite.x - x, sprite.y - y);
+            // closest approach along the ray, and how far from the center it passes
+            let along = to_x * unit_ray_x + to_y * unit_ray_y;
+            let miss_squared = to_x * to_x + to_y * to_y - along * along;
+            if miss_squared > radius * radius {
+                return None;
+            }
+            let half_chord = (radius * radius - miss_squared).sqrt();
+            if along + half_chord < 0.0 {
+                return None;
+            }
+            // starting inside the circle counts as touching it
+            let distance = (along - half_chord).max(0.0);
+            (distance <= max_distance).then_some((index, distance))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+}
+
+/// Where a sprite lands on screen, rows from the bottom, exclusive on the right and top and unclipped.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpriteRect {
+    pub left: i32,
+    pub right: i32,
+    pub bottom: i32,
+    pub top: i32,
+    pub distance: f64,
+}
+
+/// Project `sprite` scaled like walls with its bottom on the floor line, `None` behind the player.
+pub fn project_sprite(sprite: &Sprite, player: &Player, life: &Life, width: usize, height: usize) -> Option<SpriteRect> {
+    let dx = sprite.x - player.player_x;
+    let dy = sprite.y - player.player_y;
+    let distance = dx.hypot(dy);
+
+    // angle from the middle of the screen, same convention as the player's heading
+    let angle = (dx.atan2(dy) - player.vision_angle + PI).rem_euclid(TAU) - PI;
+    if angle.abs() >= PI / 2.0 || distance < 1e-3 {
+        return None;
+    }
+
+    // columns are spread evenly over the FOV angle
+    let center = (angle / life.fov_angle + 0.5) * width as f64;
+    let half_width = sprite.width / distance / life.fov_angle * width as f64 / 2.0;
+
+    let horizon = horizon_row(height, player.pitch, life.max_pitch, player.eye_height);
+    let (floor_line, ceiling_line) = wall_band(height, horizon, distance, player.eye_height);
+    let sprite_height = (ceiling_line - floor_line) * sprite.height;
+
+    Some(SpriteRect {
+        left: (center - half_width).round() as i32,
+        right: (center + half_width).round() as i32,
+        bottom: floor_line.round() as i32,
+        top: (floor_line + sprite_height).round() as i32,
+        distance,
+    })
+}
+
+/// Draw the nearest `life.max_sprites` sprites far to near, hidden behind walls nearer than them in `depth`.
+pub fn draw_sprites(buffer: &mut [Color], width: usize, height: usize, map: &Map, player: &Player, life: &Life, depth: &[RayHit]) {
+    sprite_pixels(width, height, map, player, life, depth, |index, color, _| buffer[index] = color);
+}
+
+/// `draw_sprites`, handing each pixel's index in the buffer, color and sprite distance to `put`.
+pub fn sprite_pixels(width: usize, height: usize, map: &Map, player: &Player, life: &Life, depth: &[RayHit], mut put: impl FnMut(usize, Color, f64)) {
+    let mut visible: Vec<(&Sprite, SpriteRect)> = map
+        .sprites
+        .iter()
+        .filter_map(|sprite| Some((sprite, project_sprite(sprite, player, life, width, height)?)))
+        .filter(|(_, rect)| rect.right > 0 && rect.left < width as i32)
+        .collect();
+    visible.sort_by(|(_, a), (_, b)| b.distance.total_cmp(&a.distance));
+    // only the nearest `max_sprites`, which come last
+    let skipped = visible.len().saturating_sub(life.max_sprites);
+
+    for (sprite, rect) in visible.into_iter().skip(skipped) {
+        let shade = ((-13.4375 * rect.distance + 235.0) / 235.0).clamp(0.0, 1.0);
+        let rect_width = (rect.right - rect.left).max(1) as f64;
+        let rect_height = (rect.top - rect.bottom).max(1) as f64;
+
+        for x in rect.left.max(0)..rect.right.min(width as i32) {
+            if depth[x as usize].direct_distance < rect.distance {
+                continue;
+            }
+            let u = (x - rect.left) as f64 / rect_width * 2.0 - 1.0;
+
+            for y in rect.bottom.max(0)..rect.top.min(height as i32) {
+                let v = (y - rect.bottom) as f64 / rect_height * 2.0 - 1.0;
+                if u * u + v * v > 1.0 {
+                    continue;
+                }
+                // a little darker towards the edges so it looks round
+                let edge = 1.0 - 0.4 * u * u;
+                let color = Color {
+                    r: (sprite.color.r as f64 * shade * edge) as u8,
+                    g: (sprite.color.g as f64 * shade * edge) as u8,
+                    b: (sprite.color.b as f64 * shade * edge) as u8,
+                };
+                put(y as usize * width + x as usize, color, rect.distance);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::FRAC_PI_4;
+
+    #[test]
+    fn sprite_stands_on_the_floor_line() {
+        let sprite = Sprite::new(5.0, 9.0, 0.5, 0.6, Color { r: 255, g: 255, b: 255 });
+        let player = Player::new(5.0, 5.0, 0.0);
+        let life = Life::new(FRAC_PI_4, 16.0);
+        let rect = project_sprite(&sprite, &player, &life, 200, 200).unwrap();
+        // four cells away a wall is 100 rows tall, from row 50 to 150
+        assert_eq!(rect, SpriteRect { left: 84, right: 116, bottom: 50, top: 110, distance: 4.0 });
+    }
+
+    #[test]
+    fn sprite_behind_the_player_is_not_projected() {
+        let sprite = Sprite::new(5.0, 1.0, 0.5, 0.6, Color { r: 255, g: 255, b: 255 });
+        let player = Player::new(5.0, 5.0, 0.0);
+        assert_eq!(project_sprite(&sprite, &player, &Life::new(FRAC_PI_4, 16.0), 200, 200), None);
+    }
+
+    #[test]
+    fn low_cap_draws_only_the_nearest() {
+        let mut rows = vec![format!("#{}#", ".".repeat(19)); 12];
+        rows.insert(0, "#".repeat(21));
+        rows.push("#".repeat(21));
+        let mut map = Map::parse(&rows.join("\n")).unwrap();
+        map.sprites = vec![
+            Sprite::new(11.5, 10.5, 0.5, 0.6, Color { r: 0, g: 0, b: 255 }),
+            Sprite::new(9.5, 6.5, 0.5, 0.6, Color { r: 255, g: 0, b: 0 }),
+            Sprite::new(10.5, 8.5, 0.5, 0.6, Color { r: 0, g: 255, b: 0 }),
+        ];
+        let player = Player::new(10.5, 2.5, 0.0);
+        let mut life = Life::new(1.5, 16.0);
+        life.max_sprites = 2;
+        let (width, height) = (200, 100);
+        let depth = crate::render::cast_columns(&map, &player, &life, width);
+        let mut buffer = vec![Color { r: 0, g: 0, b: 0 }; width * height];
+        draw_sprites(&mut buffer, width, height, &map, &player, &life, &depth);
+
+        let drawn = |pick: fn(&Color) -> bool| buffer.iter().any(pick);
+        assert!(drawn(|c| c.r > 0 && c.g == 0 && c.b == 0));
+        assert!(drawn(|c| c.g > 0 && c.r == 0 && c.b == 0));
+        assert!(!drawn(|c| c.b > 0));
+    }
+}
@@ -0,0 +1,38 @@
+use std::collections::HashMap;
+
+/// Named whole numbers for game logic that triggers read and change; unset names read as 0.
+#[derive(Clone, Debug, Default)]
+pub struct StateBag {
+    values: HashMap<String, i64>,
+}
+
+impl StateBag {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The value of `name`, 0 if it was never set.
+    pub fn get(&self, name: &str) -> i64 {
+        self.values.get(name).copied().unwrap_or(0)
+    }
+
+    pub fn set(&mut self, name: &str, value: i64) {
+        self.values.insert(name.to_string(), value);
+    }
+
+    /// Add `amount` to `name`, which may be negative to take away.
+    pub fn add(&mut self, name: &str, amount: i64) {
+        let value = self.get(name).saturating_add(amount);
+        self.set(name, value);
+    }
+
+    /// Whether `name` is set to anything but 0, for names used as flags.
+    pub fn has(&self, name: &str) -> bool {
+        self.get(name) != 0
+    }
+
+    /// Forget `name`, so it reads as 0 again.
+    pub fn remove(&mut self, name: &str) {
+        self.values.remove(name);
+    }
+}
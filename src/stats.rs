@@ -0,0 +1,101 @@
+use crate::ray::RayHit;
+use std::ops::Add;
+
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct FrameStats {
+    pub rays: usize,
+    /// Samples taken along all the rays, what the caster actually pays for.
+    pub steps: u64,
+    pub cells: u64,
+}
+
+impl FrameStats {
+    pub fn from_hits(hits: &[RayHit]) -> Self {
+        hits.iter().fold(Self::default(), |mut stats, hit| {
+            stats.rays += 1;
+            stats.steps += hit.steps as u64;
+            stats.cells += hit.cells as u64;
+            stats
+        })
+    }
+
+    /// Average cells passed per ray, 0.0 if there were none.
+    pub fn cells_per_ray(&self) -> f64 {
+        if self.rays == 0 { 0.0 } else { self.cells as f64 / self.rays as f64 }
+    }
+}
+
+impl Add for FrameStats {
+    type Output = Self;
+
+    fn add(self, other: FrameStats) -> Self {
+        Self { rays: self.rays + other.rays, steps: self.steps + other.steps, cells: self.cells + other.cells }
+    }
+}
+
+/// Frame times and ray counts over a whole run, for comparing builds.
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+pub struct SessionStats {
+    pub frames: u64,
+    pub total_time: f64,
+    /// In seconds, like `max_frame_time`.
+    pub min_frame_time: f64,
+    pub max_frame_time: f64,
+    pub totals: FrameStats,
+}
+
+impl SessionStats {
+    pub fn record(&mut self, frame_time: f64, stats: FrameStats) {
+        if self.frames == 0 {
+            self.min_frame_time = frame_time;
+            self.max_frame_time = frame_time;
+        } else {
+            self.min_frame_time = self.min_frame_time.min(frame_time);
+            self.max_frame_time = self.max_frame_time.max(frame_time);
+        }
+        self.frames += 1;
+        self.total_time += frame_time;
+        self.totals = self.totals + stats;
+    }
+
+    /// `None` before any frame or time has passed.
+    pub fn average_fps(&self) -> Option<f64> {
+        (self.frames > 0 && self.total_time > 0.0).then(|| self.frames as f64 / self.total_time)
+    }
+
+    /// `name value` lines with times in milliseconds; just the frame count with no frames.
+    pub fn report(&self) -> String {
+        let mut report = format!("frames {}\n", self.frames);
+        if self.frames == 0 {
+            return report;
+        }
+        if let Some(fps) = self.average_fps() {
+            report += &format!("average_fps {:.2}\n", fps);
+        }
+        report += &format!("min_frame_ms {:.3}\n", self.min_frame_time * 1000.0);
+        report += &format!("max_frame_ms {:.3}\n", self.max_frame_time * 1000.0);
+        report += &format!("rays_per_frame {:.1}\n", self.totals.rays as f64 / self.frames as f64);
+        report += &format!("steps_per_frame {:.1}\n", self.totals.steps as f64 / self.frames as f64);
+        report += &format!("cells_per_frame {:.1}\n", self.totals.cells as f64 / self.frames as f64);
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_lists_the_session() {
+        assert_eq!(SessionStats::default().report(), "frames 0\n");
+
+        let mut session = SessionStats::default();
+        session.record(0.02, FrameStats { rays: 100, steps: 900, cells: 300 });
+        session.record(0.03, FrameStats { rays: 100, steps: 1100, cells: 500 });
+        assert_eq!(
+            session.report(),
+            "frames 2\naverage_fps 40.00\nmin_frame_ms 20.000\nmax_frame_ms 30.000\n\
+             rays_per_frame 100.0\nsteps_per_frame 1000.0\ncells_per_frame 400.0\n"
+        );
+    }
+}
@@ -0,0 +1,276 @@
+/*
+    A map too big to keep in memory as a whole `Map` can be read from its file
+    a square chunk at a time as the player gets near. The file is the same
+    text as `Map::parse` takes, but with every row the same width and nothing
+    before it, so where a cell is in the file can be worked out from its
+    coordinates and only the rows of a chunk need reading:
+
+        ################
+        #......#.......#
+        #..............#
+        ################
+
+    Chunks stay in a cache of limited size, the one used longest ago making
+    room for a new one. A `Map` opened with `Map::open_streamed` looks its
+    cells up here, loading a chunk that isn't cached first, so rays,
+    collision and everything else that asks the map work on it unchanged.
+    `World` tells it where the player is each step, so the chunks around
+    them are loaded before they're looked at.
+ */
+
+use crate::map::Map;
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// Default for `StreamedMap::chunk_size`, in cells on a side.
+pub const CHUNK_SIZE: u16 = 32;
+
+/// Default for how many chunks `StreamedMap` keeps loaded.
+pub const CHUNK_CACHE_SIZE: usize = 16;
+
+/// What cells of a chunk that couldn't be read count as, see `StreamedMap::take_error`.
+pub const UNREADABLE_GLYPH: char = '#';
+
+/// Chunks by their chunk coordinates, with the order they were last used in, least recently first.
+struct ChunkCache {
+    chunks: HashMap<(u16, u16), Vec<char>>,
+    order: VecDeque<(u16, u16)>,
+    error: Option<io::Error>,
+    current: Option<(u16, u16)>,
+}
+
+/// The cells of a map read from its file a chunk at a time, see the notes at the top of this file.
+pub struct StreamedMap {
+    file: RefCell<File>,
+    pub width: u16,
+    pub height: u16,
+    /// Chunks along the right and bottom edges are cut short by the edge of the map.
+    pub chunk_size: u16,
+    /// Most chunks kept loaded at once.
+    pub capacity: usize,
+    /// Bytes from the start of a row to the start of the next, line ending included.
+    row_stride: u64,
+    cache: RefCell<ChunkCache>,
+    /// Cells changed since opening, such as doors opened, kept over evictions.
+    changed: HashMap<(u16, u16), char>,
+}
+
+impl StreamedMap {
+    /// Open the map at `path`, reading only enough to know its size; rows must be ASCII and as wide as the first.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+
+        let mut file = File::open(path)?;
+        let mut first_row = Vec::new();
+        BufReader::new(&mut file).read_until(b'\n', &mut first_row)?;
+        let ending = if first_row.ends_with(b"\r\n") { 2 } else if first_row.ends_with(b"\n") { 1 } else { 0 };
+        let width = first_row.len() - ending;
+        if width == 0 {
+            return Err(invalid("map has no rows"));
+        }
+        if !first_row.is_ascii() {
+            return Err(invalid("streamed maps must be ASCII"));
+        }
+
+        // the last row may or may not have a line ending
+        let row_stride = (width + ending) as u64;
+        let length = file.metadata()?.len();
+        let height = length.div_ceil(row_stride);
+        let (width, height) = match (u16::try_from(width), u16::try_from(height)) {
+            (Ok(width), Ok(height)) => (width, height),
+            _ => return Err(invalid("map is too large")),
+        };
+
+        Ok(Self {
+            file: RefCell::new(file),
+            width,
+            height,
+            chunk_size: CHUNK_SIZE,
+            capacity: CHUNK_CACHE_SIZE,
+            row_stride,
+            cache: RefCell::new(ChunkCache {
+                chunks: HashMap::new(),
+                order: VecDeque::new(),
+                error: None,
+                current: None,
+            }),
+            changed: HashMap::new(),
+        })
+    }
+
+    /// Chunk coordinates of the chunk holding cell `(x, y)`.
+    pub fn chunk_of(&self, x: u16, y: u16) -> (u16, u16) {
+        (x / self.chunk_size, y / self.chunk_size)
+    }
+
+    /// Whether the chunk at chunk coordinates `chunk` is loaded.
+    pub fn is_resident(&self, chunk: (u16, u16)) -> bool {
+        self.cache.borrow().chunks.contains_key(&chunk)
+    }
+
+    /// Chunk coordinates of every loaded chunk, least recently used first.
+    pub fn resident_chunks(&self) -> Vec<(u16, u16)> {
+        self.cache.borrow().order.iter().copied().collect()
+    }
+
+    /// The first error reading a chunk since the last call, if any.
+    pub fn take_error(&self) -> Option<io::Error> {
+        self.cache.borrow_mut().error.take()
+    }
+
+    /// Load the chunk at `(x, y)` and the eight around it on first entering it, returning whether it did.
+    pub fn enter(&self, x: f64, y: f64) -> bool {
+        if x < 0.0 || y < 0.0 || self.out_of_bounds(x as u16, y as u16) {
+            return false;
+        }
+        let (chunk_x, chunk_y) = self.chunk_of(x as u16, y as u16);
+        if self.cache.borrow().current == Some((chunk_x, chunk_y)) {
+            return false;
+        }
+        self.cache.borrow_mut().current = Some((chunk_x, chunk_y));
+
+        // the player's own chunk last, so it's the last to be evicted
+        let chunks_x = self.width.div_ceil(self.chunk_size);
+        let chunks_y = self.height.div_ceil(self.chunk_size);
+        for neighbour_y in chunk_y.saturating_sub(1)..(chunk_y + 2).min(chunks_y) {
+            for neighbour_x in chunk_x.saturating_sub(1)..(chunk_x + 2).min(chunks_x) {
+                if (neighbour_x, neighbour_y) != (chunk_x, chunk_y) {
+                    self.touch((neighbour_x, neighbour_y));
+                }
+            }
+        }
+        self.touch((chunk_x, chunk_y));
+        true
+    }
+
+    pub fn glyph(&self, x: u16, y: u16) -> char {
+        if let Some(&glyph) = self.changed.get(&(x, y)) {
+            return glyph;
+        }
+        let chunk = self.chunk_of(x, y);
+        self.touch(chunk);
+        let (chunk_width, _) = self.chunk_extent(chunk);
+        let (local_x, local_y) = (x - chunk.0 * self.chunk_size, y - chunk.1 * self.chunk_size);
+        self.cache.borrow().chunks[&chunk][(local_y * chunk_width + local_x) as usize]
+    }
+
+    pub fn set_glyph(&mut self, x: u16, y: u16, glyph: char) {
+        self.changed.insert((x, y), glyph);
+    }
+
+    fn out_of_bounds(&self, x: u16, y: u16) -> bool {
+        x >= self.width || y >= self.height
+    }
+
+    /// Width and height in cells of the chunk at chunk coordinates `chunk`.
+    fn chunk_extent(&self, (chunk_x, chunk_y): (u16, u16)) -> (u16, u16) {
+        let left = chunk_x * self.chunk_size;
+        let top = chunk_y * self.chunk_size;
+        (self.chunk_size.min(self.width - left), self.chunk_size.min(self.height - top))
+    }
+
+    /// Make `chunk` the most recently used, loading it and evicting the least
+    /// recently used one if it isn't loaded yet.
+    fn touch(&self, chunk: (u16, u16)) {
+        let mut cache = self.cache.borrow_mut();
+        if cache.order.back() == Some(&chunk) {
+            return;
+        }
+        if let Some(position) = cache.order.iter().position(|&loaded| loaded == chunk) {
+            cache.order.remove(position);
+            cache.order.push_back(chunk);
+            return;
+        }
+
+        let cells = match self.read_chunk(chunk) {
+            Ok(cells) => cells,
+            Err(error) => {
+                cache.error.get_or_insert(error);
+                let (chunk_width, chunk_height) = self.chunk_extent(chunk);
+                vec![UNREADABLE_GLYPH; chunk_width as usize * chunk_height as usize]
+            }
+        };
+        while cache.order.len() >= self.capacity.max(1) {
+            if let Some(evicted) = cache.order.pop_front() {
+                cache.chunks.remove(&evicted);
+            }
+        }
+        cache.chunks.insert(chunk, cells);
+        cache.order.push_back(chunk);
+    }
+
+    /// The cells of `chunk` from the file, row by row.
+    fn read_chunk(&self, chunk: (u16, u16)) -> io::Result<Vec<char>> {
+        let (chunk_width, chunk_height) = self.chunk_extent(chunk);
+        let (left, top) = (chunk.0 * self.chunk_size, chunk.1 * self.chunk_size);
+        let mut file = self.file.borrow_mut();
+
+        let mut cells = Vec::with_capacity(chunk_width as usize * chunk_height as usize);
+        let mut row = vec![0; chunk_width as usize];
+        for y in top..top + chunk_height {
+            file.seek(SeekFrom::Start(y as u64 * self.row_stride + left as u64))?;
+            file.read_exact(&mut row)?;
+            if !row.is_ascii() {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "streamed maps must be ASCII"));
+            }
+            cells.extend(row.iter().map(|&byte| byte as char));
+        }
+        Ok(cells)
+    }
+}
+
+impl Map {
+    /// A map whose cells are read from the file at `path` as needed, see the notes at the top of `stream.rs`.
+    pub fn open_streamed(path: impl AsRef<Path>) -> io::Result<Self> {
+        let stream = StreamedMap::open(path)?;
+        let mut map = Map::from_layout(stream.height, stream.width, Vec::new());
+        map.stream = Some(stream);
+        Ok(map)
+    }
+
+    /// The chunk cache of a map opened with `open_streamed`.
+    pub fn stream(&self) -> Option<&StreamedMap> {
+        self.stream.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::InputState;
+    use crate::life::Life;
+    use crate::player::Player;
+    use crate::world::World;
+    use std::f64::consts::{FRAC_PI_2, FRAC_PI_4};
+
+    #[test]
+    fn crossing_a_chunk_boundary_loads_the_next_chunk() {
+        // a corridor along row 2 across three chunks, closed off at x = 40
+        let rows: Vec<String> = (0..5)
+            .map(|y| (0..100).map(|x| if y == 2 && x > 0 && x < 99 && x != 40 { '.' } else { '#' }).collect())
+            .collect();
+        let path = std::env::temp_dir().join(format!("stream-test-{}.txt", std::process::id()));
+        std::fs::write(&path, rows.join("\n")).unwrap();
+        let map = Map::open_streamed(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!((map.width, map.height), (100, 5));
+
+        let mut world = World::new(map, Player::new(30.5, 2.5, FRAC_PI_2), Life::new(FRAC_PI_4, 16.0));
+        world.step(&InputState::default(), 1.0 / 60.0);
+        let stream = world.map.stream().unwrap();
+        assert!(stream.is_resident((1, 0)));
+        assert!(!stream.is_resident((2, 0)));
+
+        let walk = InputState { walk: 1.0, ..InputState::default() };
+        for _ in 0..100 {
+            world.step(&walk, 1.0 / 60.0);
+        }
+        assert!(world.map.stream().unwrap().is_resident((2, 0)));
+        // the wall in the second chunk stops the player like any other
+        assert!(world.seat.player.player_x > 39.0 && world.seat.player.player_x < 40.0);
+        assert!(world.map.is_wall(40.5, 2.5) && !world.map.is_wall(39.5, 2.5));
+    }
+}
@@ -0,0 +1,136 @@
+/*
+    Stress testing drives a world with made up input instead of a keyboard:
+    walking, turning, strafing and looking by random amounts that change every
+    few frames, zooming and jumping now and then, and drawing every frame.
+    The input comes from a seed, so a run that goes wrong can be replayed
+    exactly by running it again with the same seed.
+
+    Only built with the `stress` feature, see `examples/stress.rs`.
+ */
+
+use crate::bindings::Action;
+use crate::decorate::SplitMix64;
+use crate::input::InputState;
+use crate::render::Renderer;
+use crate::world::World;
+use pixel_canvas::Color;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+
+/// Frame size `run_stress` draws at, small since drawing is only there to catch panics.
+pub const STRESS_FRAME_WIDTH: usize = 64;
+pub const STRESS_FRAME_HEIGHT: usize = 48;
+
+/// Longest a stress run holds the same input, in frames.
+const MAX_INPUT_HOLD: u64 = 30;
+
+/// What went wrong in a stress run, on which frame counted from 0.
+#[derive(Clone, Debug, PartialEq)]
+pub enum StressFailure {
+    Panic { frame: usize, message: String },
+    /// The player position, angle or pitch is not a number.
+    NotFinite { frame: usize, x: f64, y: f64, angle: f64 },
+    OutOfBounds { frame: usize, x: f64, y: f64 },
+}
+
+impl fmt::Display for StressFailure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StressFailure::Panic { frame, message } => write!(f, "frame {}: panicked: {}", frame, message),
+            StressFailure::NotFinite { frame, x, y, angle } => {
+                write!(f, "frame {}: player at ({}, {}) facing {} isn't a number", frame, x, y, angle)
+            }
+            StressFailure::OutOfBounds { frame, x, y } => {
+                write!(f, "frame {}: player at ({:.3}, {:.3}) is off the map", frame, x, y)
+            }
+        }
+    }
+}
+
+impl std::error::Error for StressFailure {}
+
+/// Step and draw `world` for `frames` frames on random input from `seed`, stopping at the first failure.
+pub fn run_stress(world: &mut World, seed: u64, frames: usize, dt: f64) -> Result<(), StressFailure> {
+    let mut rng = SplitMix64(seed);
+    let mut buffer = vec![Color { r: 0, g: 0, b: 0 }; STRESS_FRAME_WIDTH * STRESS_FRAME_HEIGHT];
+    let mut input = InputState::default();
+    let mut hold = 0;
+
+    for frame in 0..frames {
+        if hold == 0 {
+            input = random_input(&mut rng);
+            hold = 1 + rng.next_u64() % MAX_INPUT_HOLD;
+        }
+        hold -= 1;
+        if rng.next_f64() < 0.02 {
+            world.seat.actions.press(Action::Jump);
+        }
+
+        let stepped = panic::catch_unwind(AssertUnwindSafe(|| {
+            world.step(&input, dt);
+            Renderer::FirstPerson.render(
+                &mut buffer,
+                STRESS_FRAME_WIDTH,
+                STRESS_FRAME_HEIGHT,
+                &world.map,
+                &world.camera(1.0),
+                &world.life,
+            );
+        }));
+        if let Err(payload) = stepped {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string());
+            return Err(StressFailure::Panic { frame, message });
+        }
+
+        let player = &world.seat.player;
+        let (x, y, angle) = (player.player_x, player.player_y, player.vision_angle);
+        if !(x.is_finite() && y.is_finite() && angle.is_finite() && player.pitch.is_finite()) {
+            return Err(StressFailure::NotFinite { frame, x, y, angle });
+        }
+        if x < 0.0 || y < 0.0 || x >= world.map.width as f64 || y >= world.map.height as f64 {
+            return Err(StressFailure::OutOfBounds { frame, x, y });
+        }
+    }
+    Ok(())
+}
+
+/// Input held for a stretch: each axis often still, sometimes full, else anywhere between.
+fn random_input(rng: &mut SplitMix64) -> InputState {
+    let mut axis = || match rng.next_u64() % 4 {
+        0 => 0.0,
+        1 => 1.0,
+        2 => -1.0,
+        _ => rng.range(-1.0, 1.0),
+    };
+    let (walk, turn, strafe, look) = (axis(), axis(), axis(), axis());
+    InputState { walk, turn, strafe, look, mouse_turn: 0.0, zoom: rng.next_f64() < 0.1, crouch: rng.next_f64() < 0.2 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::WorldBuilder;
+    use crate::maps::{demo_map, DEMO_MAPS};
+
+    #[test]
+    fn demo_maps_survive_random_input() {
+        for index in 0..DEMO_MAPS.len() {
+            let mut world = WorldBuilder::new().map(demo_map(index)).build().unwrap();
+            assert_eq!(run_stress(&mut world, 7, 3000, 1.0 / 60.0), Ok(()), "map {}", index);
+        }
+    }
+
+    #[test]
+    fn same_seed_replays_the_same_run() {
+        let run = || {
+            let mut world = WorldBuilder::new().map(demo_map(0)).build().unwrap();
+            run_stress(&mut world, 3, 500, 1.0 / 60.0).unwrap();
+            (world.seat.player.player_x, world.seat.player.player_y, world.seat.player.vision_angle)
+        };
+        assert_eq!(run(), run());
+    }
+}
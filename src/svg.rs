@@ -0,0 +1,117 @@
+use crate::life::Life;
+use crate::map::Map;
+use crate::player::Player;
+use crate::render::wall_color;
+use pixel_canvas::Color;
+use std::fmt::Write as _;
+use std::io;
+use std::path::Path;
+
+/// Size of one map cell in SVG units.
+const CELL: f64 = 16.0;
+
+/// How far the field of view cone reaches, in map cells.
+const CONE_LENGTH: f64 = 4.0;
+
+fn hex(color: Color) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b)
+}
+
+impl Map {
+    /// The map from above, a rectangle per wall cell colored by kind, with the spawn and, given a `camera`, the player and view cone.
+    pub fn to_svg(&self, camera: Option<(&Player, &Life)>) -> String {
+        let (width, height) = (self.width as f64 * CELL, self.height as f64 * CELL);
+        let mut svg = String::new();
+        // writing to a String can't fail
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {} {}">"#,
+            width, height, width, height
+        );
+        let _ = writeln!(svg, r##"<rect class="floor" width="{}" height="{}" fill="#5a1903"/>"##, width, height);
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let (left, top) = (x as f64 * CELL, y as f64 * CELL);
+                if let Some(kind) = self.wall_kind(x as f64, y as f64) {
+                    let _ = writeln!(
+                        svg,
+                        r#"<rect class="wall" x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+                        left, top, CELL, CELL, hex(wall_color(kind))
+                    );
+                } else if self.floor_height(x as f64, y as f64) > 0.0 {
+                    let _ = writeln!(
+                        svg,
+                        r##"<rect class="raised" x="{}" y="{}" width="{}" height="{}" fill="#964619"/>"##,
+                        left, top, CELL, CELL
+                    );
+                }
+            }
+        }
+
+        if let Some((x, y)) = self.spawn() {
+            let _ = writeln!(
+                svg,
+                r##"<circle class="spawn" cx="{}" cy="{}" r="{:.2}" fill="none" stroke="#50dcff" stroke-width="2"/>"##,
+                (x as f64 + 0.5) * CELL,
+                (y as f64 + 0.5) * CELL,
+                CELL / 3.0
+            );
+        }
+
+        if let Some((player, life)) = camera {
+            let (center_x, center_y) = (player.player_x * CELL, player.player_y * CELL);
+            // headings move along (sin, cos), like everywhere else
+            let point = |angle: f64| {
+                (center_x + angle.sin() * CONE_LENGTH * CELL, center_y + angle.cos() * CONE_LENGTH * CELL)
+            };
+            let (start_x, start_y) = point(player.vision_angle - life.fov_angle / 2.0);
+            let (end_x, end_y) = point(player.vision_angle + life.fov_angle / 2.0);
+            let _ = writeln!(
+                svg,
+                r##"<path class="fov" d="M {:.2} {:.2} L {:.2} {:.2} A {r} {r} 0 0 0 {:.2} {:.2} Z" fill="#c89628" fill-opacity="0.5"/>"##,
+                center_x,
+                center_y,
+                start_x,
+                start_y,
+                end_x,
+                end_y,
+                r = CONE_LENGTH * CELL
+            );
+            let _ = writeln!(
+                svg,
+                r##"<circle class="player" cx="{:.2}" cy="{:.2}" r="{}" fill="#ffdc00"/>"##,
+                center_x,
+                center_y,
+                CELL / 4.0
+            );
+        }
+
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// Write `to_svg` to a file at `path`.
+    pub fn export_svg(&self, path: impl AsRef<Path>, camera: Option<(&Player, &Life)>) -> io::Result<()> {
+        std::fs::write(path, self.to_svg(camera))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_rect_per_wall_cell() {
+        let map = Map::parse("#####\n#%P.#\n#####").unwrap();
+        let svg = map.to_svg(None);
+        assert_eq!(svg.matches(r#"class="wall""#).count(), 13);
+        assert!(svg.contains(r#"width="80" height="48""#));
+        assert_eq!(svg.matches(r#"class="spawn""#).count(), 1);
+        assert!(!svg.contains(r#"class="player""#));
+
+        let (player, life) = (Player::new(2.5, 1.5, 0.0), Life::new(1.0, 16.0));
+        let svg = map.to_svg(Some((&player, &life)));
+        assert!(svg.contains(r#"class="player""#) && svg.contains(r#"class="fov""#));
+    }
+}
@@ -0,0 +1,105 @@
+use crate::player::Player;
+use pixel_canvas::Color;
+
+/// Sideways ray nudges within their columns, one per frame in turn, averaging out to the middle.
+pub const TAA_JITTER: [f64; 4] = [-0.375, 0.125, -0.125, 0.375];
+
+/// Default for `TemporalAa::blend`.
+pub const TAA_BLEND: f32 = 0.25;
+
+/// Defaults for `TemporalAa::max_move_speed` and `max_turn_speed`.
+pub const TAA_MAX_MOVE_SPEED: f64 = 0.5;
+pub const TAA_MAX_TURN_SPEED: f64 = 0.5;
+
+/// Jitters rays each frame and blends frames together while the view holds still, to settle shimmering edges.
+pub struct TemporalAa {
+    /// How much of each new frame goes into the blend; lower is smoother and slower to settle.
+    pub blend: f32,
+    /// Moving faster than this in cells per second, or turning faster than `max_turn_speed`, shows frames unblended.
+    pub max_move_speed: f64,
+    pub max_turn_speed: f64,
+    /// In floating point so it settles on the average instead of getting stuck on rounding.
+    history: Vec<[f32; 3]>,
+    frame: usize,
+    last_camera: Option<(f64, f64, f64)>,
+}
+
+impl Default for TemporalAa {
+    fn default() -> Self {
+        Self {
+            blend: TAA_BLEND,
+            max_move_speed: TAA_MAX_MOVE_SPEED,
+            max_turn_speed: TAA_MAX_TURN_SPEED,
+            history: Vec::new(),
+            frame: 0,
+            last_camera: None,
+        }
+    }
+}
+
+impl TemporalAa {
+    /// The ray jitter for the next frame, moving on through `TAA_JITTER`.
+    pub fn next_jitter(&mut self) -> f64 {
+        self.frame = (self.frame + 1) % TAA_JITTER.len();
+        TAA_JITTER[self.frame]
+    }
+
+    /// Blend the frame in `buffer`, drawn from `camera` `dt` after the last, with the ones before.
+    pub fn resolve(&mut self, buffer: &mut [Color], camera: &Player, dt: f64) {
+        let now = (camera.player_x, camera.player_y, camera.vision_angle);
+        let moving = match self.last_camera {
+            Some((x, y, angle)) if dt > 0.0 => {
+                (now.0 - x).hypot(now.1 - y) / dt > self.max_move_speed || (now.2 - angle).abs() / dt > self.max_turn_speed
+            }
+            _ => true,
+        };
+        self.last_camera = Some(now);
+
+        if moving || self.history.len() != buffer.len() {
+            self.history = buffer.iter().map(|pixel| [pixel.r as f32, pixel.g as f32, pixel.b as f32]).collect();
+            return;
+        }
+        let blend = self.blend.clamp(0.0, 1.0);
+        for (pixel, blended) in buffer.iter_mut().zip(&mut self.history) {
+            for (sum, channel) in blended.iter_mut().zip([pixel.r, pixel.g, pixel.b]) {
+                *sum += (channel as f32 - *sum) * blend;
+            }
+            let [r, g, b] = blended.map(|channel| channel.round() as u8);
+            *pixel = Color { r, g, b };
+        }
+    }
+
+    /// Forget the frames so far, so the next one starts the blend over.
+    pub fn reset(&mut self) {
+        self.history.clear();
+        self.last_camera = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn still_view_converges_on_the_frame() {
+        let mut taa = TemporalAa::default();
+        let camera = Player::new(2.0, 2.0, 0.5);
+        let mut first = vec![Color { r: 0, g: 0, b: 0 }; 4];
+        taa.resolve(&mut first, &camera, 1.0 / 60.0);
+
+        let mut last_gap = 200;
+        for _ in 0..60 {
+            let mut frame = vec![Color { r: 200, g: 100, b: 50 }; 4];
+            taa.resolve(&mut frame, &camera, 1.0 / 60.0);
+            let gap = 200 - frame[0].r;
+            assert!(gap <= last_gap);
+            last_gap = gap;
+        }
+        assert_eq!(last_gap, 0);
+
+        // moving shows the frame as it is
+        let mut frame = vec![Color { r: 10, g: 10, b: 10 }; 4];
+        taa.resolve(&mut frame, &Player::new(3.0, 2.0, 0.5), 1.0 / 60.0);
+        assert_eq!(frame[0].r, 10);
+    }
+}
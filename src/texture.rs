@@ -0,0 +1,169 @@
+use crate::ppm::read_ppm;
+use pixel_canvas::Color;
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::path::Path;
+
+/// One level of a texture's mip chain, pixels stored row by row from the top.
+pub struct MipLevel {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<Color>,
+}
+
+/// An image wrapped around walls, with a chain of half size copies for walls drawn small.
+pub struct Texture {
+    pub levels: Vec<MipLevel>,
+    /// How many map cells one repeat of the texture covers along a wall.
+    pub span: f64,
+}
+
+impl Texture {
+    pub fn new(width: usize, height: usize, pixels: Vec<Color>) -> Self {
+        let mut levels = vec![MipLevel { width, height, pixels }];
+
+        while let Some(level) = levels.last().filter(|level| level.width > 1 || level.height > 1) {
+            let next = downsample(level);
+            levels.push(next);
+        }
+
+        Self { levels, span: 1.0 }
+    }
+
+    /// A generated red brick pattern, `size` texels square.
+    pub fn bricks(size: usize) -> Self {
+        let brick_height = (size / 4).max(2);
+        let brick_width = (size / 2).max(2);
+        let mortar = Color { r: 170, g: 165, b: 150 };
+
+        let pixels = (0..size * size)
+            .map(|i| {
+                let (x, y) = (i % size, i / size);
+                // every other row of bricks is shifted by half a brick
+                let shift = if (y / brick_height).is_multiple_of(2) { 0 } else { brick_width / 2 };
+                if y % brick_height == 0 || (x + shift) % brick_width == 0 {
+                    mortar
+                } else {
+                    let variation = ((x / brick_width + y / brick_height) % 3) as u8 * 12;
+                    Color { r: 150 + variation, g: 60 + variation / 2, b: 40 }
+                }
+            })
+            .collect();
+
+        Self::new(size, size, pixels)
+    }
+
+    /// Load a texture from a binary PPM file, see `ppm::read_ppm`.
+    pub fn load_ppm(path: impl AsRef<Path>) -> io::Result<Self> {
+        let (width, height, pixels) = read_ppm(&mut BufReader::new(File::open(path)?))?;
+        Ok(Self::new(width, height, pixels))
+    }
+
+    pub fn width(&self) -> usize {
+        self.levels[0].width
+    }
+
+    pub fn height(&self) -> usize {
+        self.levels[0].height
+    }
+
+    /// The first mip level no more than twice as tall as a wall drawn `projected_height` pixels tall.
+    pub fn mip_for_height(&self, projected_height: f64) -> usize {
+        let last = self.levels.len() - 1;
+        if projected_height <= 0.0 {
+            return last;
+        }
+
+        let ratio = self.height() as f64 / projected_height;
+        if ratio <= 1.0 {
+            0
+        } else {
+            (ratio.log2().floor() as usize).min(last)
+        }
+    }
+
+    /// Nearest texel of `level` at `(u, v)`, both wrapping, with `v = 0` at the top.
+    pub fn sample(&self, level: usize, u: f64, v: f64) -> Color {
+        let level = &self.levels[level.min(self.levels.len() - 1)];
+        let x = ((u.rem_euclid(1.0) * level.width as f64) as usize).min(level.width - 1);
+        let y = ((v.rem_euclid(1.0) * level.height as f64) as usize).min(level.height - 1);
+        level.pixels[y * level.width + x]
+    }
+}
+
+/// What to draw on walls without a texture, gray so missing art stands out while walls keep their shape.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WallPattern {
+    /// A single shade, as untextured walls are drawn by default.
+    Flat,
+    /// Two to a cell across and four up the wall, with darker mortar.
+    Bricks,
+    /// Squares four to a cell each way, alternating light and dark.
+    Checker,
+}
+
+impl WallPattern {
+    /// Brightness at `(u, v)` as for `Texture::sample`, 1.0 being the wall's usual shade.
+    pub fn shade(self, u: f64, v: f64) -> f64 {
+        match self {
+            WallPattern::Flat => 1.0,
+            WallPattern::Bricks => {
+                let row = (v * 4.0).floor();
+                // every other row of bricks is shifted by half a brick
+                let along = u * 2.0 + if row.rem_euclid(2.0) == 0.0 { 0.0 } else { 0.5 };
+                let (across, up) = (along.rem_euclid(1.0), (v * 4.0).rem_euclid(1.0));
+                if across < 0.06 || up < 0.1 {
+                    0.55
+                } else {
+                    // a little variation from brick to brick
+                    1.0 - (along.floor() + row).rem_euclid(3.0) * 0.08
+                }
+            }
+            WallPattern::Checker => {
+                let (column, row) = ((u * 4.0).floor(), (v * 4.0).floor());
+                if (column + row).rem_euclid(2.0) == 0.0 { 1.0 } else { 0.7 }
+            }
+        }
+    }
+}
+
+/// Half size copy of `level`, each texel averaging the 2x2 block it covers.
+fn downsample(level: &MipLevel) -> MipLevel {
+    let width = (level.width / 2).max(1);
+    let height = (level.height / 2).max(1);
+    let mut pixels = Vec::with_capacity(width * height);
+
+    for y in 0..height {
+        for x in 0..width {
+            let (mut r, mut g, mut b, mut count) = (0u32, 0u32, 0u32, 0u32);
+            for source_y in (y * 2)..(y * 2 + 2).min(level.height) {
+                for source_x in (x * 2)..(x * 2 + 2).min(level.width) {
+                    let texel = level.pixels[source_y * level.width + source_x];
+                    r += texel.r as u32;
+                    g += texel.g as u32;
+                    b += texel.b as u32;
+                    count += 1;
+                }
+            }
+            pixels.push(Color { r: (r / count) as u8, g: (g / count) as u8, b: (b / count) as u8 });
+        }
+    }
+
+    MipLevel { width, height, pixels }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn far_wall_selects_coarser_mip() {
+        let texture = Texture::bricks(64);
+        let screen_height = 200.0;
+        let near = texture.mip_for_height(2.0 * screen_height / 2.0);
+        let far = texture.mip_for_height(2.0 * screen_height / 20.0);
+        assert_eq!(near, 0);
+        assert!(far > near);
+        assert!(texture.levels[far].height < texture.levels[near].height);
+    }
+}
@@ -0,0 +1,42 @@
+/// Simulation step length in seconds, one step per frame at pixel_canvas' 60fps.
+pub const FIXED_DT: f64 = 1.0 / 60.0;
+
+/// Most steps run per frame; time past that is dropped rather than caught up (no spiral of death).
+pub const MAX_STEPS: u32 = 5;
+
+/// Turns elapsed time into whole steps of `dt`, carrying the remainder over.
+pub struct FixedTimestep {
+    pub dt: f64,
+    pub max_steps: u32,
+    accumulator: f64,
+}
+
+impl FixedTimestep {
+    pub fn new(dt: f64, max_steps: u32) -> Self {
+        Self { dt, max_steps, accumulator: 0.0 }
+    }
+
+    /// Add `elapsed` seconds and return how many steps of `dt` to run now.
+    pub fn advance(&mut self, elapsed: f64) -> u32 {
+        self.accumulator += elapsed;
+        let steps = ((self.accumulator / self.dt) as u32).min(self.max_steps);
+        self.accumulator -= steps as f64 * self.dt;
+
+        // couldn't catch up within max_steps, drop the backlog
+        if self.accumulator >= self.dt {
+            self.accumulator %= self.dt;
+        }
+        steps
+    }
+
+    /// How far between the last step and the next, from 0.0 to 1.0, for blending when rendering.
+    pub fn alpha(&self) -> f64 {
+        self.accumulator / self.dt
+    }
+}
+
+impl Default for FixedTimestep {
+    fn default() -> Self {
+        Self::new(FIXED_DT, MAX_STEPS)
+    }
+}
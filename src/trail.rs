@@ -0,0 +1,52 @@
+use std::collections::VecDeque;
+
+/// The player's most recent positions, oldest first, up to `length` of them.
+pub struct Trail {
+    positions: VecDeque<(f64, f64)>,
+    length: usize,
+}
+
+impl Trail {
+    pub fn new(length: usize) -> Self {
+        Self { positions: VecDeque::with_capacity(length), length }
+    }
+
+    /// Record a position, unless it's where the player already was.
+    pub fn push(&mut self, x: f64, y: f64) {
+        if self.length == 0 || self.positions.back() == Some(&(x, y)) {
+            return;
+        }
+        if self.positions.len() == self.length {
+            self.positions.pop_front();
+        }
+        self.positions.push_back((x, y));
+    }
+
+    pub fn clear(&mut self) {
+        self.positions.clear();
+    }
+
+    pub fn length(&self) -> usize {
+        self.length
+    }
+
+    pub fn set_length(&mut self, length: usize) {
+        self.length = length;
+        while self.positions.len() > length {
+            self.positions.pop_front();
+        }
+    }
+
+    /// Stored positions, oldest first.
+    pub fn positions(&self) -> impl Iterator<Item = &(f64, f64)> + '_ {
+        self.positions.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+}
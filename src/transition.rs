@@ -0,0 +1,51 @@
+use crate::easing::{smoothstep, Easing};
+use crate::player::Player;
+use std::f64::consts::{PI, TAU};
+
+/// The camera gliding to where a teleport put the player, see `World::transition_time`.
+#[derive(Clone)]
+pub struct Transition {
+    from: Player,
+    duration: f64,
+    /// Seconds in as of the last step and the one before, blended like the rest of the camera.
+    elapsed: f64,
+    previous_elapsed: f64,
+    easing: Easing,
+}
+
+impl Transition {
+    /// Glide from `from` over `duration` seconds, turning the short way round.
+    pub fn new(mut from: Player, to_angle: f64, duration: f64) -> Self {
+        from.vision_angle = to_angle + (from.vision_angle - to_angle + PI).rem_euclid(TAU) - PI;
+        Self { from, duration, elapsed: 0.0, previous_elapsed: 0.0, easing: smoothstep }
+    }
+
+    pub fn with_easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    /// Advance by `dt` seconds.
+    pub fn update(&mut self, dt: f64) {
+        self.previous_elapsed = self.elapsed;
+        self.elapsed = (self.elapsed + dt).min(self.duration);
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+
+    /// Eased progress from 0.0 to 1.0.
+    pub fn progress(&self, alpha: f64) -> f64 {
+        if self.duration <= 0.0 {
+            return 1.0;
+        }
+        let elapsed = self.previous_elapsed + (self.elapsed - self.previous_elapsed) * alpha;
+        (self.easing)(elapsed / self.duration)
+    }
+
+    /// `camera`, at the destination, pulled back towards the start.
+    pub fn apply(&self, camera: &mut Player, alpha: f64) {
+        *camera = self.from.lerp(camera, self.progress(alpha));
+    }
+}
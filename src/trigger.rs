@@ -0,0 +1,80 @@
+use crate::sprite::Sprite;
+use crate::state::StateBag;
+use pixel_canvas::Color;
+
+/// Color of sprites put down by `spawn` lines in a manifest.
+pub const SPAWN_COLOR: Color = Color { r: 170, g: 50, b: 50 };
+
+/// What a trigger does when the player walks into its cell.
+#[derive(Clone)]
+pub enum TriggerAction {
+    /// Turn the wall at the cell into floor, e.g. to open a door.
+    Open { x: u16, y: u16 },
+    /// Add the sprite to the map.
+    Spawn(Sprite),
+    /// Text for the program to show the player.
+    Message(String),
+    /// Anything else, for the program to act on by name.
+    Event(String),
+    /// Set a value in the world's `StateBag`.
+    Set { name: String, value: i64 },
+    /// Add to a value in the world's `StateBag`, or take away if negative.
+    Add { name: String, amount: i64 },
+}
+
+/// An action tied to a map cell, fired when the player walks into it.
+#[derive(Clone)]
+pub struct Trigger {
+    pub x: u16,
+    pub y: u16,
+    pub action: TriggerAction,
+    pub repeat: bool,
+    /// Only fire while this `StateBag` value is at least this much, without using up a one-shot trigger.
+    pub requires: Option<(String, i64)>,
+    fired: bool,
+}
+
+impl Trigger {
+    /// A trigger that fires the first time the player enters `(x, y)`.
+    pub fn once(x: u16, y: u16, action: TriggerAction) -> Self {
+        Self { x, y, action, repeat: false, requires: None, fired: false }
+    }
+
+    /// A trigger that fires every time the player enters `(x, y)`.
+    pub fn repeating(x: u16, y: u16, action: TriggerAction) -> Self {
+        Self { x, y, action, repeat: true, requires: None, fired: false }
+    }
+
+    pub fn requiring(mut self, name: &str, at_least: i64) -> Self {
+        self.requires = Some((name.to_string(), at_least));
+        self
+    }
+
+    pub fn has_fired(&self) -> bool {
+        self.fired
+    }
+
+    /// The action, if the trigger fires on entering `(x, y)`; a one-shot trigger returns it only once.
+    pub fn enter(&mut self, x: u16, y: u16, state: &StateBag) -> Option<&TriggerAction> {
+        if (self.x, self.y) != (x, y) || (self.fired && !self.repeat) {
+            return None;
+        }
+        if let Some((name, at_least)) = &self.requires {
+            if state.get(name) < *at_least {
+                return None;
+            }
+        }
+        self.fired = true;
+        Some(&self.action)
+    }
+}
+
+/// Receives each fired action after the world has carried out the ones it can.
+pub type TriggerHook = Box<dyn FnMut(&TriggerAction)>;
+
+/// A solid sprite in the middle of cell `(x, y)`, for `spawn` lines.
+pub fn spawned_sprite(x: u16, y: u16) -> Sprite {
+    let mut sprite = Sprite::new(x as f64 + 0.5, y as f64 + 0.5, 0.4, 0.8, SPAWN_COLOR);
+    sprite.solid = true;
+    sprite
+}
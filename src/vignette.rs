@@ -0,0 +1,70 @@
+use crate::easing::smoothstep;
+use pixel_canvas::Color;
+
+/// Default for `Vignette::strength`.
+pub const VIGNETTE_STRENGTH: f32 = 0.5;
+
+/// Default for `Vignette::radius`.
+pub const VIGNETTE_RADIUS: f32 = 0.5;
+
+/// Share of a pixel's color the vignette keeps, falling from 1.0 past `radius` to `1 - strength` at the corners.
+pub fn vignette_factor(x: usize, y: usize, width: usize, height: usize, strength: f32, radius: f32) -> f32 {
+    let across = |at: usize, size: usize| (at as f32 + 0.5) / size.max(1) as f32 * 2.0 - 1.0;
+    let distance = across(x, width).hypot(across(y, height));
+    let falloff = smoothstep(((distance - radius) / (std::f32::consts::SQRT_2 - radius).max(1e-3)) as f64) as f32;
+    1.0 - strength.clamp(0.0, 1.0) * falloff
+}
+
+/// Darkens the frame's edges, with the per-pixel factors cached while size and settings don't change.
+pub struct Vignette {
+    /// 0.0 leaves the corners alone, 1.0 turns them black.
+    pub strength: f32,
+    /// Distance from the middle, as a fraction of the way to an edge, where darkening starts.
+    pub radius: f32,
+    factors: Vec<f32>,
+    /// What `factors` was worked out for: width, height, strength, radius.
+    built_for: Option<(usize, usize, f32, f32)>,
+}
+
+impl Default for Vignette {
+    fn default() -> Self {
+        Self::new(VIGNETTE_STRENGTH, VIGNETTE_RADIUS)
+    }
+}
+
+impl Vignette {
+    pub fn new(strength: f32, radius: f32) -> Self {
+        Self { strength, radius, factors: Vec::new(), built_for: None }
+    }
+
+    /// Darken a `width` x `height` frame in `buffer`.
+    pub fn apply(&mut self, buffer: &mut [Color], width: usize, height: usize) {
+        let wanted = (width, height, self.strength, self.radius);
+        if self.built_for != Some(wanted) {
+            self.factors = (0..height)
+                .flat_map(|y| (0..width).map(move |x| (x, y)))
+                .map(|(x, y)| vignette_factor(x, y, width, height, self.strength, self.radius))
+                .collect();
+            self.built_for = Some(wanted);
+        }
+        for (pixel, &factor) in buffer.iter_mut().zip(&self.factors) {
+            let darken = |channel: u8| (channel as f32 * factor) as u8;
+            *pixel = Color { r: darken(pixel.r), g: darken(pixel.g), b: darken(pixel.b) };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn corner_is_darker_than_center() {
+        let (width, height) = (64, 48);
+        let mut buffer = vec![Color { r: 200, g: 200, b: 200 }; width * height];
+        Vignette::default().apply(&mut buffer, width, height);
+        let center = buffer[height / 2 * width + width / 2];
+        let corner = buffer[0];
+        assert!(corner.r < center.r, "corner {} center {}", corner.r, center.r);
+    }
+}
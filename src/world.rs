@@ -0,0 +1,857 @@
+use crate::bindings::Action;
+use crate::bob::HeadBob;
+use crate::easing::{smoothstep, Easing};
+use crate::input::{ActionBuffer, InputState};
+use crate::life::Life;
+use crate::map::{Map, WallKind, RAISED_FLOOR_HEIGHT};
+use crate::muzzle::Muzzle;
+use crate::player::{Player, PITCH_SPEED};
+use crate::portal::Portal;
+use crate::ray::cast_ray;
+use crate::shake::Shake;
+use crate::sound::{Footsteps, SoundEvent, SoundHook};
+use crate::sprite::{pick_sprite, solid_contact};
+use crate::state::StateBag;
+use crate::trail::Trail;
+use crate::transition::Transition;
+use crate::trigger::{TriggerAction, TriggerHook};
+
+/// How many past positions the minimap trail keeps by default.
+pub const TRAIL_LENGTH: usize = 120;
+
+/// Default for `World::stuck_nudge`, in map cells.
+pub const STUCK_NUDGE: f64 = 0.001;
+
+/// Default for `World::collision_substep`, in map cells.
+pub const COLLISION_SUBSTEP: f64 = 0.25;
+
+/// Highest the floor can rise from one cell to the next for the player to
+/// still walk up onto it, in wall heights, as high as a raised floor step.
+/// Jumping gets them up higher.
+pub const STEP_HEIGHT: f64 = RAISED_FLOOR_HEIGHT;
+
+/// Default for `World::max_turn`, in radians; key turns only reach it below 10 frames a second.
+pub const MAX_TURN: f64 = 0.75;
+
+/// Most pieces one step's move is split into, see `World::collision_substep`.
+pub const MAX_COLLISION_SUBSTEPS: usize = 16;
+
+/// What lies ahead of the player, see `World::probe_ahead`.
+#[derive(Clone, Copy, Debug)]
+pub struct Probe {
+    /// The first wall that blocks sight and how far away it is.
+    pub wall: Option<(&'static WallKind, f64)>,
+    /// Index into the map's sprites of the nearest sprite in front of that wall, and its distance.
+    pub sprite: Option<(usize, f64)>,
+}
+
+/// Everything the simulation advances and the renderers draw.
+pub struct World {
+    pub map: Map,
+    /// The player being stepped, and everything else that's theirs.
+    pub seat: Seat,
+    pub life: Life,
+    /// Off, a diagonal move stops along y where the cell it skips is a wall, rather than clipping the corner.
+    pub corner_cutting: bool,
+    /// Seconds `teleport` and `load_map` glide the camera over, ignoring input; 0.0 jumps at once.
+    pub transition_time: f64,
+    pub transition_easing: Easing,
+    /// Push the player out of walls `teleport` or `load_map` put them in, see `Player::resolve_overlap`.
+    pub resolve_overlaps: bool,
+    /// Where shots leave from relative to the player, see `muzzle_point`.
+    pub muzzle: Muzzle,
+    /// Named values for game logic, read and changed by triggers.
+    pub state: StateBag,
+    /// How close to a wall ahead, in map cells, a slide along another wall starts curving away so inside corners round off; 0.0 is off.
+    pub corner_rounding: f64,
+    /// How far to push the player sideways off a wall edge when a move gets nowhere, in map cells; 0.0 turns it off.
+    pub stuck_nudge: f64,
+    /// Longest piece of a move checked against walls at once, so fast moves can't skip thin walls; 0.0 is off.
+    pub collision_substep: f64,
+    /// Scale walking and strafing by `zoom_speed_factor` so a zoomed view doesn't seem to rush past.
+    pub zoom_slows_movement: bool,
+    /// Most the player turns in one step, keys and mouse together, in radians, so mouse spikes can't whip the view around.
+    pub max_turn: f64,
+    /// Whether something that shows, other than the camera, changed since `take_dirty`.
+    dirty: bool,
+    sound_hook: Option<SoundHook>,
+    trigger_hook: Option<TriggerHook>,
+}
+
+/// What belongs to one player rather than the world, so several can take turns stepping it.
+pub struct Seat {
+    pub player: Player,
+    /// The player as it was before the last step, for interpolated rendering.
+    pub previous_player: Player,
+    /// Where the player has recently been.
+    pub trail: Trail,
+    pub footsteps: Footsteps,
+    /// Presses waiting for the player to be able to act on them.
+    pub actions: ActionBuffer,
+    /// Camera shake from bumping into things.
+    pub shake: Shake,
+    /// Walk forward as if the key were held; `Action::Autorun` toggles it and walking back stops it.
+    pub autorun: bool,
+    /// The camera bobbing along as the player walks.
+    pub bob: HeadBob,
+    transition: Option<Transition>,
+    /// Whether the last move ran into something, so holding a key against a
+    /// wall only shakes once.
+    blocked: bool,
+}
+
+impl Seat {
+    pub fn new(player: Player) -> Self {
+        Self {
+            previous_player: player.clone(),
+            player,
+            trail: Trail::new(TRAIL_LENGTH),
+            footsteps: Footsteps::default(),
+            actions: ActionBuffer::default(),
+            shake: Shake::default(),
+            autorun: false,
+            bob: HeadBob::default(),
+            transition: None,
+            blocked: false,
+        }
+    }
+
+    /// The player blended between the last two steps by `alpha`, with transition, shake and bob.
+    pub fn camera(&self, alpha: f64) -> Player {
+        let mut camera = self.previous_player.lerp(&self.player, alpha);
+        // drawing only looks at eye_height
+        camera.eye_height = camera.crouched_eye_height();
+        camera.crouch = 0.0;
+        if let Some(transition) = &self.transition {
+            transition.apply(&mut camera, alpha);
+        }
+        self.shake.apply(&mut camera);
+        self.bob.apply(&mut camera);
+        camera
+    }
+}
+
+impl World {
+    /// A world with `player` in `map`, pushed out of the wall if they start
+    /// inside one.
+    pub fn new(map: Map, mut player: Player, life: Life) -> Self {
+        player.resolve_overlap(&map);
+        Self {
+            map,
+            seat: Seat::new(player),
+            life,
+            corner_cutting: true,
+            transition_time: 0.0,
+            transition_easing: smoothstep,
+            resolve_overlaps: true,
+            muzzle: Muzzle::default(),
+            state: StateBag::new(),
+            corner_rounding: 0.0,
+            stuck_nudge: STUCK_NUDGE,
+            collision_substep: COLLISION_SUBSTEP,
+            zoom_slows_movement: false,
+            max_turn: MAX_TURN,
+            dirty: true,
+            sound_hook: None,
+            trigger_hook: None,
+        }
+    }
+
+    /// Trade the world's player for the one in `seat`.
+    pub fn swap_seat(&mut self, seat: &mut Seat) {
+        std::mem::swap(&mut self.seat, seat);
+    }
+
+    /// Step the player in `seat` through the same map; lifts only move on in `step`.
+    pub fn step_seat(&mut self, seat: &mut Seat, input: &InputState, dt: f64) {
+        self.swap_seat(seat);
+        self.step_player(input, dt);
+        self.swap_seat(seat);
+    }
+
+    /// Call `hook` with every sound the world makes from now on.
+    pub fn on_sound(&mut self, hook: impl FnMut(&SoundEvent) + 'static) {
+        self.sound_hook = Some(Box::new(hook));
+    }
+
+    /// Pass a sound at `(x, y)` to the sound hook with how loud and to which side the player hears it.
+    pub fn emit_sound(&mut self, name: &'static str, x: f64, y: f64) {
+        if let Some(hook) = &mut self.sound_hook {
+            hook(&SoundEvent::heard_by(name, x, y, &self.seat.player));
+        }
+    }
+
+    /// Call `hook` with the action of every trigger that fires from now on.
+    pub fn on_trigger(&mut self, hook: impl FnMut(&TriggerAction) + 'static) {
+        self.trigger_hook = Some(Box::new(hook));
+    }
+
+    /// Fire the map's triggers for cell `(x, y)`, carrying out what the world can before the hook.
+    pub fn enter_cell(&mut self, x: u16, y: u16) {
+        let state = &self.state;
+        let fired: Vec<TriggerAction> =
+            self.map.triggers.iter_mut().filter_map(|trigger| trigger.enter(x, y, state).cloned()).collect();
+        for action in fired {
+            self.dirty = true;
+            match &action {
+                TriggerAction::Open { x, y } => self.map.open(*x, *y),
+                TriggerAction::Spawn(sprite) => self.map.sprites.push(*sprite),
+                TriggerAction::Set { name, value } => self.state.set(name, *value),
+                TriggerAction::Add { name, amount } => self.state.add(name, *amount),
+                TriggerAction::Message(_) | TriggerAction::Event(_) => (),
+            }
+            if let Some(hook) = &mut self.trigger_hook {
+                hook(&action);
+            }
+        }
+    }
+
+    /// Height of the floor under the player, which they stand on when not in
+    /// the air. On a lift it goes up and down with the lift.
+    pub fn ground_height(&self) -> f64 {
+        self.map.floor_height(self.seat.player.player_x, self.seat.player.player_y)
+    }
+
+    /// Advance the simulation by `dt` seconds.
+    pub fn step(&mut self, input: &InputState, dt: f64) {
+        self.dirty |= self.map.update_lifts(dt);
+        self.step_player(input, dt);
+    }
+
+    /// Note a change made from outside the world that shows, see `take_dirty`.
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Whether anything that shows other than the camera has changed since the last call; true on the first.
+    pub fn take_dirty(&mut self) -> bool {
+        std::mem::take(&mut self.dirty)
+    }
+
+    /// The field of view over the unzoomed one, what `zoom_slows_movement` scales movement by.
+    pub fn zoom_speed_factor(&self) -> f64 {
+        if self.life.base_fov_angle <= 0.0 {
+            return 1.0;
+        }
+        self.life.fov_angle / self.life.base_fov_angle
+    }
+
+    fn step_player(&mut self, input: &InputState, dt: f64) {
+        self.seat.previous_player = self.seat.player.clone();
+
+        // hands off while the camera glides to a teleport's destination
+        let idle = InputState::default();
+        let input = match &mut self.seat.transition {
+            Some(transition) => {
+                transition.update(dt);
+                &idle
+            }
+            None => input,
+        };
+
+        if self.seat.transition.is_none() && self.seat.actions.take(Action::Autorun) {
+            self.seat.autorun = !self.seat.autorun;
+        }
+        if input.walk < 0.0 {
+            self.seat.autorun = false;
+        }
+        let running;
+        let input = if self.seat.autorun && input.walk == 0.0 {
+            running = InputState { walk: 1.0, ..*input };
+            &running
+        } else {
+            input
+        };
+
+        // turn first so walking and strafing both follow the new heading,
+        // turning while strafing then curves around instead of drifting
+        let turn = input.turn * self.seat.player.turn_speed * dt + input.mouse_turn;
+        let max_turn = self.max_turn.max(0.0);
+        self.seat.player.rotate(turn.clamp(-max_turn, max_turn));
+        let max_pitch = self.life.max_pitch.max(0.0);
+        self.seat.player.pitch = (self.seat.player.pitch + input.look * PITCH_SPEED * dt).clamp(-max_pitch, max_pitch);
+
+        // stay down while there's no room to stand up
+        let mut standing = self.seat.player.clone();
+        standing.crouch = 0.0;
+        let cramped = !has_head_room(&self.map, &standing, standing.player_x, standing.player_y);
+        self.seat.player.update_crouch(input.crouch || cramped, dt);
+
+        let speed = if self.zoom_slows_movement { self.zoom_speed_factor() } else { 1.0 };
+        let forward = input.walk * self.seat.player.forward_speed * speed * dt;
+        let sideways = input.strafe * self.seat.player.strafe_speed * speed * dt;
+        let (dx, dy) = self.seat.player.movement(forward, sideways);
+        let ground = self.ground_height();
+        self.move_player_in_substeps(dx, dy);
+        // stepping off a ledge, or a lift partway up, drops from where the
+        // player was rather than snapping down
+        let drop = ground - self.ground_height();
+        if drop > 0.0 {
+            self.seat.player.jump_height += drop;
+        }
+        if self.seat.player.player_x == self.seat.previous_player.player_x && self.seat.player.player_y == self.seat.previous_player.player_y {
+            self.unstick(dx, dy);
+        }
+
+        let cell = |player: &Player| (player.player_x.floor() as u16, player.player_y.floor() as u16);
+        let (cell_x, cell_y) = cell(&self.seat.player);
+        if (cell_x, cell_y) != cell(&self.seat.previous_player) {
+            if let Some(portal) = self.map.portal(cell_x, cell_y).copied() {
+                self.go_through(&portal);
+            }
+        }
+        let (cell_x, cell_y) = cell(&self.seat.player);
+        if (cell_x, cell_y) != cell(&self.seat.previous_player) {
+            self.enter_cell(cell_x, cell_y);
+        }
+        if let Some(stream) = self.map.stream() {
+            stream.enter(self.seat.player.player_x, self.seat.player.player_y);
+        }
+
+        // only what was actually walked counts, not running into a wall
+        let walked = (self.seat.player.player_x - self.seat.previous_player.player_x)
+            .hypot(self.seat.player.player_y - self.seat.previous_player.player_y);
+        for _ in 0..self.seat.footsteps.advance(walked) {
+            self.emit_sound("footstep", self.seat.player.player_x, self.seat.player.player_y);
+        }
+        self.seat.bob.update(walked, dt);
+
+        // a jump pressed while still in the air waits for the landing, and
+        // there's no jumping under a low ceiling
+        let low_ceiling = self.map.ceiling_height(self.seat.player.player_x, self.seat.player.player_y) < 1.0;
+        if self.seat.transition.is_none() && !self.seat.player.is_airborne() && !low_ceiling && self.seat.actions.take(Action::Jump) {
+            self.seat.player.jump();
+        }
+        self.seat.player.fall(dt);
+        self.seat.actions.tick(dt);
+
+        self.seat.shake.update(dt);
+        let fov_angle = self.life.fov_angle;
+        self.life.update_zoom(input.zoom, dt);
+        self.dirty |= self.life.fov_angle != fov_angle;
+
+        self.seat.trail.push(self.seat.player.player_x, self.seat.player.player_y);
+        if self.seat.transition.as_ref().is_some_and(Transition::is_done) {
+            self.seat.transition = None;
+        }
+    }
+
+    /// Move by `(dx, dy)` an axis at a time, sliding along walls and up to solid sprites; see `corner_cutting` and `corner_rounding`.
+    pub fn move_player(&mut self, dx: f64, dy: f64) {
+        let (x, y) = (self.seat.player.player_x, self.seat.player.player_y);
+        let (dx, dy) = if self.corner_rounding > 0.0 { self.round_corner(dx, dy) } else { (dx, dy) };
+        let mut blocked = false;
+
+        // x goes first, so a move into the diagonal neighbour cell only clips
+        // a corner when the cell it skips along y is a wall
+        let diagonal = x.floor() != (x + dx).floor() && y.floor() != (y + dy).floor();
+        let corner = !self.corner_cutting && diagonal && self.blocks_player(x, y + dy);
+
+        let allowed_x = if self.blocks_player(x + dx, y) { 0.0 } else { solid_contact(&self.map.sprites, x, y, dx, 0.0) };
+        self.seat.player.player_x += dx * allowed_x;
+        blocked |= dx != 0.0 && allowed_x < 1.0;
+
+        let x = self.seat.player.player_x;
+        let allowed_y = if corner || self.blocks_player(x, y + dy) { 0.0 } else { solid_contact(&self.map.sprites, x, y, 0.0, dy) };
+        self.seat.player.player_y += dy * allowed_y;
+        blocked |= dy != 0.0 && allowed_y < 1.0;
+
+        if blocked && !self.seat.blocked {
+            self.seat.shake.kick();
+        }
+        self.seat.blocked = blocked;
+    }
+
+    /// `move_player` split into pieces of at most `collision_substep`.
+    pub fn move_player_in_substeps(&mut self, dx: f64, dy: f64) {
+        let length = dx.hypot(dy);
+        let substeps = if self.collision_substep > 0.0 {
+            ((length / self.collision_substep).ceil() as usize).clamp(1, MAX_COLLISION_SUBSTEPS)
+        } else {
+            1
+        };
+        for _ in 0..substeps {
+            self.move_player(dx / substeps as f64, dy / substeps as f64);
+        }
+    }
+
+    /// Whether a wall, a ceiling too low or floor more than `STEP_HEIGHT` up stops the player at `(x, y)`.
+    fn blocks_player(&self, x: f64, y: f64) -> bool {
+        let feet = self.ground_height() + self.seat.player.jump_height;
+        self.map.blocks_movement(x, y)
+            || !has_head_room(&self.map, &self.seat.player, x, y)
+            || self.map.floor_height(x, y) > feet + STEP_HEIGHT
+    }
+
+    /// Bend a move along a wall into an inside corner away from that wall, more the closer both walls are.
+    fn round_corner(&self, dx: f64, dy: f64) -> (f64, f64) {
+        let (x, y) = (self.seat.player.player_x, self.seat.player.player_y);
+        // the same either way round, with `along` the bigger part of the move
+        let (along, across, flip) = if dy.abs() > dx.abs() { (dy, dx, false) } else { (dx, dy, true) };
+        let gap = |along_step: f64, across_step: f64| {
+            let direction = if flip { (along_step, across_step) } else { (across_step, along_step) };
+            wall_gap(&self.map, x, y, direction)
+        };
+
+        // the wall alongside is the one the move leans towards, or the
+        // nearer one when it goes straight along, give or take rounding
+        let side = if across.abs() > along.abs() * 1e-9 {
+            across.signum()
+        } else if gap(0.0, 1.0) < gap(0.0, -1.0) {
+            1.0
+        } else {
+            -1.0
+        };
+        if along == 0.0 {
+            return (dx, dy);
+        }
+        // easing in with both walls keeps the path smooth rather than
+        // snapping when either one comes within range
+        let near = |gap: f64| (1.0 - gap / self.corner_rounding).max(0.0);
+        let closeness = near(gap(along.signum(), 0.0)) * near(gap(0.0, side));
+        let across = across + (-side * along.abs() - across) * closeness;
+        if flip { (dx, across) } else { (across, dy) }
+    }
+
+    /// Retry a move that got nowhere from `stuck_nudge` to either side, keeping the first that makes progress.
+    fn unstick(&mut self, dx: f64, dy: f64) {
+        let length = dx.hypot(dy);
+        if self.stuck_nudge <= 0.0 || length == 0.0 {
+            return;
+        }
+        let (x, y) = (self.seat.player.player_x, self.seat.player.player_y);
+        let (side_x, side_y) = (-dy / length * self.stuck_nudge, dx / length * self.stuck_nudge);
+
+        for sign in [1.0, -1.0] {
+            let (nudged_x, nudged_y) = (x + sign * side_x, y + sign * side_y);
+            if self.blocks_player(nudged_x, nudged_y) {
+                continue;
+            }
+            self.seat.player.player_x = nudged_x;
+            self.seat.player.player_y = nudged_y;
+            self.move_player_in_substeps(dx, dy);
+            if (self.seat.player.player_x, self.seat.player.player_y) != (nudged_x, nudged_y) {
+                return;
+            }
+            self.seat.player.player_x = x;
+            self.seat.player.player_y = y;
+        }
+    }
+
+    /// Where something the player fires should start from, see
+    /// `Muzzle::point`.
+    pub fn muzzle_point(&self) -> (f64, f64) {
+        self.muzzle.point(&self.seat.player, &self.map)
+    }
+
+    /// What the player would run into within `distance` straight ahead.
+    pub fn probe_ahead(&self, distance: f64) -> Probe {
+        let (x, y, angle) = (self.seat.player.player_x, self.seat.player.player_y, self.seat.player.vision_angle);
+
+        let hit = cast_ray(&self.map, x, y, angle, distance, 0);
+        let wall = hit
+            .cell
+            .and_then(|(cell_x, cell_y)| self.map.wall_kind(cell_x as f64, cell_y as f64))
+            .map(|kind| (kind, hit.distance));
+
+        // sprites behind the wall are out of the way
+        let sprite = pick_sprite(&self.map.sprites, x, y, angle, hit.distance);
+
+        Probe { wall, sprite }
+    }
+
+    /// Move the player without a trail across the map, gliding over `transition_time`.
+    pub fn teleport(&mut self, x: f64, y: f64, angle: f64) {
+        self.seat.transition = (self.transition_time > 0.0).then(|| Transition::new(self.camera(1.0), angle, self.transition_time).with_easing(self.transition_easing));
+        self.seat.player.player_x = x;
+        self.seat.player.player_y = y;
+        self.seat.player.vision_angle = angle;
+        if self.resolve_overlaps {
+            self.seat.player.resolve_overlap(&self.map);
+        }
+        self.seat.previous_player = self.seat.player.clone();
+        self.seat.trail.clear();
+    }
+
+    /// Carry the player, and where they were a step ago so the camera doesn't sweep, through `portal`.
+    fn go_through(&mut self, portal: &Portal) {
+        for player in [&mut self.seat.player, &mut self.seat.previous_player] {
+            (player.player_x, player.player_y, player.vision_angle) = portal.carry(player.player_x, player.player_y, player.vision_angle);
+        }
+        if self.resolve_overlaps {
+            self.seat.player.resolve_overlap(&self.map);
+        }
+        self.seat.trail.clear();
+    }
+
+    /// Swap in another map and put the player on its spawn, or the middle of
+    /// the map if it has none. Textures carry over, and so does the sky
+    /// unless the new map brings its own. Everything that belongs to the
+    /// level itself comes from the new map, and `state` starts empty.
+    pub fn load_map(&mut self, mut map: Map) {
+        map.textures = std::mem::take(&mut self.map.textures);
+        if map.sky.is_none() {
+            map.sky = self.map.sky.take();
+        }
+        let (spawn_x, spawn_y) = map.spawn().unwrap_or((map.width / 2, map.height / 2));
+        self.map = map;
+        self.state = StateBag::new();
+        self.dirty = true;
+        self.teleport(spawn_x as f64 + 0.5, spawn_y as f64 + 0.5, 0.0);
+    }
+
+    /// The camera for the seated player, see `Seat::camera`.
+    pub fn camera(&self, alpha: f64) -> Player {
+        self.seat.camera(alpha)
+    }
+}
+
+/// Distance from `(x, y)` to the next cell along the unit step `direction` if it blocks movement, otherwise infinity.
+fn wall_gap(map: &Map, x: f64, y: f64, direction: (f64, f64)) -> f64 {
+    let (step_x, step_y) = direction;
+    if !map.blocks_movement(x.floor() + 0.5 + step_x, y.floor() + 0.5 + step_y) {
+        return f64::INFINITY;
+    }
+    let edge = |position: f64, step: f64| if step > 0.0 { position.floor() + 1.0 - position } else { position - position.floor() };
+    if step_x != 0.0 { edge(x, step_x) } else { edge(y, step_y) }
+}
+
+/// Whether `player`'s head, as crouched as they are now, fits under the ceiling at `(x, y)`.
+fn has_head_room(map: &Map, player: &Player, x: f64, y: f64) -> bool {
+    map.floor_height(x, y) + player.head_height() <= map.ceiling_height(x, y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lift::Lift;
+    use crate::sprite::Sprite;
+    use crate::trigger::Trigger;
+    use pixel_canvas::Color;
+    use std::cell::Cell;
+    use std::f64::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+    use std::rc::Rc;
+
+    fn world(source: &str, x: f64, y: f64, angle: f64) -> World {
+        World::new(Map::parse(source).unwrap(), Player::new(x, y, angle), Life::new(FRAC_PI_4, 16.0))
+    }
+
+    /// A `size` x `size` room of open floor inside a border of walls.
+    fn open_room(size: usize) -> String {
+        let wall = "#".repeat(size);
+        let row = format!("#{}#", ".".repeat(size - 2));
+        let mut rows = vec![wall.clone()];
+        rows.extend(std::iter::repeat_n(row, size - 2));
+        rows.push(wall);
+        rows.join("\n")
+    }
+
+    #[test]
+    fn strafing_while_turning_curves_with_the_heading() {
+        let mut world = world(&open_room(21), 10.5, 10.5, 0.0);
+        let input = InputState { strafe: 1.0, turn: 1.0, ..InputState::default() };
+        let dt = 0.02;
+        let (mut x, mut y, mut angle) = (10.5, 10.5, 0.0);
+        for _ in 0..5 {
+            world.step(&input, dt);
+            angle += world.seat.player.turn_speed * dt;
+            let step = world.seat.player.strafe_speed * dt;
+            x += angle.cos() * step;
+            y -= angle.sin() * step;
+        }
+        assert!((world.seat.player.vision_angle - angle).abs() < 1e-9);
+        assert!((world.seat.player.player_x - x).abs() < 1e-9);
+        assert!((world.seat.player.player_y - y).abs() < 1e-9);
+    }
+
+    #[test]
+    fn walking_emits_a_footstep_per_stride() {
+        let mut world = world(&open_room(21), 10.5, 2.5, 0.0);
+        let footsteps = Rc::new(Cell::new(0));
+        let counted = footsteps.clone();
+        world.on_sound(move |event| {
+            if event.name == "footstep" {
+                counted.set(counted.get() + 1);
+            }
+        });
+        let input = InputState { walk: 1.0, ..InputState::default() };
+        // 3.6 cells at 0.8 a stride
+        for _ in 0..30 {
+            world.step(&input, 0.01);
+        }
+        assert!((world.seat.player.player_y - 6.1).abs() < 1e-9);
+        assert_eq!(footsteps.get(), 4);
+
+        // standing still adds none
+        for _ in 0..30 {
+            world.step(&InputState::default(), 0.01);
+        }
+        assert_eq!(footsteps.get(), 4);
+    }
+
+    #[test]
+    fn probe_finds_wall_and_sprite_ahead() {
+        let mut world = world("#######\n#.....#\n#######", 1.5, 1.5, FRAC_PI_2);
+        world.map.sprites.push(Sprite::new(3.5, 1.5, 0.5, 0.5, Color { r: 255, g: 0, b: 0 }));
+        let probe = world.probe_ahead(10.0);
+        let (kind, distance) = probe.wall.unwrap();
+        assert_eq!(kind.glyph, '#');
+        assert!((distance - 4.5).abs() < 1e-6);
+        let (index, distance) = probe.sprite.unwrap();
+        assert_eq!(index, 0);
+        assert!((distance - 1.75).abs() < 1e-9);
+
+        assert!(world.probe_ahead(1.0).wall.is_none());
+    }
+
+    #[test]
+    fn solid_sprite_stops_the_player_at_contact() {
+        let mut world = world(&open_room(9), 4.5, 7.5, 0.0);
+        let mut barrel = Sprite::new(4.5, 3.5, 1.0, 1.0, Color { r: 0, g: 255, b: 0 });
+        barrel.solid = true;
+        world.map.sprites.push(barrel);
+        for _ in 0..20 {
+            world.move_player(0.0, -0.3);
+        }
+        assert!((world.seat.player.player_y - 4.0).abs() < 1e-9);
+        assert_eq!(world.seat.player.player_x, 4.5);
+
+        world.map.sprites[0].solid = false;
+        world.move_player(0.0, -0.3);
+        assert!(world.seat.player.player_y < 4.0);
+    }
+
+    #[test]
+    fn jump_pressed_just_before_landing_happens_on_landing() {
+        for (window, jumps) in [(0.15, true), (0.05, false)] {
+            let mut world = world(&open_room(5), 2.5, 2.5, 0.0);
+            world.seat.actions.window = window;
+            world.seat.player.jump_height = 0.05;
+            world.seat.actions.press(Action::Jump);
+            // three steps to come down, the fourth takes off again
+            for _ in 0..4 {
+                world.step(&InputState::default(), 0.04);
+            }
+            assert_eq!(world.seat.player.vertical_speed > 0.0, jumps);
+        }
+    }
+
+    #[test]
+    fn diagonal_move_past_a_wall_corner() {
+        for (cutting, end_y) in [(true, 2.1), (false, 1.9)] {
+            let mut world = world("####\n#..#\n##.#\n####", 1.9, 1.9, 0.0);
+            world.corner_cutting = cutting;
+            world.move_player(0.2, 0.2);
+            assert!((world.seat.player.player_x - 2.1).abs() < 1e-9);
+            assert!((world.seat.player.player_y - end_y).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn camera_lands_on_the_teleport_target() {
+        let mut world = world(&open_room(9), 2.5, 2.5, 0.0);
+        world.transition_time = 0.5;
+        world.teleport(6.5, 6.5, 1.0);
+        let walk = InputState { walk: 1.0, ..InputState::default() };
+
+        world.step(&walk, 0.25);
+        let halfway = world.camera(1.0);
+        assert!(halfway.player_x > 2.5 && halfway.player_x < 6.5);
+        assert!(halfway.vision_angle > 0.0 && halfway.vision_angle < 1.0);
+
+        world.step(&walk, 0.25);
+        let camera = world.camera(1.0);
+        assert_eq!((camera.player_x, camera.player_y, camera.vision_angle), (6.5, 6.5, 1.0));
+
+        // input was ignored on the way, and counts again once there
+        world.step(&walk, 0.01);
+        assert!(world.seat.player.player_y > 6.5);
+    }
+
+    #[test]
+    fn trigger_fires_once_on_entering_its_cell() {
+        use crate::trigger::Trigger;
+
+        let mut world = world(&open_room(8), 1.5, 1.5, FRAC_PI_2);
+        world.map.triggers.push(Trigger::once(3, 1, TriggerAction::Event("alarm".to_string())));
+        let fired = Rc::new(Cell::new(0));
+        let count = fired.clone();
+        world.on_trigger(move |_| count.set(count.get() + 1));
+
+        let walk = InputState { walk: 1.0, ..InputState::default() };
+        let back = InputState { walk: -1.0, ..InputState::default() };
+        for input in [&walk, &back, &walk] {
+            for _ in 0..20 {
+                world.step(input, 1.0 / 60.0);
+            }
+        }
+        assert_eq!(fired.get(), 1);
+        assert!(world.map.triggers[0].has_fired());
+    }
+
+    #[test]
+    fn nudge_frees_a_player_wedged_on_a_seam() {
+        let source = "#####\n#...#\n##.##\n##.##\n#####";
+        let walk = InputState { walk: 1.0, ..InputState::default() };
+        // a hair inside the wall column beside the gap south
+        let wedged = |nudge: f64| {
+            let mut world = world(source, 2.0 - 1e-12, 1.9, 0.0);
+            world.stuck_nudge = nudge;
+            world.step(&walk, 1.0 / 60.0);
+            world.seat.player.player_y
+        };
+        assert_eq!(wedged(0.0), 1.9);
+        assert!(wedged(STUCK_NUDGE) > 2.0);
+    }
+
+    #[test]
+    fn rounding_curves_a_slide_out_of_an_inside_corner() {
+        // sliding east along the north wall towards the north east corner
+        let path = |rounding: f64| {
+            let mut world = world(&open_room(8), 3.5, 1.2, FRAC_PI_2);
+            world.corner_rounding = rounding;
+            (0..40)
+                .map(|_| {
+                    world.move_player(0.1, -0.02);
+                    (world.seat.player.player_x, world.seat.player.player_y)
+                })
+                .collect::<Vec<_>>()
+        };
+        let (plain, rounded) = (path(0.0), path(0.5));
+        let (plain_end, rounded_end) = (plain[39], rounded[39]);
+        assert!(plain_end.0 > 6.9 && plain_end.1 < 1.05, "stuck in the corner");
+        assert!(rounded_end.1 > plain_end.1 + 0.3, "curved away from the north wall");
+        // the same until the east wall comes within range
+        assert_eq!(plain[20], rounded[20]);
+    }
+
+    #[test]
+    fn standing_up_is_blocked_under_a_low_ceiling() {
+        let mut world = world("######\n#.--.#\n######", 2.5, 1.5, FRAC_PI_2);
+        world.seat.player.crouch = 1.0;
+        for _ in 0..30 {
+            world.step(&InputState::default(), 1.0 / 60.0);
+        }
+        assert_eq!(world.seat.player.crouch, 1.0);
+
+        // out from under it the player stands back up, and can't walk back in standing
+        world.seat.player.player_x = 4.5;
+        for _ in 0..30 {
+            world.step(&InputState::default(), 1.0 / 60.0);
+        }
+        assert_eq!(world.seat.player.crouch, 0.0);
+        world.move_player(-0.6, 0.0);
+        assert_eq!(world.seat.player.player_x, 4.5);
+    }
+
+    #[test]
+    fn door_trigger_opens_only_once_the_flag_is_set() {
+        let mut world = world("#####\n#...#\n#####\n#...#\n#####", 1.5, 1.5, 0.0);
+        world.map.triggers.push(Trigger::repeating(2, 1, TriggerAction::Open { x: 2, y: 2 }).requiring("key", 1));
+        world.enter_cell(2, 1);
+        assert!(world.map.is_wall(2.5, 2.5));
+
+        world.state.set("key", 1);
+        world.enter_cell(2, 1);
+        assert!(!world.map.is_wall(2.5, 2.5));
+    }
+
+    #[test]
+    fn loading_a_map_clears_the_state() {
+        let mut world = world(&open_room(5), 2.5, 2.5, 0.0);
+        world.state.set("key", 1);
+        world.load_map(Map::parse(&open_room(5)).unwrap());
+        assert!(!world.state.has("key"));
+    }
+
+    #[test]
+    fn fast_move_stops_at_a_thin_wall() {
+        let mut world = world("#########\n#...#...#\n#########", 1.5, 1.5, FRAC_PI_2);
+        world.move_player_in_substeps(4.0, 0.0);
+        assert!(world.seat.player.player_x < 4.0, "{}", world.seat.player.player_x);
+    }
+
+    #[test]
+    fn ground_tracks_a_lift_while_standing_on_it() {
+        let mut world = world(&open_room(5), 2.5, 2.5, 0.0);
+        let mut lift = Lift::new(2, 2, 0.0, 0.4);
+        lift.wait = 0.0;
+        world.map.lifts.push(lift);
+        let mut heights = Vec::new();
+        for _ in 0..60 {
+            world.step(&InputState::default(), 1.0 / 60.0);
+            let lift = world.map.lift(2, 2).unwrap().height();
+            assert!((world.ground_height() - lift).abs() < 1e-9);
+            assert_eq!(world.seat.player.jump_height, 0.0);
+            heights.push(lift);
+        }
+        assert!(heights.last().unwrap() > &0.2, "{:?}", heights.last());
+    }
+
+    #[test]
+    fn mouse_spike_turns_no_further_than_the_max() {
+        let mut world = world(&open_room(5), 2.5, 2.5, 0.0);
+        world.step(&InputState { mouse_turn: 15.0, ..InputState::default() }, 1.0 / 60.0);
+        assert!((world.seat.player.vision_angle - world.max_turn).abs() < 1e-9);
+
+        world.step(&InputState { mouse_turn: -9.0, ..InputState::default() }, 1.0 / 60.0);
+        assert!(world.seat.player.vision_angle.abs() < 1e-9);
+    }
+
+    #[test]
+    fn slower_strafe_covers_less_ground_than_walking() {
+        let moved = |input: InputState| {
+            let mut world = world(&open_room(21), 10.5, 10.5, 0.0);
+            world.seat.player.forward_speed = 3.0;
+            world.seat.player.strafe_speed = 1.5;
+            for _ in 0..30 {
+                world.step(&input, 1.0 / 60.0);
+            }
+            (world.seat.player.player_x - 10.5).hypot(world.seat.player.player_y - 10.5)
+        };
+        let forward = moved(InputState { walk: 1.0, ..InputState::default() });
+        let sideways = moved(InputState { strafe: 1.0, ..InputState::default() });
+        assert!((forward - 1.5).abs() < 1e-9, "{}", forward);
+        assert!((sideways - 0.75).abs() < 1e-9, "{}", sideways);
+    }
+
+    #[test]
+    fn autorun_walks_on_with_no_input_until_a_wall() {
+        let mut world = world(&open_room(10), 3.5, 8.5, PI);
+        world.seat.actions.press(Action::Autorun);
+        let mut last_y = world.seat.player.player_y;
+        let mut steps = 0;
+        loop {
+            world.step(&InputState::default(), 1.0 / 60.0);
+            if world.seat.player.player_y >= last_y {
+                break;
+            }
+            last_y = world.seat.player.player_y;
+            steps += 1;
+            assert!(steps < 100, "never reached the wall");
+        }
+        assert!(steps > 1);
+        assert!(world.seat.autorun);
+        // stopped against the wall along the top
+        assert!(world.seat.player.player_y < 1.5, "{}", world.seat.player.player_y);
+    }
+
+    #[test]
+    fn zoomed_in_step_covers_less_ground_when_enabled() {
+        let step_while_zoomed = |slows: bool| {
+            let mut world = world(&open_room(21), 10.5, 10.5, 0.0);
+            world.zoom_slows_movement = slows;
+            let zoom = InputState { zoom: true, ..InputState::default() };
+            for _ in 0..120 {
+                world.step(&zoom, 1.0 / 60.0);
+            }
+            assert!(world.life.fov_angle < world.life.base_fov_angle);
+            let before = world.seat.player.player_y;
+            world.step(&InputState { walk: 1.0, ..zoom }, 1.0 / 60.0);
+            world.seat.player.player_y - before
+        };
+        let (slowed, raw) = (step_while_zoomed(true), step_while_zoomed(false));
+        assert!((raw - 0.2).abs() < 1e-9, "{}", raw);
+        assert!(slowed < raw / 2.0, "{}", slowed);
+    }
+}